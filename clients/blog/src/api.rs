@@ -0,0 +1,193 @@
+//! Talks to the blog API.
+
+use std::fmt::{self, Display, Formatter};
+
+use blog_api::{
+    auth::{LoginRequest, Session},
+    error::ErrorBody,
+    passkeys::{LoginFinish, LoginStart, Passkey, RegistrationFinish, RegistrationStart},
+    paths,
+    posts::{ListParams, Post, PostInput, PostPage, Series, TagCount},
+    revisions::{DiffParams, Revision, RevisionDiff, RevisionSummary},
+    search::SearchHit,
+};
+use gloo_net::http::{Request, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The API answered, but with an error.
+    Api(ErrorBody),
+    /// The request never made it, or the response made no sense.
+    Network(String),
+}
+impl Error {
+    /// Whether the API said there's nothing there.
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Error::Api(ErrorBody { status: 404, .. }))
+    }
+
+    /// Whether the API wants a login first.
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self, Error::Api(ErrorBody { status: 401, .. }))
+    }
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::Api(ErrorBody { message: Some(message), .. }) => write!(f, "{message}"),
+            Error::Api(ErrorBody { status, error, .. }) => write!(f, "{status} {error}"),
+            Error::Network(e) => write!(f, "Couldn't reach the server: {e}"),
+        }
+    }
+}
+impl From<gloo_net::Error> for Error {
+    fn from(e: gloo_net::Error) -> Self {
+        Error::Network(e.to_string())
+    }
+}
+
+/// Turns error statuses into [`Error::Api`], falling back to the status line if the body isn't
+/// an [`ErrorBody`] (say, a proxy's error page).
+async fn check(response: Response) -> Result<Response, Error> {
+    if response.ok() {
+        return Ok(response);
+    }
+    let body = response.json::<ErrorBody>().await.unwrap_or_else(|_| ErrorBody {
+        status: response.status(),
+        error: response.status_text(),
+        message: None,
+    });
+    Err(Error::Api(body))
+}
+
+async fn fetch<T: DeserializeOwned>(request: Result<Request, gloo_net::Error>) -> Result<T, Error> {
+    let response = check(request?.send().await?).await?;
+    Ok(response.json().await?)
+}
+
+fn get(url: &str) -> RequestBuilder {
+    Request::get(url).header("accept", "application/json")
+}
+
+/// Anything that changes something has to come with the session's CSRF token.
+fn authenticated(request: RequestBuilder, session: &Session) -> RequestBuilder {
+    request.header("accept", "application/json").header("x-csrf-token", session.csrf_token.as_str())
+}
+
+/// `GET /api/posts`
+pub async fn list_posts(params: &ListParams) -> Result<PostPage, Error> {
+    let limit = params.limit.map(|limit| limit.to_string());
+    let query = [
+        ("after", params.after.as_ref().map(|after| after.as_str())),
+        ("limit", limit.as_deref()),
+        ("tag", params.tag.as_deref()),
+    ];
+    let query = query.into_iter().filter_map(|(key, value)| Some((key, value?)));
+    fetch(get(paths::POSTS).query(query).build()).await
+}
+
+/// `GET /api/series/:slug`
+pub async fn get_series(slug: &str) -> Result<Series, Error> {
+    fetch(get(paths::series(slug).as_str()).build()).await
+}
+
+/// `GET /api/tags`
+pub async fn list_tags() -> Result<Vec<TagCount>, Error> {
+    fetch(get(paths::TAGS).build()).await
+}
+
+/// `GET /api/search`
+pub async fn search(q: &str) -> Result<Vec<SearchHit>, Error> {
+    fetch(get(paths::SEARCH).query([("q", q)]).build()).await
+}
+
+/// `GET /api/posts/:slug`
+pub async fn get_post(slug: &str) -> Result<Post, Error> {
+    fetch(get(paths::post(slug).as_str()).build()).await
+}
+
+/// `GET /api/posts/:slug/edit`
+pub async fn get_post_to_edit(slug: &str) -> Result<Post, Error> {
+    fetch(get(paths::edit(slug).as_str()).build()).await
+}
+
+/// `POST /api/posts`
+pub async fn create_post(input: &PostInput, session: &Session) -> Result<Post, Error> {
+    fetch(authenticated(Request::post(paths::POSTS), session).json(input)).await
+}
+
+/// `PUT /api/posts/:slug`
+pub async fn update_post(slug: &str, input: &PostInput, session: &Session) -> Result<Post, Error> {
+    fetch(authenticated(Request::put(paths::post(slug).as_str()), session).json(input)).await
+}
+
+/// `DELETE /api/posts/:slug`
+pub async fn delete_post(slug: &str, session: &Session) -> Result<(), Error> {
+    check(authenticated(Request::delete(paths::post(slug).as_str()), session).send().await?).await?;
+    Ok(())
+}
+
+/// `GET /api/posts/:slug/revisions`
+pub async fn list_revisions(slug: &str) -> Result<Vec<RevisionSummary>, Error> {
+    fetch(get(paths::revisions(slug).as_str()).build()).await
+}
+
+/// `GET /api/posts/:slug/revisions/:number`
+pub async fn get_revision(slug: &str, number: u32) -> Result<Revision, Error> {
+    fetch(get(paths::revision(slug, number).as_str()).build()).await
+}
+
+/// `GET /api/posts/:slug/diff`
+pub async fn diff_revisions(slug: &str, params: &DiffParams) -> Result<RevisionDiff, Error> {
+    let from = params.from.map(|from| from.to_string());
+    let to = params.to.map(|to| to.to_string());
+    let query = [("from", from.as_deref()), ("to", to.as_deref())];
+    let query = query.into_iter().filter_map(|(key, value)| Some((key, value?)));
+    fetch(get(paths::diff(slug).as_str()).query(query).build()).await
+}
+
+/// `POST /api/login`
+pub async fn login(request: &LoginRequest) -> Result<Session, Error> {
+    fetch(Request::post(paths::LOGIN).json(request)).await
+}
+
+/// `GET /api/session`, `None` when nobody's logged in.
+pub async fn session() -> Result<Option<Session>, Error> {
+    match fetch(get(paths::SESSION).build()).await {
+        Ok(session) => Ok(Some(session)),
+        Err(e) if e.is_unauthorized() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// `DELETE /api/session`
+pub async fn logout(session: &Session) -> Result<(), Error> {
+    check(authenticated(Request::delete(paths::SESSION), session).send().await?).await?;
+    Ok(())
+}
+
+/// `GET /api/passkeys`
+pub async fn list_passkeys() -> Result<Vec<Passkey>, Error> {
+    fetch(get(paths::PASSKEYS).build()).await
+}
+
+/// `POST /api/passkeys/register`
+pub async fn start_passkey_registration(session: &Session) -> Result<RegistrationStart, Error> {
+    fetch(authenticated(Request::post(paths::PASSKEY_REGISTER), session).build()).await
+}
+
+/// `POST /api/passkeys/register/finish`
+pub async fn finish_passkey_registration(request: &RegistrationFinish, session: &Session) -> Result<Passkey, Error> {
+    fetch(authenticated(Request::post(paths::PASSKEY_REGISTER_FINISH), session).json(request)).await
+}
+
+/// `POST /api/passkeys/login`
+pub async fn start_passkey_login() -> Result<LoginStart, Error> {
+    fetch(Request::post(paths::PASSKEY_LOGIN).build()).await
+}
+
+/// `POST /api/passkeys/login/finish`
+pub async fn finish_passkey_login(request: &LoginFinish) -> Result<Session, Error> {
+    fetch(Request::post(paths::PASSKEY_LOGIN_FINISH).json(request)).await
+}
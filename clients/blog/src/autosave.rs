@@ -0,0 +1,90 @@
+//! Copies of unsaved work, kept in `localStorage` so that closing the tab doesn't lose it.
+//!
+//! Edits to existing posts are kept under their slug. New posts haven't got one yet, so they get a
+//! generated id until they're saved for the first time.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use web_sys::Storage;
+
+const PREFIX: &str = "blog-editor:";
+const NEW_PREFIX: &str = "blog-editor:new:";
+
+/// Where a local copy lives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Key {
+    /// An existing post, by slug.
+    Post(String),
+    /// A post that hasn't been saved to the server yet.
+    New(String),
+}
+impl Key {
+    /// A key for a post nobody's started on yet.
+    pub fn new_post() -> Self {
+        let now = js_sys::Date::now() as u64;
+        let nonce = (js_sys::Math::random() * 1e6) as u64;
+        Key::New(format!("{now:x}{nonce:x}"))
+    }
+
+    fn storage_key(&self) -> String {
+        match self {
+            Key::Post(slug) => format!("{PREFIX}{slug}"),
+            Key::New(id) => format!("{NEW_PREFIX}{id}"),
+        }
+    }
+}
+
+/// A local copy, and when it was made.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Saved<T> {
+    pub value: T,
+    /// Milliseconds since the epoch.
+    pub saved_at: f64,
+}
+impl<T> Saved<T> {
+    /// When the copy was made, in the reader's own format.
+    pub fn saved_at_string(&self) -> String {
+        js_sys::Date::new(&self.saved_at.into()).to_locale_string("default", &js_sys::Object::new()).into()
+    }
+}
+
+/// Nothing works without storage (or a window, when rendering on the server), but nothing breaks
+/// either.
+fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+pub fn load<T: DeserializeOwned>(key: &Key) -> Option<Saved<T>> {
+    let json = storage()?.get_item(key.storage_key().as_str()).ok()??;
+    serde_json::from_str(json.as_str()).ok()
+}
+
+pub fn store<T: Serialize>(key: &Key, value: &T) {
+    let Some(storage) = storage() else {
+        return;
+    };
+    let saved = Saved { value, saved_at: js_sys::Date::now() };
+    if let Ok(json) = serde_json::to_string(&saved) {
+        // Storage being full isn't worth interrupting anyone over.
+        let _ = storage.set_item(key.storage_key().as_str(), json.as_str());
+    }
+}
+
+pub fn remove(key: &Key) {
+    if let Some(storage) = storage() {
+        let _ = storage.remove_item(key.storage_key().as_str());
+    }
+}
+
+/// The most recent copy of a post that never made it to the server.
+pub fn latest_new<T: DeserializeOwned>() -> Option<(Key, Saved<T>)> {
+    let storage = storage()?;
+    let length = storage.length().ok()?;
+    (0..length)
+        .filter_map(|idx| storage.key(idx).ok().flatten())
+        .filter_map(|key| Some(Key::New(key.strip_prefix(NEW_PREFIX)?.to_owned())))
+        .filter_map(|key| {
+            let saved = load(&key)?;
+            Some((key, saved))
+        })
+        .max_by(|(_, a): &(Key, Saved<T>), (_, b)| a.saved_at.total_cmp(&b.saved_at))
+}
@@ -1,4 +1,33 @@
-use yew::prelude::*;
+use std::{rc::Rc, time::Duration};
+
+use blog_api::{
+    auth::Session,
+    error::ErrorBody,
+    posts::{is_valid_slug, Post, PostInput, PostSeries},
+    revisions::Revision,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{BeforeUnloadEvent, HtmlInputElement, HtmlTextAreaElement};
+use yew::{platform::{spawn_local, time::sleep}, prelude::*};
+use yew_router::prelude::*;
+
+use crate::{
+    api,
+    autosave::{self, Key, Saved},
+    fetch::{use_fetch, Fetched},
+    passkeys::PasskeyPanel,
+    revisions::RevisionPanel,
+    schedule::{self, SchedulePicker},
+    Route,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// How long typing has to stop for before a local copy gets made.
+const AUTOSAVE_DELAY: Duration = Duration::from_secs(1);
 
 #[derive(Debug, PartialEq)]
 #[derive(Properties)]
@@ -7,10 +36,551 @@ pub struct Props {
     pub slug: Option<String>,
 }
 
+/// Writes new posts, or edits the post at `slug`.
 #[function_component]
 pub fn Editor(props: &Props) -> Html {
-    html! {
-        <>{"under construction: "}{props.slug.as_ref().map(String::as_ref).unwrap_or("")}</>
+    let navigator = use_navigator();
+    // Saving can move the editor to another slug. Keep what was saved around so that it doesn't
+    // have to be fetched right back.
+    let just_saved = use_mut_ref(|| None::<Post>);
+    let post = {
+        let just_saved = just_saved.clone();
+        use_fetch(props.slug.clone(), |_| None, move |slug| async move {
+            let Some(slug) = slug else {
+                return Ok(None);
+            };
+            if let Some(post) = just_saved.borrow_mut().take().filter(|post| post.slug == slug) {
+                return Ok(Some(post));
+            }
+            api::get_post_to_edit(slug.as_str()).await.map(Some)
+        })
+    };
+
+    let on_saved = {
+        let slug = props.slug.clone();
+        Callback::from(move |post: Post| {
+            if slug.as_deref() == Some(post.slug.as_str()) {
+                return;
+            }
+            let route = Route::EditExisting { slug: post.slug.clone() };
+            *just_saved.borrow_mut() = Some(post);
+            if let Some(navigator) = navigator.as_ref() {
+                navigator.replace(&route);
+            }
+        })
+    };
+
+    match &*post {
+        Fetched::Loading => html! {
+            <div class="editor">
+                <p>{"Loading post..."}</p>
+            </div>
+        },
+        Fetched::Failed(e) => html! {
+            <div class="editor">
+                <p class="editor-error">{"Couldn't load the post: "}{e}</p>
+            </div>
+        },
+        Fetched::Loaded(post) => html! {
+            <Form key={post.as_ref().map(|post| post.slug.clone()).unwrap_or_default()} post={post.clone()} {on_saved} />
+        },
     }
 }
 
+/// What's in the form, as typed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Fields {
+    title: String,
+    slug: String,
+    /// Comma separated.
+    tags: String,
+    summary: String,
+    body: String,
+    /// New posts name themselves after their title until the slug gets edited by hand.
+    slug_touched: bool,
+    /// The title of the series the post is in, or nothing.
+    #[serde(default)]
+    series: String,
+    /// Follows the series title, unless the series was named something else elsewhere.
+    #[serde(default)]
+    series_slug: String,
+    #[serde(default)]
+    series_part: String,
+}
+impl Fields {
+    fn from_post(post: &Post) -> Self {
+        Self {
+            title: post.title.clone(),
+            slug: post.slug.clone(),
+            tags: post.tags.join(", "),
+            summary: post.summary.clone().unwrap_or_default(),
+            body: post.body.clone(),
+            slug_touched: true,
+            ..Self::default()
+        }.with_series(post.series.as_ref())
+    }
+
+    fn from_revision(revision: &Revision) -> Self {
+        Self {
+            title: revision.title.clone(),
+            slug: revision.slug.clone(),
+            tags: revision.tags.join(", "),
+            summary: revision.summary.clone().unwrap_or_default(),
+            body: revision.body.clone(),
+            slug_touched: true,
+            ..Self::default()
+        }.with_series(revision.series.as_ref())
+    }
+
+    fn with_series(self, series: Option<&PostSeries>) -> Self {
+        match series {
+            Some(series) => Self {
+                series: series.title.clone(),
+                series_slug: series.slug.clone(),
+                series_part: series.part.to_string(),
+                ..self
+            },
+            None => self,
+        }
+    }
+
+    /// The series as typed, `None` if there's no series or its part isn't a number.
+    fn post_series(&self) -> Option<PostSeries> {
+        let title = self.series.trim();
+        if title.is_empty() {
+            return None;
+        }
+        Some(PostSeries {
+            slug: self.series_slug.clone(),
+            title: title.to_owned(),
+            part: self.series_part.trim().parse().ok()?,
+        })
+    }
+
+    fn input(&self, draft: bool, publish_at: Option<DateTime<Utc>>) -> PostInput {
+        PostInput {
+            slug: self.slug.trim().to_owned(),
+            title: self.title.trim().to_owned(),
+            body: self.body.clone(),
+            summary: Some(self.summary.trim().to_owned()).filter(|summary| !summary.is_empty()),
+            tags: self.tags.split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            series: self.post_series(),
+            draft,
+            publish_at,
+        }
+    }
+
+    /// Catches what the API would refuse anyway, before bothering it.
+    fn check(&self) -> Errors {
+        Errors {
+            title: self.title.trim().is_empty().then(|| "Posts need a title.".to_owned()),
+            slug: (!is_valid_slug(self.slug.trim()))
+                .then(|| "Use lowercase letters, digits and dashes, without a dash at either end.".to_owned()),
+            series: if self.series.trim().is_empty() {
+                None
+            } else if !is_valid_slug(self.series_slug.as_str()) {
+                Some("Series names need a letter or a digit in them.".to_owned())
+            } else if self.post_series().is_none_or(|series| series.part == 0) {
+                Some("Parts are numbered from 1.".to_owned())
+            } else {
+                None
+            },
+            general: None,
+        }
+    }
+}
+
+/// A change to one of the fields.
+enum Edit {
+    Title(String),
+    Slug(String),
+    Tags(String),
+    Summary(String),
+    Series(String),
+    SeriesPart(String),
+    Body(String),
+    /// Brings back a local copy or an earlier revision.
+    Restore(Fields),
+}
+impl Reducible for Fields {
+    type Action = Edit;
+
+    fn reduce(self: Rc<Self>, edit: Edit) -> Rc<Self> {
+        let mut fields = (*self).clone();
+        match edit {
+            Edit::Title(title) => {
+                if !fields.slug_touched {
+                    fields.slug = slug_from_title(title.as_str());
+                }
+                fields.title = title;
+            },
+            Edit::Slug(slug) => {
+                fields.slug = slug;
+                fields.slug_touched = true;
+            },
+            Edit::Tags(tags) => fields.tags = tags,
+            Edit::Summary(summary) => fields.summary = summary,
+            Edit::Series(series) => {
+                if fields.series_slug.is_empty() || fields.series_slug == slug_from_title(fields.series.as_str()) {
+                    fields.series_slug = slug_from_title(series.as_str());
+                }
+                fields.series = series;
+            },
+            Edit::SeriesPart(part) => fields.series_part = part,
+            Edit::Body(body) => fields.body = body,
+            Edit::Restore(restored) => fields = restored,
+        }
+        Rc::new(fields)
+    }
+}
+
+/// Problems to point out next to the fields they're about.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Errors {
+    title: Option<String>,
+    slug: Option<String>,
+    series: Option<String>,
+    general: Option<String>,
+}
+impl Errors {
+    fn is_empty(&self) -> bool {
+        self == &Errors::default()
+    }
+
+    fn from_api(e: api::Error) -> Self {
+        match e {
+            api::Error::Api(ErrorBody { status: 409 | 422, message, .. }) => Errors {
+                slug: Some(message.unwrap_or_else(|| "That slug can't be used.".to_owned())),
+                ..Errors::default()
+            },
+            e if e.is_unauthorized() => Errors {
+                general: Some("The login ran out. Log in again in another tab, then save again.".to_owned()),
+                ..Errors::default()
+            },
+            e => Errors { general: Some(e.to_string()), ..Errors::default() },
+        }
+    }
+}
+
+/// Turns a title into a slug, for new posts whose slug hasn't been touched.
+fn slug_from_title(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_owned()
+}
+
+#[derive(Debug, PartialEq)]
+#[derive(Properties)]
+struct FormProps {
+    /// The post being edited, or nothing for a new post.
+    post: Option<Post>,
+    on_saved: Callback<Post>,
+}
+
+/// Where the local copy goes, and whatever was left there last time.
+fn autosaved(post: Option<&Post>) -> (Key, Option<Saved<Fields>>) {
+    match post {
+        Some(post) => {
+            let key = Key::Post(post.slug.clone());
+            let saved = autosave::load(&key);
+            (key, saved)
+        },
+        // Carry on with the last new post that never got saved, if there is one.
+        None => match autosave::latest_new() {
+            Some((key, saved)) => (key, Some(saved)),
+            None => (Key::new_post(), None),
+        },
+    }
+}
+
+/// Warns before leaving the page while `dirty`.
+#[hook]
+fn use_unload_warning(dirty: bool) {
+    use_effect_with(dirty, |dirty| {
+        let window = web_sys::window().filter(|_| *dirty);
+        let warning = window.as_ref().map(|window| {
+            let warning = Closure::<dyn Fn(BeforeUnloadEvent)>::new(|e: BeforeUnloadEvent| {
+                e.prevent_default();
+                // Older browsers only go by this.
+                e.set_return_value("There are unsaved changes.");
+            });
+            window.set_onbeforeunload(Some(warning.as_ref().unchecked_ref()));
+            warning
+        });
+        move || {
+            if let Some(window) = window {
+                window.set_onbeforeunload(None);
+            }
+            drop(warning);
+        }
+    });
+}
+
+#[function_component]
+fn Form(props: &FormProps) -> Html {
+    let session = use_context::<Session>().expect("the editor is only shown inside `RequireLogin`");
+    let navigator = use_navigator();
+    let fields = use_reducer(|| props.post.as_ref().map(Fields::from_post).unwrap_or_default());
+    // What the server has, to tell whether there's anything unsaved.
+    let baseline = use_state(|| (*fields).clone());
+    // The slug the post is saved under, which is where updates go.
+    let saved_slug = use_mut_ref(|| props.post.as_ref().map(|post| post.slug.clone()));
+    let draft = use_state(|| props.post.as_ref().is_none_or(|post| post.draft));
+    let published_at = use_state(|| props.post.as_ref().and_then(|post| post.published_at));
+    let errors = use_state(Errors::default);
+    let saving = use_state(|| false);
+    // Counts saves, for the history to keep up with.
+    let saves = use_state(|| 0_u32);
+
+    let autosaved = use_memo((), |_| autosaved(props.post.as_ref()));
+    let key = use_mut_ref(|| autosaved.0.clone());
+    // Only worth offering if it's any different.
+    let leftover = use_state(|| autosaved.1.clone().filter(|saved| saved.value != *fields));
+    // Bumped on every change, so that only the last of a burst of changes gets saved locally.
+    let generation = use_mut_ref(|| 0_u64);
+
+    let dirty = *fields != *baseline;
+    use_unload_warning(dirty);
+    {
+        let key = key.clone();
+        use_effect_with(((*fields).clone(), (*baseline).clone()), move |(fields, baseline)| {
+            let ticket = {
+                let mut generation = generation.borrow_mut();
+                *generation += 1;
+                *generation
+            };
+            if fields == baseline {
+                return;
+            }
+            let fields = fields.clone();
+            spawn_local(async move {
+                sleep(AUTOSAVE_DELAY).await;
+                if *generation.borrow() == ticket {
+                    autosave::store(&key.borrow(), &fields);
+                }
+            });
+        });
+    }
+
+    let on_restore = {
+        let fields = fields.dispatcher();
+        let leftover = leftover.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(saved) = (*leftover).clone() {
+                fields.dispatch(Edit::Restore(saved.value));
+            }
+            leftover.set(None);
+        })
+    };
+    let on_revision = {
+        let fields = fields.dispatcher();
+        Callback::from(move |revision: Revision| fields.dispatch(Edit::Restore(Fields::from_revision(&revision))))
+    };
+    let on_discard = {
+        let key = key.clone();
+        let leftover = leftover.clone();
+        Callback::from(move |_: MouseEvent| {
+            autosave::remove(&key.borrow());
+            leftover.set(None);
+        })
+    };
+
+    let preview = use_memo(fields.body.clone(), |body| blog_markdown::render(body.as_str()).html);
+
+    let on_input = |edit: fn(String) -> Edit| {
+        let fields = fields.dispatcher();
+        Callback::from(move |e: InputEvent| fields.dispatch(edit(e.target_unchecked_into::<HtmlInputElement>().value())))
+    };
+    let on_body = {
+        let fields = fields.dispatcher();
+        Callback::from(move |e: InputEvent| {
+            fields.dispatch(Edit::Body(e.target_unchecked_into::<HtmlTextAreaElement>().value()));
+        })
+    };
+
+    // Saves, leaving the post a draft or not, and publishing it at the given time if there is one.
+    let save = {
+        let session = session.clone();
+        let fields = fields.clone();
+        let baseline = baseline.clone();
+        let leftover = leftover.clone();
+        let saves = saves.clone();
+        let draft = draft.clone();
+        let published_at = published_at.clone();
+        let errors = errors.clone();
+        let saving = saving.clone();
+        let on_saved = props.on_saved.clone();
+        Callback::from(move |(as_draft, publish_at): (bool, Option<DateTime<Utc>>)| {
+            let checked = fields.check();
+            if !checked.is_empty() {
+                errors.set(checked);
+                return;
+            }
+            errors.set(Errors::default());
+            saving.set(true);
+
+            let input = fields.input(as_draft, publish_at);
+            let snapshot = (*fields).clone();
+            let saved_slug = saved_slug.clone();
+            let key = key.clone();
+            let baseline = baseline.clone();
+            let leftover = leftover.clone();
+            let saves = saves.clone();
+            let draft = draft.clone();
+            let published_at = published_at.clone();
+            let errors = errors.clone();
+            let saving = saving.clone();
+            let on_saved = on_saved.clone();
+            let session = session.clone();
+            spawn_local(async move {
+                let existing = saved_slug.borrow().clone();
+                let result = match existing {
+                    Some(slug) => api::update_post(slug.as_str(), &input, &session).await,
+                    None => api::create_post(&input, &session).await,
+                };
+                saving.set(false);
+                match result {
+                    Ok(post) => {
+                        // The server has it now, so the local copy can go.
+                        autosave::remove(&key.borrow());
+                        *key.borrow_mut() = Key::Post(post.slug.clone());
+                        autosave::remove(&key.borrow());
+                        leftover.set(None);
+                        baseline.set(snapshot);
+                        saves.set(*saves + 1);
+                        *saved_slug.borrow_mut() = Some(post.slug.clone());
+                        draft.set(post.draft);
+                        published_at.set(post.published_at);
+                        on_saved.emit(post);
+                    },
+                    Err(e) => errors.set(Errors::from_api(e)),
+                }
+            });
+        })
+    };
+    let on_save = {
+        let save = save.clone();
+        let draft = *draft;
+        let published_at = *published_at;
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            // Leaves any schedule as it is, leaving the date out would publish a scheduled post.
+            save.emit((draft, if draft { None } else { published_at }));
+        })
+    };
+    // Scheduled posts go live right away too. The server picks the time, so a wrong clock here
+    // can't schedule the post instead.
+    let on_publish = {
+        let save = save.clone();
+        Callback::from(move |_: MouseEvent| save.emit((false, None)))
+    };
+    let on_schedule = {
+        let save = save.clone();
+        Callback::from(move |at: DateTime<Utc>| save.emit((false, Some(at))))
+    };
+    let on_unpublish = Callback::from(move |_: MouseEvent| save.emit((true, None)));
+    let scheduled = published_at.filter(|at| !*draft && *at > schedule::now());
+    let on_logout = {
+        let errors = errors.clone();
+        Callback::from(move |_: MouseEvent| {
+            let session = session.clone();
+            let navigator = navigator.clone();
+            let errors = errors.clone();
+            spawn_local(async move {
+                match api::logout(&session).await {
+                    Ok(()) => {
+                        if let Some(navigator) = navigator {
+                            navigator.push(&Route::PostList);
+                        }
+                    },
+                    Err(e) => errors.set(Errors::from_api(e)),
+                }
+            });
+        })
+    };
+
+    html! {
+        <form class="editor" onsubmit={on_save}>
+            if let Some(saved) = leftover.as_ref() {
+                <div class="editor-recovery">
+                    <span>{"There are unsaved changes from "}{saved.saved_at_string()}{"."}</span>
+                    <button type="button" onclick={on_restore}>{"Restore"}</button>
+                    <button type="button" onclick={on_discard}>{"Discard"}</button>
+                </div>
+            }
+            <div class="editor-title">
+                <input type="text" placeholder="Title" value={fields.title.clone()} oninput={on_input(Edit::Title)} />
+            </div>
+            if let Some(error) = errors.title.as_ref() {
+                <p class="editor-error">{error}</p>
+            }
+            <div class="editor-slug">
+                <label for="editor-slug">{"/blog/post/"}</label>
+                <input id="editor-slug" type="text" placeholder="slug" value={fields.slug.clone()} oninput={on_input(Edit::Slug)} />
+            </div>
+            if let Some(error) = errors.slug.as_ref() {
+                <p class="editor-error">{error}</p>
+            }
+            <div class="editor-meta">
+                <input type="text" placeholder="Tags, separated by commas" value={fields.tags.clone()} oninput={on_input(Edit::Tags)} />
+                <input type="text" placeholder="Summary" value={fields.summary.clone()} oninput={on_input(Edit::Summary)} />
+            </div>
+            <div class="editor-meta">
+                <input type="text" placeholder="Series, if it's part of one" value={fields.series.clone()} oninput={on_input(Edit::Series)} />
+                <input class="editor-part" type="number" min="1" placeholder="Part" value={fields.series_part.clone()} oninput={on_input(Edit::SeriesPart)} />
+            </div>
+            if let Some(error) = errors.series.as_ref() {
+                <p class="editor-error">{error}</p>
+            }
+            <div class="editor-body">
+                <textarea placeholder="Write in Markdown..." value={fields.body.clone()} oninput={on_body} />
+                <div class="editor-preview post">
+                    <h1>{&fields.title}</h1>
+                    {Html::from_html_unchecked((*preview).clone().into())}
+                </div>
+            </div>
+            if let Some(error) = errors.general.as_ref() {
+                <p class="editor-error">{error}</p>
+            }
+            <div class="editor-actions">
+                <span class="editor-status">
+                    {match scheduled {
+                        _ if *draft => "Draft".to_owned(),
+                        Some(at) => format!("Scheduled for {}", at.format("%B %-d, %Y, %H:%M UTC")),
+                        None => "Published".to_owned(),
+                    }}
+                    if dirty {
+                        <span class="editor-dirty">{" · Unsaved changes"}</span>
+                    }
+                </span>
+                <button type="submit" disabled={*saving}>{"Save"}</button>
+                if *draft || scheduled.is_some() {
+                    <button type="button" disabled={*saving} onclick={on_publish}>
+                        {if *draft { "Publish" } else { "Publish now" }}
+                    </button>
+                }
+                if !*draft {
+                    <button type="button" disabled={*saving} onclick={on_unpublish}>{"Unpublish"}</button>
+                }
+                <button type="button" onclick={on_logout}>{"Log out"}</button>
+            </div>
+            if *draft || scheduled.is_some() {
+                <SchedulePicker {scheduled} disabled={*saving} {on_schedule} />
+            }
+            if let Some(post) = props.post.as_ref() {
+                <RevisionPanel slug={post.slug.clone()} saves={*saves} on_restore={on_revision} />
+            }
+            <PasskeyPanel />
+        </form>
+    }
+}
@@ -0,0 +1,102 @@
+use std::rc::Rc;
+
+use blog_api::{error::ErrorBody, posts::{Post, PostSeries}};
+use chrono::DateTime;
+use yew::Reducible;
+
+use super::{Edit, Errors, Fields};
+use crate::api;
+
+fn edit(fields: Fields, edits: impl IntoIterator<Item = Edit>) -> Fields {
+    let fields = edits.into_iter().fold(Rc::new(fields), Reducible::reduce);
+    (*fields).clone()
+}
+
+fn post(series: Option<PostSeries>) -> Post {
+    let date = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    Post {
+        slug: "saved".to_owned(),
+        title: "Saved".to_owned(),
+        body: "Words.".to_owned(),
+        html: "<p>Words.</p>".to_owned(),
+        toc: vec![],
+        reading_minutes: 1,
+        summary: None,
+        tags: vec!["a".to_owned(), "b".to_owned()],
+        series,
+        author: "Ben".to_owned(),
+        created_at: date,
+        updated_at: date,
+        published_at: None,
+        draft: true,
+        neighbors: Default::default(),
+        series_parts: vec![],
+    }
+}
+
+#[test]
+fn new_posts_name_themselves_after_their_title() {
+    let fields = edit(Fields::default(), [Edit::Title("Hello, World!".to_owned())]);
+    assert_eq!(fields.slug, "hello-world");
+
+    let fields = edit(fields, [Edit::Slug("greetings".to_owned()), Edit::Title("Hello again".to_owned())]);
+    assert_eq!((fields.title.as_str(), fields.slug.as_str()), ("Hello again", "greetings"));
+
+    // Saved posts keep their slug.
+    let fields = edit(Fields::from_post(&post(None)), [Edit::Title("Renamed".to_owned())]);
+    assert_eq!(fields.slug, "saved");
+}
+
+#[test]
+fn series_slugs_follow_their_title_until_named_elsewhere() {
+    let fields = edit(Fields::default(), [Edit::Series("Rust Tips".to_owned()), Edit::Series("Rust Tricks".to_owned())]);
+    assert_eq!(fields.series_slug, "rust-tricks");
+
+    let series = PostSeries { slug: "tips".to_owned(), title: "Rust Tips".to_owned(), part: 2 };
+    let fields = edit(Fields::from_post(&post(Some(series))), [Edit::Series("Rust Tricks".to_owned())]);
+    assert_eq!((fields.series_slug.as_str(), fields.series_part.as_str()), ("tips", "2"));
+}
+
+#[test]
+fn input_is_tidied_up() {
+    let fields = edit(Fields::default(), [
+        Edit::Title("  Spaced out  ".to_owned()),
+        Edit::Tags(" rust, ,web ,".to_owned()),
+        Edit::Summary("   ".to_owned()),
+        Edit::Series("Odds".to_owned()),
+        Edit::SeriesPart("first".to_owned()),
+    ]);
+    let input = fields.input(true, None);
+    assert_eq!((input.title.as_str(), input.slug.as_str()), ("Spaced out", "spaced-out"));
+    assert_eq!(input.tags, ["rust", "web"]);
+    assert_eq!(input.summary, None);
+    assert_eq!(input.series, None);
+
+    let input = edit(fields, [Edit::SeriesPart(" 3 ".to_owned())]).input(false, None);
+    assert_eq!(input.series, Some(PostSeries { slug: "odds".to_owned(), title: "Odds".to_owned(), part: 3 }));
+}
+
+#[test]
+fn checks_catch_what_the_api_would_refuse() {
+    assert!(Fields::from_post(&post(None)).check().is_empty());
+
+    let errors = Fields::default().check();
+    assert!(errors.title.is_some() && errors.slug.is_some() && errors.series.is_none(), "{errors:?}");
+
+    let fields = edit(Fields::from_post(&post(None)), [Edit::Series("Odds".to_owned()), Edit::SeriesPart("0".to_owned())]);
+    assert_eq!(fields.check().series.as_deref(), Some("Parts are numbered from 1."));
+    let fields = edit(fields, [Edit::Series("!!!".to_owned()), Edit::SeriesPart("1".to_owned())]);
+    assert_eq!(fields.check().series.as_deref(), Some("Series names need a letter or a digit in them."));
+}
+
+#[test]
+fn api_errors_land_next_to_their_field() {
+    let body = |status, message: &str| ErrorBody { status, error: String::new(), message: Some(message.to_owned()) };
+
+    let errors = Errors::from_api(api::Error::Api(body(409, "taken")));
+    assert_eq!(errors.slug.as_deref(), Some("taken"));
+    let errors = Errors::from_api(api::Error::Api(body(401, "log in")));
+    assert!(errors.general.unwrap().contains("Log in again"));
+    let errors = Errors::from_api(api::Error::Network("offline".to_owned()));
+    assert!(errors.general.unwrap().contains("offline"));
+}
@@ -0,0 +1,68 @@
+//! Loading things from the API from within components.
+
+use std::{future::Future, rc::Rc};
+
+use yew::{platform::spawn_local, prelude::*};
+
+use crate::{
+    api,
+    prefetched::Prefetched,
+    world::{take_prefetched, PrefetchedSlot},
+};
+
+/// Where a request is at.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fetched<T> {
+    Loading,
+    Loaded(T),
+    Failed(api::Error),
+}
+
+/// Fetches with `fetch` whenever `deps` change.
+///
+/// The first render starts from whatever `prefetched` takes from the server's data instead, which
+/// is how hydration ends up rendering the same thing the server did without a round trip.
+/// Responses for anything but the latest `deps` are dropped.
+#[hook]
+pub fn use_fetch<D, T, F, Fut>(
+    deps: D,
+    prefetched: impl FnOnce(&mut Prefetched) -> Option<T>,
+    fetch: F,
+) -> UseStateHandle<Fetched<T>>
+where
+    D: Clone + PartialEq + 'static,
+    T: 'static,
+    F: FnOnce(D) -> Fut + 'static,
+    Fut: Future<Output = Result<T, api::Error>> + 'static,
+{
+    let slot = use_context::<Rc<PrefetchedSlot>>();
+    let state = use_state(|| match take_prefetched(slot.as_deref(), prefetched) {
+        Some(value) => Fetched::Loaded(value),
+        None => Fetched::Loading,
+    });
+    let skip_first = use_mut_ref(|| matches!(*state, Fetched::Loaded(_)));
+    let latest = use_mut_ref(|| None::<D>);
+
+    {
+        let state = state.clone();
+        use_effect_with(deps, move |deps| {
+            if std::mem::take(&mut *skip_first.borrow_mut()) {
+                return;
+            }
+            *latest.borrow_mut() = Some(deps.clone());
+            state.set(Fetched::Loading);
+
+            let deps = deps.clone();
+            spawn_local(async move {
+                let result = fetch(deps.clone()).await;
+                if latest.borrow().as_ref() == Some(&deps) {
+                    state.set(match result {
+                        Ok(value) => Fetched::Loaded(value),
+                        Err(e) => Fetched::Failed(e),
+                    });
+                }
+            });
+        });
+    }
+    state
+}
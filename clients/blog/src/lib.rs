@@ -1,55 +1,144 @@
+pub mod api;
+pub mod prefetched;
+
+mod autosave;
+mod fetch;
 mod world;
 
 mod post_list;
 mod post;
+mod search;
+mod series;
 
 mod editor;
+mod login;
+mod passkeys;
+mod revisions;
+mod schedule;
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests;
 
+use std::rc::Rc;
+
+use chrono::{DateTime, Utc};
+#[cfg(feature = "hydration")]
 use wasm_bindgen::prelude::*;
 
 use yew::prelude::*;
-use yew_router::prelude::*;
+use yew_router::{history::AnyHistory, prelude::*};
+
+use prefetched::Prefetched;
+use world::PrefetchedSlot;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[derive(Routable)]
-enum Route {
+pub enum Route {
     #[at("/blog")]
     PostList,
     #[at("/blog/post/:slug")]
     Post { slug: String },
+    #[at("/blog/tag/:tag")]
+    Tag { tag: String },
+    #[at("/blog/series/:slug")]
+    Series { slug: String },
+    #[at("/blog/search")]
+    Search,
     #[at("/blog/editor/:slug")]
     EditExisting { slug: String },
     #[at("/blog/editor")]
     EditNew,
+    #[at("/blog/login")]
+    Login,
 }
 
 fn switch(route: Route) -> Html {
     match route {
         Route::PostList => html! {<post_list::PostList/>},
         Route::Post { slug } => html! {<post::Post slug={slug} />},
-        Route::EditNew => html! {<editor::Editor />},
-        Route::EditExisting { slug } => html! {<editor::Editor slug={slug} />},
+        Route::Tag { tag } => html! {<post_list::PostList tag={tag} />},
+        Route::Series { slug } => html! {<series::Series slug={slug} />},
+        Route::Search => html! {<search::Search />},
+        Route::EditNew => html! {
+            <login::RequireLogin><editor::Editor /></login::RequireLogin>
+        },
+        Route::EditExisting { slug } => html! {
+            <login::RequireLogin><editor::Editor slug={slug} /></login::RequireLogin>
+        },
+        Route::Login => html! {<login::Login />},
     }
 }
 
+/// Sets the title of the tab, the same way the server titles pages.
+fn set_title(title: &str) {
+    if let Some(document) = web_sys::window().and_then(|window| window.document()) {
+        document.set_title(format!("{title} | Benjamin Xu").as_str());
+    }
+}
+
+/// Dates as they're shown next to posts.
+fn format_date(date: DateTime<Utc>) -> String {
+    date.format("%B %-d, %Y").to_string()
+}
+
+#[derive(Debug, PartialEq)]
+#[derive(Properties)]
+struct Props {
+    /// The browser's history, or a stand-in when rendering on the server.
+    history: AnyHistory,
+    prefetched: Rc<PrefetchedSlot>,
+}
+
+// Both the server and the browser render this same tree, otherwise hydration falls over.
 #[function_component(Main)]
-fn app() -> Html {
+fn app(props: &Props) -> Html {
     html! {
-        <world::World>
-            <BrowserRouter>
+        <world::World prefetched={props.prefetched.clone()}>
+            <Router history={props.history.clone()}>
                 <Switch<Route> render={switch} />
-            </BrowserRouter>
+            </Router>
         </world::World>
     }
 }
 
+/// Renders the page at `path` and `query`, to be hydrated by [`init`] once it reaches the browser.
+#[cfg(feature = "ssr")]
+pub async fn render<Q>(path: String, query: Q, prefetched: Prefetched) -> String
+where
+    Q: serde::Serialize + Send + 'static,
+{
+    use yew_router::history::{History, MemoryHistory};
+
+    yew::ServerRenderer::<Main>::with_props(move || {
+        let history = MemoryHistory::new();
+        if history.replace_with_query(path.as_str(), &query).is_err() {
+            history.replace(path);
+        }
+        Props {
+            history: history.into(),
+            prefetched: Rc::new(PrefetchedSlot::new(prefetched)),
+        }
+    }).render().await
+}
+
+#[cfg(feature = "hydration")]
 #[wasm_bindgen(start)]
 pub fn init() -> Result<(), JsValue> {
-    yew::Renderer::<Main>::with_root(
-        web_sys::window().expect("`window` exists.")
-            .document().expect("`document` exists in `window`.")
-            .query_selector(".blog").expect("lookup to work.").expect("has a blog root.")
-            .dyn_into().expect("blog is an html element.")
-    ).render();
+    use yew_router::history::BrowserHistory;
+
+    let document = web_sys::window().expect("`window` exists.")
+        .document().expect("`document` exists in `window`.");
+    let prefetched = document.get_element_by_id(prefetched::ELEMENT_ID)
+        .and_then(|element| element.text_content())
+        .map(|json| Prefetched::from_script_json(json.as_str()))
+        .unwrap_or_default();
+    yew::Renderer::<Main>::with_root_and_props(
+        document.query_selector(".blog").expect("lookup to work.").expect("has a blog root.")
+            .dyn_into().expect("blog is an html element."),
+        Props {
+            history: BrowserHistory::new().into(),
+            prefetched: Rc::new(PrefetchedSlot::new(prefetched)),
+        },
+    ).hydrate();
     Ok(())
 }
@@ -0,0 +1,134 @@
+//! Logging in, and keeping everyone else out of the editor.
+
+use blog_api::auth::{LoginRequest, Session};
+use serde::{Deserialize, Serialize};
+use web_sys::HtmlInputElement;
+use yew::{platform::spawn_local, prelude::*};
+use yew_router::prelude::*;
+
+use crate::{api, fetch::{use_fetch, Fetched}, passkeys, set_title, Route};
+
+/// Query parameters of the login page.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct LoginParams {
+    /// Where to go once logged in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+#[derive(Properties)]
+pub struct RequireLoginProps {
+    pub children: Html,
+}
+
+/// Only shows its children to whoever's logged in, and hands them the [`Session`] through context.
+/// Everyone else gets sent to log in, and back here afterwards.
+#[function_component]
+pub fn RequireLogin(props: &RequireLoginProps) -> Html {
+    let session = use_fetch((), |_| None, |_| api::session());
+    let navigator = use_navigator();
+    let location = use_location();
+    use_effect_with(matches!(*session, Fetched::Loaded(None)), move |logged_out| {
+        if !*logged_out {
+            return;
+        }
+        let next = location.map(|location| location.path().to_owned());
+        if let Some(navigator) = navigator {
+            let _ = navigator.replace_with_query(&Route::Login, &LoginParams { next });
+        }
+    });
+
+    match &*session {
+        Fetched::Loading | Fetched::Loaded(None) => html! {
+            <div class="editor">
+                <p>{"Checking who's there..."}</p>
+            </div>
+        },
+        Fetched::Failed(e) => html! {
+            <div class="editor">
+                <p class="editor-error">{"Couldn't check whether you're logged in: "}{e}</p>
+            </div>
+        },
+        Fetched::Loaded(Some(session)) => html! {
+            <ContextProvider<Session> context={session.clone()}>
+                {props.children.clone()}
+            </ContextProvider<Session>>
+        },
+    }
+}
+
+#[function_component]
+pub fn Login() -> Html {
+    let navigator = use_navigator();
+    // Only ever back into the blog, wherever the link came from.
+    let next = use_location()
+        .and_then(|location| location.query::<LoginParams>().ok())
+        .and_then(|params| Route::recognize(params.next?.as_str()))
+        .filter(|route| *route != Route::Login)
+        .unwrap_or(Route::EditNew);
+    let username = use_node_ref();
+    let password = use_node_ref();
+    let error = use_state(|| None::<String>);
+    let busy = use_state(|| false);
+    use_effect_with((), |_| set_title("Log in"));
+
+    // Either way of logging in ends up here.
+    let finish = {
+        let error = error.clone();
+        let busy = busy.clone();
+        Callback::from(move |result: Result<Session, String>| match result {
+            Ok(_) => {
+                if let Some(navigator) = &navigator {
+                    navigator.replace(&next);
+                }
+            },
+            Err(e) => {
+                error.set(Some(e));
+                busy.set(false);
+            },
+        })
+    };
+
+    let onsubmit = {
+        let username = username.clone();
+        let password = password.clone();
+        let busy = busy.clone();
+        let finish = finish.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let value = |input: &NodeRef| input.cast::<HtmlInputElement>().map(|input| input.value()).unwrap_or_default();
+            let request = LoginRequest { username: value(&username), password: value(&password) };
+            busy.set(true);
+            let finish = finish.clone();
+            spawn_local(async move {
+                finish.emit(api::login(&request).await.map_err(|e| e.to_string()));
+            });
+        })
+    };
+    let on_passkey = {
+        let busy = busy.clone();
+        Callback::from(move |_: MouseEvent| {
+            busy.set(true);
+            let finish = finish.clone();
+            spawn_local(async move {
+                finish.emit(passkeys::login().await.map_err(|e| e.to_string()));
+            });
+        })
+    };
+
+    html! {
+        <form class="login" {onsubmit}>
+            <h1>{"Log in"}</h1>
+            <label for="login-username">{"Username"}</label>
+            <input id="login-username" type="text" autocomplete="username" required=true ref={username} />
+            <label for="login-password">{"Password"}</label>
+            <input id="login-password" type="password" autocomplete="current-password" required=true ref={password} />
+            if let Some(e) = error.as_ref() {
+                <p class="editor-error">{e}</p>
+            }
+            <button type="submit" disabled={*busy}>{"Log in"}</button>
+            <button type="button" class="login-passkey" disabled={*busy} onclick={on_passkey}>{"Log in with a passkey"}</button>
+        </form>
+    }
+}
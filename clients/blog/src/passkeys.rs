@@ -0,0 +1,237 @@
+//! Passkeys in the browser: registering them from the editor, and logging in with them.
+//!
+//! The WebAuthn option dictionaries are still unstable in `web-sys`, so they're built as plain
+//! objects. What comes back goes through the stable credential types.
+//!
+//! The editor lists passkeys and adds new ones in a [`PasskeyPanel`].
+
+use std::fmt::{self, Display, Formatter};
+
+use blog_api::{
+    auth::Session,
+    passkeys::{
+        AssertionResponse, Base64Url, CreationOptions, LoginFinish, Passkey, RegistrationFinish, RegistrationResponse,
+        RequestOptions,
+    },
+};
+use js_sys::{Array, ArrayBuffer, Object, Reflect, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    AuthenticatorAssertionResponse, AuthenticatorAttestationResponse, CredentialCreationOptions,
+    CredentialRequestOptions, CredentialsContainer, HtmlInputElement, PublicKeyCredential,
+};
+use yew::{platform::spawn_local, prelude::*};
+
+use crate::{api, fetch::{use_fetch, Fetched}, format_date};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Api(api::Error),
+    /// The browser couldn't or wouldn't make or use a passkey, say because the user cancelled.
+    Browser(String),
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::Api(e) => write!(f, "{e}"),
+            Error::Browser(e) => write!(f, "The browser didn't go along with it: {e}"),
+        }
+    }
+}
+impl From<api::Error> for Error {
+    fn from(e: api::Error) -> Self {
+        Error::Api(e)
+    }
+}
+impl From<JsValue> for Error {
+    fn from(e: JsValue) -> Self {
+        // DOMExceptions, like the NotAllowedError of a cancelled prompt, are errors too.
+        let message = e.dyn_ref::<js_sys::Error>().map(|e| String::from(e.message())).unwrap_or_else(|| format!("{e:?}"));
+        Error::Browser(message)
+    }
+}
+
+/// Makes a new passkey for whoever's logged in, calling it `name`.
+pub async fn register(name: String, session: &Session) -> Result<Passkey, Error> {
+    let start = api::start_passkey_registration(session).await?;
+    let response = create(&start.options).await?;
+    let finish = RegistrationFinish { ceremony: start.ceremony, name, response };
+    Ok(api::finish_passkey_registration(&finish, session).await?)
+}
+
+/// Logs in with whichever passkey the user picks.
+pub async fn login() -> Result<Session, Error> {
+    let start = api::start_passkey_login().await?;
+    let response = get(&start.options).await?;
+    Ok(api::finish_passkey_login(&LoginFinish { ceremony: start.ceremony, response }).await?)
+}
+
+fn credentials() -> Result<CredentialsContainer, Error> {
+    let window = web_sys::window().ok_or_else(|| Error::Browser("there's no window".to_owned()))?;
+    Ok(window.navigator().credentials())
+}
+
+fn object(entries: &[(&str, JsValue)]) -> JsValue {
+    let object = Object::new();
+    for (key, value) in entries {
+        Reflect::set(&object, &(*key).into(), value).expect("plain objects take any property");
+    }
+    object.into()
+}
+
+fn bytes(bytes: &Base64Url) -> JsValue {
+    Uint8Array::from(bytes.0.as_slice()).into()
+}
+
+fn from_buffer(buffer: &ArrayBuffer) -> Base64Url {
+    Base64Url(Uint8Array::new(buffer).to_vec())
+}
+
+fn descriptors(ids: &[Base64Url]) -> JsValue {
+    ids.iter()
+        .map(|id| object(&[("type", "public-key".into()), ("id", bytes(id))]))
+        .collect::<Array>()
+        .into()
+}
+
+/// `navigator.credentials.create`, for a passkey the user has to verify themselves to use.
+async fn create(options: &CreationOptions) -> Result<RegistrationResponse, Error> {
+    let algorithms = options.algorithms.iter()
+        .map(|alg| object(&[("type", "public-key".into()), ("alg", (*alg).into())]))
+        .collect::<Array>();
+    let public_key = object(&[
+        ("challenge", bytes(&options.challenge)),
+        ("rp", object(&[("id", options.rp_id.as_str().into()), ("name", options.rp_name.as_str().into())])),
+        ("user", object(&[
+            ("id", bytes(&options.user_id)),
+            ("name", options.user_name.as_str().into()),
+            ("displayName", options.user_display_name.as_str().into()),
+        ])),
+        ("pubKeyCredParams", algorithms.into()),
+        ("excludeCredentials", descriptors(options.exclude_credentials.as_slice())),
+        ("authenticatorSelection", object(&[
+            // Logging in doesn't ask for a username, so the authenticator has to remember the user.
+            ("residentKey", "required".into()),
+            ("requireResidentKey", true.into()),
+            ("userVerification", "required".into()),
+        ])),
+        ("attestation", "none".into()),
+        ("timeout", options.timeout_ms.into()),
+    ]);
+    let request = CredentialCreationOptions::new();
+    Reflect::set(&request, &"publicKey".into(), &public_key)?;
+
+    let credential: PublicKeyCredential = JsFuture::from(credentials()?.create_with_options(&request)?).await?.dyn_into()?;
+    let response: AuthenticatorAttestationResponse = credential.response().dyn_into().map_err(JsValue::from)?;
+    Ok(RegistrationResponse {
+        id: from_buffer(&credential.raw_id()),
+        client_data_json: from_buffer(&response.client_data_json()),
+        attestation_object: from_buffer(&response.attestation_object()),
+    })
+}
+
+/// `navigator.credentials.get`, with the user verifying themselves.
+async fn get(options: &RequestOptions) -> Result<AssertionResponse, Error> {
+    let public_key = object(&[
+        ("challenge", bytes(&options.challenge)),
+        ("rpId", options.rp_id.as_str().into()),
+        ("allowCredentials", descriptors(options.allow_credentials.as_slice())),
+        ("userVerification", "required".into()),
+        ("timeout", options.timeout_ms.into()),
+    ]);
+    let request = CredentialRequestOptions::new();
+    Reflect::set(&request, &"publicKey".into(), &public_key)?;
+
+    let credential: PublicKeyCredential = JsFuture::from(credentials()?.get_with_options(&request)?).await?.dyn_into()?;
+    let response: AuthenticatorAssertionResponse = credential.response().dyn_into().map_err(JsValue::from)?;
+    Ok(AssertionResponse {
+        id: from_buffer(&credential.raw_id()),
+        client_data_json: from_buffer(&response.client_data_json()),
+        authenticator_data: from_buffer(&response.authenticator_data()),
+        signature: from_buffer(&response.signature()),
+        user_handle: response.user_handle().as_ref().map(from_buffer),
+    })
+}
+
+#[function_component]
+pub fn PasskeyPanel() -> Html {
+    let session = use_context::<Session>().expect("passkeys are only managed inside `RequireLogin`");
+    // Bumped with every new passkey, to list it.
+    let added = use_state(|| 0u32);
+    let passkeys = use_fetch(*added, |_| None, |_| api::list_passkeys());
+    let name = use_node_ref();
+    let busy = use_state(|| false);
+    let error = use_state(|| None::<Error>);
+
+    let on_add = {
+        let name = name.clone();
+        let added = added.clone();
+        let busy = busy.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let name = name.cast::<HtmlInputElement>();
+            let value = name.as_ref().map(|name| name.value()).unwrap_or_default();
+            busy.set(true);
+            let session = session.clone();
+            let added = added.clone();
+            let busy = busy.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match register(value, &session).await {
+                    Ok(_) => {
+                        if let Some(name) = name {
+                            name.set_value("");
+                        }
+                        error.set(None);
+                        added.set(*added + 1);
+                    },
+                    Err(e) => error.set(Some(e)),
+                }
+                busy.set(false);
+            });
+        })
+    };
+    // The panel sits in the editor's form, where Enter would save the post instead.
+    let on_keydown = Callback::from(|e: KeyboardEvent| {
+        if e.key() == "Enter" {
+            e.prevent_default();
+        }
+    });
+
+    let list = match &*passkeys {
+        Fetched::Loading => html! { <p>{"Loading passkeys..."}</p> },
+        Fetched::Failed(e) => html! { <p class="editor-error">{"Couldn't load the passkeys: "}{e}</p> },
+        Fetched::Loaded(passkeys) if passkeys.is_empty() => html! { <p>{"No passkeys yet."}</p> },
+        Fetched::Loaded(passkeys) => html! {
+            <ul class="passkey-list">
+                {for passkeys.iter().map(|passkey| html! {
+                    <li>
+                        <span class="passkey-name">{&passkey.name}</span>
+                        <span class="passkey-used">
+                            {"Added "}{format_date(passkey.created_at)}
+                            {match passkey.last_used_at {
+                                Some(at) => format!(", last used {}", format_date(at)),
+                                None => ", never used".to_owned(),
+                            }}
+                        </span>
+                    </li>
+                })}
+            </ul>
+        },
+    };
+
+    html! {
+        <details class="editor-passkeys">
+            <summary>{"Passkeys"}</summary>
+            {list}
+            <div class="passkey-add">
+                <input type="text" placeholder="Name, like the device it's on" ref={name} onkeydown={on_keydown} />
+                <button type="button" disabled={*busy} onclick={on_add}>{"Add a passkey"}</button>
+            </div>
+            if let Some(e) = error.as_ref() {
+                <p class="editor-error">{e.to_string()}</p>
+            }
+        </details>
+    }
+}
@@ -1,4 +1,8 @@
+use blog_api::posts::{Post as ApiPost, PostLink};
 use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::{api, fetch::{use_fetch, Fetched}, format_date, post_list, series, set_title, Route};
 
 #[derive(Debug, PartialEq)]
 #[derive(Properties)]
@@ -8,7 +12,85 @@ pub struct Props {
 
 #[function_component]
 pub fn Post(props: &Props) -> Html {
+    let slug = props.slug.clone();
+    let post = use_fetch(
+        props.slug.clone(),
+        |prefetched| prefetched.post.take().filter(|post| post.slug == slug),
+        |slug| async move { api::get_post(slug.as_str()).await },
+    );
+
+    {
+        let title = match &*post {
+            Fetched::Loaded(post) => Some(post.title.clone()),
+            Fetched::Failed(e) if e.is_not_found() => Some("Post not found".to_owned()),
+            Fetched::Loading | Fetched::Failed(_) => None,
+        };
+        use_effect_with(title, |title| {
+            if let Some(title) = title {
+                set_title(title.as_str());
+            }
+        });
+    }
+
+    match &*post {
+        Fetched::Loaded(post) => view(post),
+        Fetched::Loading => html! {
+            <div class="post">
+                <p>{"Loading post..."}</p>
+            </div>
+        },
+        Fetched::Failed(e) if e.is_not_found() => html! {
+            <div class="post">
+                <h1>{"Post not found"}</h1>
+                <p>{"There's no post here. It might have been renamed, or taken down for now."}</p>
+                <p><Link<Route> to={Route::PostList}>{"Back to the blog"}</Link<Route>></p>
+            </div>
+        },
+        Fetched::Failed(e) => html! {
+            <div class="post">
+                <p>{"Couldn't load the post: "}{e}</p>
+            </div>
+        },
+    }
+}
+
+fn view(post: &ApiPost) -> Html {
+    // Only worth mentioning if it happened on another day.
+    let updated = post.published_at
+        .filter(|published| published.date_naive() != post.updated_at.date_naive())
+        .map(|_| post.updated_at);
+
     html! {
-        <>{"under construction: "}{&props.slug}</>
+        <article class="post">
+            <h1>{&post.title}</h1>
+            <p class="post-published-date">
+                if let Some(published) = post.published_at {
+                    <time datetime={published.to_rfc3339()}>{format_date(published)}</time>{" · "}
+                }
+                if let Some(updated) = updated {
+                    {"updated "}<time datetime={updated.to_rfc3339()}>{format_date(updated)}</time>{" · "}
+                }
+                {post.reading_minutes}{" min read"}
+            </p>
+            if !post.tags.is_empty() {
+                <ul class="post-tags">{for post.tags.iter().map(|t| post_list::tag(t))}</ul>
+            }
+            {series::series_box(post)}
+            {Html::from_html_unchecked(post.html.clone().into())}
+            <nav class="pagination-buttons">
+                {neighbor(post.neighbors.newer.as_ref(), "← ", "")}
+                {neighbor(post.neighbors.older.as_ref(), "", " →")}
+            </nav>
+        </article>
+    }
+}
+
+/// A link to the next post over, or a placeholder keeping the other one in place.
+fn neighbor(link: Option<&PostLink>, before: &'static str, after: &'static str) -> Html {
+    match link {
+        Some(link) => html! {
+            <Link<Route> to={Route::Post { slug: link.slug.clone() }}>{before}{&link.title}{after}</Link<Route>>
+        },
+        None => html! { <span /> },
     }
 }
@@ -1,13 +1,185 @@
+use blog_api::posts::{ListParams, PostPage, PostSummary, TagCount};
 use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::{api, fetch::{use_fetch, Fetched}, format_date, search::SearchBox, set_title, Route};
 
 #[derive(Debug, PartialEq)]
 #[derive(Properties)]
 pub struct Props {
+    /// Only list posts with this tag.
+    #[prop_or_default]
+    pub tag: Option<String>,
 }
 
+/// Lists published posts, newest first, along with a cloud of every tag.
+///
+/// Paging lives in the query string (`?after=...`), so every page of the listing can be linked to
+/// and reloaded. Tags get their own pages, though the old `/blog?tag=rust` links still work.
 #[function_component]
-pub fn PostList(_props: &Props) -> Html {
+pub fn PostList(props: &Props) -> Html {
+    let location = use_location();
+    let mut params = location.as_ref()
+        .and_then(|location| location.query::<ListParams>().ok())
+        .unwrap_or_default();
+    if props.tag.is_some() {
+        params.tag = props.tag.clone();
+    }
+    let page = use_fetch(
+        params.clone(),
+        |prefetched| prefetched.posts.take(),
+        |params| async move { api::list_posts(&params).await },
+    );
+    use_effect_with(params.tag.clone(), |tag| match tag {
+        Some(tag) => set_title(format!("Posts tagged {tag}").as_str()),
+        None => set_title("Blog"),
+    });
+
+    html! {
+        <div class="post-list">
+            if let Some(tag) = params.tag.as_ref() {
+                <h1>{"Posts tagged "}<span class="post-tag">{tag}</span></h1>
+                <p class="post-list-filter">
+                    <Link<Route> to={Route::PostList}>{"See every post"}</Link<Route>>
+                </p>
+            } else {
+                <h1>{"Posts"}</h1>
+            }
+            <SearchBox />
+            <TagCloud current={params.tag.clone()} />
+            {match &*page {
+                Fetched::Loading => html! {
+                    <p class="no-post-text">{"Loading posts..."}</p>
+                },
+                Fetched::Failed(e) => html! {
+                    <p class="no-post-text">{"Couldn't load posts: "}{e}</p>
+                },
+                Fetched::Loaded(page) => listing(page, &params),
+            }}
+        </div>
+    }
+}
+
+fn listing(page: &PostPage, params: &ListParams) -> Html {
+    if page.posts.is_empty() {
+        let text = if params.after.is_some() {
+            "That's everything!"
+        } else if params.tag.is_some() {
+            "Nothing with that tag yet."
+        } else {
+            "Nothing here yet, check back soon!"
+        };
+        return html! {
+            <>
+                <p class="no-post-text">{text}</p>
+                {pagination(page, params)}
+            </>
+        };
+    }
+
+    html! {
+        <>
+            <ul>{for page.posts.iter().map(item)}</ul>
+            {pagination(page, params)}
+        </>
+    }
+}
+
+/// The same listing, from the start or after `next`.
+fn pagination(page: &PostPage, params: &ListParams) -> Html {
+    let route = match params.tag.clone() {
+        Some(tag) => Route::Tag { tag },
+        None => Route::PostList,
+    };
+    let older = page.next.clone().map(|next| ListParams { after: Some(next), ..ListParams::default() });
+    html! {
+        <div class="pagination-buttons">
+            if params.after.is_some() {
+                <Link<Route> to={route.clone()}>{"← Newest posts"}</Link<Route>>
+            } else {
+                <span />
+            }
+            if let Some(older) = older {
+                <Link<Route, ListParams> to={route} query={Some(older)}>{"Older posts →"}</Link<Route, ListParams>>
+            }
+        </div>
+    }
+}
+
+pub fn item(post: &PostSummary) -> Html {
+    html! {
+        <li class="post-item" key={post.slug.clone()}>
+            <h2>
+                <Link<Route> to={Route::Post { slug: post.slug.clone() }} classes="post-title-link">
+                    {&post.title}
+                </Link<Route>>
+            </h2>
+            if let Some(published) = post.published_at {
+                <p class="post-published-date">{format_date(published)}</p>
+            }
+            if let Some(series) = post.series.as_ref() {
+                <p class="post-series">
+                    {"Part "}{series.part}{" of "}
+                    <Link<Route> to={Route::Series { slug: series.slug.clone() }}>{&series.title}</Link<Route>>
+                </p>
+            }
+            if let Some(summary) = post.summary.as_ref() {
+                <p class="post-summary">{summary}</p>
+            }
+            if !post.tags.is_empty() {
+                <ul class="post-tags">{for post.tags.iter().map(|t| tag(t))}</ul>
+            }
+        </li>
+    }
+}
+
+/// A tag, linking to everything else with it.
+pub fn tag(tag: &str) -> Html {
+    html! {
+        <li>
+            <Link<Route> to={Route::Tag { tag: tag.to_owned() }} classes="post-tag">{tag}</Link<Route>>
+        </li>
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[derive(Properties)]
+struct TagCloudProps {
+    /// The tag being listed, if any.
+    current: Option<String>,
+}
+
+/// Every tag, bigger the more posts have it.
+#[function_component]
+fn TagCloud(props: &TagCloudProps) -> Html {
+    let tags = use_fetch((), |prefetched| prefetched.tags.take(), |_| api::list_tags());
+    // Not worth holding up the posts over, so there's nothing to show until it's there.
+    let Fetched::Loaded(tags) = &*tags else {
+        return html! {};
+    };
+    if tags.is_empty() {
+        return html! {};
+    }
+    let most = tags.iter().map(|tag| tag.posts).max().unwrap_or(1);
+    html! {
+        <ul class="tag-cloud">
+            {for tags.iter().map(|tag| cloud_tag(tag, most, props.current.as_deref() == Some(tag.tag.as_str())))}
+        </ul>
+    }
+}
+
+fn cloud_tag(tag: &TagCount, most: usize, current: bool) -> Html {
+    // From 0.85em for tags on a single post to 1.6em for the most used ones.
+    let size = 0.85 + 0.75 * (tag.posts.saturating_sub(1) as f64) / (most.saturating_sub(1).max(1) as f64);
+    let title = match tag.posts {
+        1 => "1 post".to_owned(),
+        posts => format!("{posts} posts"),
+    };
     html! {
-        <>{"under construction"}</>
+        <li key={tag.tag.clone()} style={format!("font-size: {size:.2}em")} {title}>
+            <Link<Route> to={Route::Tag { tag: tag.tag.clone() }} classes={classes!("post-tag", current.then_some("current"))}>
+                {&tag.tag}
+            </Link<Route>>
+        </li>
     }
 }
@@ -0,0 +1,42 @@
+//! Data the server fetched while rendering the page, handed over so that hydration renders the
+//! exact same thing without asking the API again.
+
+use blog_api::{posts::{Post, PostPage, Series, TagCount}, search::SearchHit};
+use serde::{Deserialize, Serialize};
+
+/// The id of the `<script type="application/json">` carrying the data.
+pub const ELEMENT_ID: &str = "blog-prefetched";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Prefetched {
+    /// The first page of posts, for the post list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub posts: Option<PostPage>,
+    /// The post being read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post: Option<Post>,
+    /// The series being read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<Series>,
+    /// Every tag, for the tag cloud next to the post list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<TagCount>>,
+    /// What the search in the query string found.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<Vec<SearchHit>>,
+}
+impl Prefetched {
+    /// The data as a JSON blob that can sit inside a `<script>` without closing it early.
+    pub fn to_script_json(&self) -> String {
+        serde_json::to_string(self)
+            .expect("prefetched data serializes")
+            .replace('<', "\\u003c")
+    }
+
+    /// Reads back what [`Self::to_script_json`] put on the page.
+    ///
+    /// Anything missing or mangled just means nothing was prefetched.
+    pub fn from_script_json(json: &str) -> Self {
+        serde_json::from_str(json).unwrap_or_default()
+    }
+}
@@ -0,0 +1,176 @@
+//! The history of a post in the editor: what every save changed, and a way back to it.
+
+use blog_api::revisions::{Change, DiffLine, DiffParams, Revision, RevisionDiff, RevisionSummary};
+use chrono::{DateTime, Utc};
+use yew::{platform::spawn_local, prelude::*};
+
+use crate::{api, fetch::{use_fetch, Fetched}, format_date};
+
+/// Unchanged lines kept around a change. Longer unchanged stretches fold away.
+const CONTEXT: usize = 2;
+
+#[derive(Debug, PartialEq)]
+#[derive(Properties)]
+pub struct Props {
+    pub slug: String,
+    /// Changes whenever the post is saved, so that the history picks up the new revision.
+    pub saves: u32,
+    /// Brings a revision back into the editor.
+    pub on_restore: Callback<Revision>,
+}
+
+fn saved_at(at: DateTime<Utc>) -> String {
+    format!("{}, {}", format_date(at), at.format("%H:%M UTC"))
+}
+
+#[function_component]
+pub fn RevisionPanel(props: &Props) -> Html {
+    let revisions = use_fetch((props.slug.clone(), props.saves), |_| None, |(slug, _)| async move {
+        api::list_revisions(slug.as_str()).await
+    });
+    let selected = use_state(|| None::<u32>);
+    let diff = use_fetch((props.slug.clone(), props.saves, *selected), |_| None, |(slug, _, selected)| async move {
+        match selected {
+            // What that save changed.
+            Some(to) => api::diff_revisions(slug.as_str(), &DiffParams { from: None, to: Some(to) }).await.map(Some),
+            None => Ok(None),
+        }
+    });
+    let restoring = use_state(|| false);
+    let error = use_state(|| None::<api::Error>);
+
+    let on_restore = {
+        let slug = props.slug.clone();
+        let selected = selected.clone();
+        let restoring = restoring.clone();
+        let error = error.clone();
+        let on_restore = props.on_restore.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(number) = *selected else {
+                return;
+            };
+            restoring.set(true);
+            let slug = slug.clone();
+            let restoring = restoring.clone();
+            let error = error.clone();
+            let on_restore = on_restore.clone();
+            spawn_local(async move {
+                match api::get_revision(slug.as_str(), number).await {
+                    Ok(revision) => {
+                        error.set(None);
+                        on_restore.emit(revision);
+                    },
+                    Err(e) => error.set(Some(e)),
+                }
+                restoring.set(false);
+            });
+        })
+    };
+
+    let list = match &*revisions {
+        Fetched::Loading => html! { <p>{"Loading history..."}</p> },
+        Fetched::Failed(e) => html! { <p class="editor-error">{"Couldn't load the history: "}{e}</p> },
+        Fetched::Loaded(revisions) => html! {
+            <ol class="revision-list">
+                {for revisions.iter().map(|revision| entry(revision, *selected, &selected))}
+            </ol>
+        },
+    };
+    let diff = match &*diff {
+        Fetched::Loaded(None) => html! {},
+        Fetched::Loading => html! { <p>{"Loading changes..."}</p> },
+        Fetched::Failed(e) => html! { <p class="editor-error">{"Couldn't load the changes: "}{e}</p> },
+        Fetched::Loaded(Some(diff)) => html! {
+            <div class="revision-changes">
+                {changes(diff)}
+                if let Some(e) = error.as_ref() {
+                    <p class="editor-error">{"Couldn't restore it: "}{e}</p>
+                }
+                <button type="button" disabled={*restoring} onclick={on_restore}>
+                    {format!("Restore revision {}", diff.to.number)}
+                </button>
+            </div>
+        },
+    };
+
+    html! {
+        <details class="editor-revisions">
+            <summary>{"History"}</summary>
+            {list}
+            {diff}
+        </details>
+    }
+}
+
+fn entry(revision: &RevisionSummary, selected: Option<u32>, select: &UseStateHandle<Option<u32>>) -> Html {
+    let number = revision.number;
+    let onclick = {
+        let select = select.clone();
+        Callback::from(move |_: MouseEvent| select.set(Some(number)))
+    };
+    html! {
+        <li class={classes!((selected == Some(number)).then_some("selected"))}>
+            <button type="button" {onclick}>
+                <span class="revision-number">{number}</span>
+                <span class="revision-title">{&revision.title}</span>
+                <span class="revision-saved">{saved_at(revision.saved_at)}{" by "}{&revision.author}</span>
+            </button>
+        </li>
+    }
+}
+
+fn changes(diff: &RevisionDiff) -> Html {
+    let against = match diff.from.as_ref() {
+        Some(from) => format!("Changes since revision {}", from.number),
+        None => "The first revision".to_owned(),
+    };
+    html! {
+        <>
+            <h3>{against}{", saved "}{saved_at(diff.to.saved_at)}{" by "}{&diff.to.author}</h3>
+            if diff.fields.is_empty() {
+                <p>{"Nothing changed."}</p>
+            }
+            {for diff.fields.iter().map(|field| html! {
+                <div class="revision-field">
+                    <h4>{&field.field}</h4>
+                    <pre class="revision-diff">{lines(field.lines.as_slice())}</pre>
+                </div>
+            })}
+        </>
+    }
+}
+
+/// Shows the changed lines and a few around them, folding away the rest.
+fn lines(lines: &[DiffLine]) -> Html {
+    let near_change = |idx: usize| {
+        lines[idx.saturating_sub(CONTEXT)..(idx + CONTEXT + 1).min(lines.len())]
+            .iter()
+            .any(|line| line.change != Change::Same)
+    };
+    let fold = |count: usize| html! {
+        <span class="diff-fold">{format!("⋯ {count} unchanged line{}\n", if count == 1 { "" } else { "s" })}</span>
+    };
+
+    let mut shown = vec![];
+    let mut folded = 0;
+    for (idx, line) in lines.iter().enumerate() {
+        if !near_change(idx) {
+            folded += 1;
+            continue;
+        }
+        if folded > 0 {
+            shown.push(fold(folded));
+            folded = 0;
+        }
+        let (class, marker) = match line.change {
+            Change::Same => ("diff-same", ' '),
+            Change::Added => ("diff-added", '+'),
+            Change::Removed => ("diff-removed", '-'),
+        };
+        shown.push(html! { <span class={class}>{format!("{marker} {}\n", line.text)}</span> });
+    }
+    if folded > 0 {
+        shown.push(fold(folded));
+    }
+    shown.into_iter().collect()
+}
@@ -0,0 +1,109 @@
+//! Picking when a post goes live, as a local date and time in some time zone.
+//!
+//! The zone starts out as the browser's, but posts are often timed for readers somewhere else.
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use js_sys::{Array, Intl, Object, Reflect};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+/// What `datetime-local` inputs hand out, with or without seconds.
+const LOCAL_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"];
+
+/// The browser's idea of the current time. `Utc::now` has no clock to go on in here.
+pub fn now() -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(js_sys::Date::now() as i64).unwrap_or_default()
+}
+
+/// The time zone the browser is set to, if it's one `chrono-tz` knows.
+fn browser_zone() -> Option<Tz> {
+    let options = Intl::DateTimeFormat::new(&Array::new(), &Object::new()).resolved_options();
+    Reflect::get(&options, &"timeZone".into()).ok()?.as_string()?.parse().ok()
+}
+
+/// `local` in `zone`, as the moment it names.
+fn to_utc(local: &str, zone: Tz) -> Result<DateTime<Utc>, String> {
+    let naive = LOCAL_FORMATS.iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(local, format).ok())
+        .ok_or_else(|| "Pick a date and time.".to_owned())?;
+    // Clocks going back make some times happen twice. The first of them is as good as any.
+    let at = zone.from_local_datetime(&naive).earliest()
+        .ok_or_else(|| format!("That time gets skipped over in {}.", zone.name()))?;
+    Ok(at.with_timezone(&Utc))
+}
+
+#[derive(Debug, PartialEq)]
+#[derive(Properties)]
+pub struct Props {
+    /// When the post is scheduled for already, if it is.
+    pub scheduled: Option<DateTime<Utc>>,
+    pub disabled: bool,
+    pub on_schedule: Callback<DateTime<Utc>>,
+}
+
+#[function_component]
+pub fn SchedulePicker(props: &Props) -> Html {
+    let zone = use_state(|| Tz::UTC);
+    let local = use_state(String::new);
+    let error = use_state(|| None::<String>);
+
+    {
+        let zone = zone.clone();
+        let local = local.clone();
+        // Only the browser knows its zone, so this waits until after hydration.
+        use_effect_with(props.scheduled, move |scheduled| {
+            let browser = browser_zone().unwrap_or(Tz::UTC);
+            zone.set(browser);
+            if let Some(at) = scheduled {
+                local.set(at.with_timezone(&browser).format(LOCAL_FORMATS[0]).to_string());
+            }
+        });
+    }
+
+    let on_local = {
+        let local = local.clone();
+        Callback::from(move |e: InputEvent| local.set(e.target_unchecked_into::<HtmlInputElement>().value()))
+    };
+    let on_zone = {
+        let zone = zone.clone();
+        Callback::from(move |e: Event| {
+            if let Ok(picked) = e.target_unchecked_into::<HtmlSelectElement>().value().parse() {
+                zone.set(picked);
+            }
+        })
+    };
+    let on_schedule = {
+        let zone = zone.clone();
+        let local = local.clone();
+        let error = error.clone();
+        let on_schedule = props.on_schedule.clone();
+        Callback::from(move |_: MouseEvent| {
+            match to_utc(local.as_str(), *zone) {
+                Ok(at) if at <= now() => error.set(Some("That's already passed, publish it instead.".to_owned())),
+                Ok(at) => {
+                    error.set(None);
+                    on_schedule.emit(at);
+                },
+                Err(e) => error.set(Some(e)),
+            }
+        })
+    };
+
+    html! {
+        <div class="editor-schedule">
+            <input type="datetime-local" value={(*local).clone()} oninput={on_local} />
+            <select onchange={on_zone}>
+                {for TZ_VARIANTS.iter().map(|tz| html! {
+                    <option value={tz.name()} selected={*tz == *zone}>{tz.name()}</option>
+                })}
+            </select>
+            <button type="button" disabled={props.disabled} onclick={on_schedule}>
+                {if props.scheduled.is_some() { "Reschedule" } else { "Schedule" }}
+            </button>
+            if let Some(e) = error.as_ref() {
+                <p class="editor-error">{e}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,204 @@
+//! Searching the blog: the box to type into, and the page listing what it found.
+//!
+//! The query lives in the query string (`/blog/search?q=...`), so searches can be linked to and
+//! reloaded like any other page.
+
+use std::time::Duration;
+
+use blog_api::search::{Fragment, SearchHit, SearchParams};
+use web_sys::HtmlInputElement;
+use yew::{platform::{spawn_local, time::sleep}, prelude::*};
+use yew_router::prelude::*;
+
+use crate::{api, fetch::{use_fetch, Fetched}, format_date, set_title, Route};
+
+/// How long typing has to stop for before searching.
+const SEARCH_DELAY: Duration = Duration::from_millis(300);
+
+/// What the search in the query string found, best match first.
+#[function_component]
+pub fn Search() -> Html {
+    let location = use_location();
+    let q = location.as_ref()
+        .and_then(|location| location.query::<SearchParams>().ok())
+        .unwrap_or_default()
+        .q;
+    let hits = use_fetch(
+        q.clone(),
+        |prefetched| prefetched.search.take(),
+        |q| async move {
+            // Nothing to ask the server about.
+            if q.trim().is_empty() {
+                return Ok(vec![]);
+            }
+            api::search(q.as_str()).await
+        },
+    );
+    use_effect_with(q.clone(), |q| match q.trim() {
+        "" => set_title("Search"),
+        q => set_title(format!("Search for {q}").as_str()),
+    });
+
+    html! {
+        <div class="post-list">
+            <h1>{"Search"}</h1>
+            <SearchBox query={q.clone()} focus=true />
+            {match &*hits {
+                _ if q.trim().is_empty() => html! {
+                    <p class="no-post-text">{"Type something to look for in every post."}</p>
+                },
+                Fetched::Loading => html! {
+                    <p class="no-post-text">{"Searching..."}</p>
+                },
+                Fetched::Failed(e) => html! {
+                    <p class="no-post-text">{"Couldn't search: "}{e}</p>
+                },
+                Fetched::Loaded(hits) if hits.is_empty() => html! {
+                    <p class="no-post-text">{"Nothing matches that."}</p>
+                },
+                Fetched::Loaded(hits) => html! {
+                    <ul class="search-results">{for hits.iter().map(hit)}</ul>
+                },
+            }}
+        </div>
+    }
+}
+
+fn hit(hit: &SearchHit) -> Html {
+    html! {
+        <li class="post-item" key={hit.post.slug.clone()}>
+            <h2>
+                <Link<Route> to={Route::Post { slug: hit.post.slug.clone() }} classes="post-title-link">
+                    {fragments(&hit.title)}
+                </Link<Route>>
+            </h2>
+            if let Some(published) = hit.post.published_at {
+                <p class="post-published-date">{format_date(published)}</p>
+            }
+            if !hit.snippet.is_empty() {
+                <p class="search-snippet">{fragments(&hit.snippet)}</p>
+            }
+        </li>
+    }
+}
+
+/// Text with whatever matched highlighted.
+fn fragments(fragments: &[Fragment]) -> Html {
+    fragments.iter()
+        .map(|fragment| match fragment.matched {
+            true => html! { <mark>{&fragment.text}</mark> },
+            false => html! { {&fragment.text} },
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+#[derive(Properties)]
+pub struct SearchBoxProps {
+    /// The search being shown, if any.
+    #[prop_or_default]
+    pub query: String,
+    /// Whether to focus the box once it's there, for carrying on typing after landing on the
+    /// results.
+    #[prop_or_default]
+    pub focus: bool,
+}
+
+/// Searches as soon as typing stops for a moment, or right away on enter.
+#[function_component]
+pub fn SearchBox(props: &SearchBoxProps) -> Html {
+    let navigator = use_navigator();
+    let on_results = use_route::<Route>() == Some(Route::Search);
+    let text = use_state(|| props.query.clone());
+    let input = use_node_ref();
+    // Bumped on every keystroke, so that only the last of a burst of them searches.
+    let generation = use_mut_ref(|| 0_u64);
+    // The last search this box went to, to tell it apart from going back to an older one.
+    let sent = use_mut_ref(|| props.query.clone());
+
+    {
+        let input = input.clone();
+        use_effect_with(props.focus, move |focus| {
+            let Some(input) = input.cast::<HtmlInputElement>().filter(|_| *focus) else {
+                return;
+            };
+            let _ = input.focus();
+            // Typing carries on from the end, not the start.
+            let end = input.value().encode_utf16().count() as u32;
+            let _ = input.set_selection_range(end, end);
+        });
+    }
+    {
+        let text = text.clone();
+        let sent = sent.clone();
+        use_effect_with(props.query.clone(), move |query| {
+            if *query != *sent.borrow() {
+                *sent.borrow_mut() = query.clone();
+                text.set(query.clone());
+            }
+        });
+    }
+
+    // Stays on the results page rather than leaving a trail of half typed searches in the history.
+    let search = Callback::from(move |q: String| {
+        let Some(navigator) = navigator.as_ref() else {
+            return;
+        };
+        if q.trim().is_empty() && !on_results {
+            return;
+        }
+        *sent.borrow_mut() = q.clone();
+        let params = SearchParams { q, limit: None };
+        let _ = match on_results {
+            true => navigator.replace_with_query(&Route::Search, &params),
+            false => navigator.push_with_query(&Route::Search, &params),
+        };
+    });
+    let on_input = {
+        let text = text.clone();
+        let generation = generation.clone();
+        let search = search.clone();
+        Callback::from(move |e: InputEvent| {
+            let value = e.target_unchecked_into::<HtmlInputElement>().value();
+            text.set(value.clone());
+            let ticket = {
+                let mut generation = generation.borrow_mut();
+                *generation += 1;
+                *generation
+            };
+            let generation = generation.clone();
+            let search = search.clone();
+            spawn_local(async move {
+                sleep(SEARCH_DELAY).await;
+                if *generation.borrow() == ticket {
+                    search.emit(value);
+                }
+            });
+        })
+    };
+    let on_submit = {
+        let text = text.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            // Whatever was still waiting is what's being searched for now.
+            *generation.borrow_mut() += 1;
+            search.emit((*text).clone());
+        })
+    };
+
+    html! {
+        // Still a working form before the page hydrates.
+        <form class="search-box" role="search" action="/blog/search" method="get" onsubmit={on_submit}>
+            <input
+                ref={input}
+                type="search"
+                name="q"
+                placeholder="Search posts"
+                aria-label="Search posts"
+                value={(*text).clone()}
+                oninput={on_input}
+            />
+            <button type="submit">{"Search"}</button>
+        </form>
+    }
+}
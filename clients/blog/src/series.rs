@@ -0,0 +1,122 @@
+//! Series: their own page listing every part, and the box on each part pointing at the others.
+
+use blog_api::posts::{Post, SeriesPart};
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::{api, fetch::{use_fetch, Fetched}, post_list, set_title, Route};
+
+#[derive(Debug, PartialEq)]
+#[derive(Properties)]
+pub struct Props {
+    pub slug: String,
+}
+
+/// Every published part of a series, in reading order.
+#[function_component]
+pub fn Series(props: &Props) -> Html {
+    let slug = props.slug.clone();
+    let series = use_fetch(
+        props.slug.clone(),
+        |prefetched| prefetched.series.take().filter(|series| series.slug == slug),
+        |slug| async move { api::get_series(slug.as_str()).await },
+    );
+
+    {
+        let title = match &*series {
+            Fetched::Loaded(series) => Some(series.title.clone()),
+            Fetched::Failed(e) if e.is_not_found() => Some("Series not found".to_owned()),
+            Fetched::Loading | Fetched::Failed(_) => None,
+        };
+        use_effect_with(title, |title| {
+            if let Some(title) = title {
+                set_title(title.as_str());
+            }
+        });
+    }
+
+    html! {
+        <div class="post-list">
+            {match &*series {
+                Fetched::Loading => html! {
+                    <p class="no-post-text">{"Loading the series..."}</p>
+                },
+                Fetched::Failed(e) if e.is_not_found() => html! {
+                    <>
+                        <h1>{"Series not found"}</h1>
+                        <p class="no-post-text">{"There's no series here, or nothing in it is out yet."}</p>
+                        <p><Link<Route> to={Route::PostList}>{"Back to the blog"}</Link<Route>></p>
+                    </>
+                },
+                Fetched::Failed(e) => html! {
+                    <p class="no-post-text">{"Couldn't load the series: "}{e}</p>
+                },
+                Fetched::Loaded(series) => html! {
+                    <>
+                        <h1>{&series.title}</h1>
+                        <p class="post-list-filter">
+                            {parts(series.posts.len())}{" · "}
+                            <Link<Route> to={Route::PostList}>{"See every post"}</Link<Route>>
+                        </p>
+                        <ol class="series-posts">{for series.posts.iter().map(post_list::item)}</ol>
+                    </>
+                },
+            }}
+        </div>
+    }
+}
+
+fn parts(count: usize) -> String {
+    match count {
+        1 => "1 part so far".to_owned(),
+        count => format!("{count} parts so far"),
+    }
+}
+
+/// Where `post` sits in its series, with links to the other parts. Nothing for posts outside of
+/// one.
+pub fn series_box(post: &Post) -> Html {
+    let Some(series) = post.series.as_ref() else {
+        return html! {};
+    };
+    if post.series_parts.is_empty() {
+        return html! {};
+    }
+    let at = post.series_parts.iter().position(|part| part.slug == post.slug);
+    let previous = at.and_then(|at| at.checked_sub(1)).and_then(|at| post.series_parts.get(at));
+    let next = at.and_then(|at| post.series_parts.get(at + 1));
+
+    html! {
+        <aside class="series-box">
+            <p class="series-box-title">
+                {"Part "}{series.part}{" of "}
+                <Link<Route> to={Route::Series { slug: series.slug.clone() }}>{&series.title}</Link<Route>>
+            </p>
+            <ol>
+                {for post.series_parts.iter().map(|part| html! {
+                    <li key={part.slug.clone()} value={part.part.to_string()}>
+                        if part.slug == post.slug {
+                            <span class="series-box-current">{&part.title}</span>
+                        } else {
+                            <Link<Route> to={Route::Post { slug: part.slug.clone() }}>{&part.title}</Link<Route>>
+                        }
+                    </li>
+                })}
+            </ol>
+            <nav class="pagination-buttons">
+                {step(previous, "← Previous: ", "")}
+                {step(next, "Next: ", " →")}
+            </nav>
+        </aside>
+    }
+}
+
+/// A link to the next part over, or a placeholder keeping the other one in place.
+fn step(part: Option<&SeriesPart>, before: &'static str, after: &'static str) -> Html {
+    match part {
+        Some(part) => html! {
+            <Link<Route> to={Route::Post { slug: part.slug.clone() }}>{before}{&part.title}{after}</Link<Route>>
+        },
+        None => html! { <span /> },
+    }
+}
@@ -0,0 +1,55 @@
+//! Pages as the server renders them. Effects don't run there, so whatever a page fetches for
+//! itself shows up as still loading.
+
+use blog_api::posts::{PostPage, PostSummary};
+use chrono::DateTime;
+
+use crate::{prefetched::Prefetched, render};
+
+async fn page(path: &str, prefetched: Prefetched) -> String {
+    render(path.to_owned(), Vec::<(String, String)>::new(), prefetched).await
+}
+
+fn summary(slug: &str) -> PostSummary {
+    let date = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    PostSummary {
+        slug: slug.to_owned(),
+        title: format!("All about {slug}"),
+        summary: None,
+        tags: vec![],
+        series: None,
+        author: "Ben".to_owned(),
+        published_at: Some(date),
+        updated_at: date,
+        draft: false,
+    }
+}
+
+#[tokio::test]
+async fn prefetched_data_is_rendered_right_away() {
+    let prefetched = Prefetched {
+        posts: Some(PostPage { posts: vec![summary("hello")], next: None }),
+        tags: Some(vec![]),
+        ..Prefetched::default()
+    };
+    let html = page("/blog", prefetched).await;
+    assert!(html.contains("All about hello"), "{html}");
+    assert!(!html.contains("Loading"), "{html}");
+}
+
+#[tokio::test]
+async fn pages_fetch_what_was_not_prefetched() {
+    let html = page("/blog", Prefetched::default()).await;
+    assert!(html.contains("Loading posts..."), "{html}");
+    let html = page("/blog/post/hello", Prefetched::default()).await;
+    assert!(html.contains("Loading post..."), "{html}");
+
+    // Data for another page doesn't count.
+    let prefetched = Prefetched {
+        posts: Some(PostPage { posts: vec![summary("hello")], next: None }),
+        ..Prefetched::default()
+    };
+    let html = page("/blog/post/hello", prefetched).await;
+    assert!(html.contains("Loading post..."), "{html}");
+    assert!(!html.contains("All about hello"), "{html}");
+}
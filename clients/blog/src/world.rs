@@ -1,15 +1,40 @@
+use std::{cell::RefCell, rc::Rc};
+
 use yew::prelude::*;
 
+use crate::prefetched::Prefetched;
+
+/// What the server prefetched, until a page claims it.
+#[derive(Debug, Default, PartialEq)]
+pub struct PrefetchedSlot(RefCell<Prefetched>);
+
+impl PrefetchedSlot {
+    pub fn new(prefetched: Prefetched) -> Self {
+        Self(RefCell::new(prefetched))
+    }
+}
+
 #[derive(Debug, PartialEq)]
 #[derive(Properties)]
 pub struct Props {
+    pub prefetched: Rc<PrefetchedSlot>,
     pub children: Html,
 }
 
+/// Everything the pages share, provided as context.
 #[function_component]
 pub fn World(props: &Props) -> Html {
     html! {
-        <>{props.children.clone()}</>
+        <ContextProvider<Rc<PrefetchedSlot>> context={props.prefetched.clone()}>
+            {props.children.clone()}
+        </ContextProvider<Rc<PrefetchedSlot>>>
     }
 }
 
+/// Takes something the server prefetched out of the context.
+///
+/// Only meant for the first render of the page the server rendered. Anything mounted later (say,
+/// after following a link) finds nothing and fetches for itself, rather than showing stale data.
+pub fn take_prefetched<T>(slot: Option<&PrefetchedSlot>, take: impl FnOnce(&mut Prefetched) -> Option<T>) -> Option<T> {
+    slot.and_then(|slot| take(&mut slot.0.borrow_mut()))
+}
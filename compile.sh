@@ -69,7 +69,8 @@ mkdir -p $output_artifact/bin;
 cp $target_dir/release/static-server $output_artifact/bin;
 
 # Compile api server.
-# TODO
+cargo build --bin api-server --release;
+cp $target_dir/release/api-server $output_artifact/bin;
 
 set +x;
 
@@ -0,0 +1,22 @@
+//! Logging in and out of the editor.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Body of `POST /api/login`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// The logged in user's session, returned on login and by `GET /api/session`.
+///
+/// The session itself lives in a cookie the client can't read, this is what it needs to know.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub username: String,
+    pub expires_at: DateTime<Utc>,
+    /// Echoed back in the `x-csrf-token` header of every request that changes something.
+    pub csrf_token: String,
+}
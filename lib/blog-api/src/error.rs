@@ -0,0 +1,17 @@
+//! What the API responds with when something goes wrong.
+
+use serde::{Deserialize, Serialize};
+
+/// The body of every error response, e.g.
+/// `{"status": 404, "error": "Not Found", "message": "There's no post with that slug."}`.
+///
+/// This is the same shape the static server uses for its JSON errors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorBody {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The reason phrase of the status.
+    pub error: String,
+    /// Details meant for whoever made the request.
+    pub message: Option<String>,
+}
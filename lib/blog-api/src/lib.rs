@@ -0,0 +1,15 @@
+//! The requests and responses of the blog API.
+//!
+//! Both `api-server` and the `blog` client build against these, so the two can't drift apart.
+//! Everything here has to keep building for `wasm32-unknown-unknown`.
+
+pub mod auth;
+pub mod error;
+pub mod passkeys;
+pub mod paths;
+pub mod posts;
+pub mod revisions;
+pub mod search;
+
+#[cfg(test)]
+mod tests;
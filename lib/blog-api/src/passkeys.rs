@@ -0,0 +1,135 @@
+//! Logging in with a passkey instead of a password.
+//!
+//! Both registering a passkey and logging in with one take two round trips: the server hands out
+//! options with a fresh challenge, the browser has an authenticator sign it, and the server checks
+//! what comes back. Binary data goes over the wire as unpadded base64url, like WebAuthn's own JSON.
+
+use std::fmt::{self, Debug, Formatter};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Bytes, as unpadded base64url on the wire.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Base64Url(pub Vec<u8>);
+impl Base64Url {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.0.as_slice())
+    }
+
+    pub fn decode(encoded: &str) -> Option<Self> {
+        URL_SAFE_NO_PAD.decode(encoded).ok().map(Self)
+    }
+}
+impl Debug for Base64Url {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.encode())
+    }
+}
+impl From<Vec<u8>> for Base64Url {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+impl Serialize for Base64Url {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.encode().as_str())
+    }
+}
+impl<'de> Deserialize<'de> for Base64Url {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        Self::decode(encoded.as_str()).ok_or_else(|| D::Error::custom("expected unpadded base64url"))
+    }
+}
+
+/// What `navigator.credentials.create` needs to make a passkey.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreationOptions {
+    pub challenge: Base64Url,
+    /// The domain passkeys are tied to.
+    pub rp_id: String,
+    pub rp_name: String,
+    /// Identifies the user to the authenticator, which hands it back when logging in.
+    pub user_id: Base64Url,
+    pub user_name: String,
+    pub user_display_name: String,
+    /// COSE algorithm identifiers the server can verify, most preferred first.
+    pub algorithms: Vec<i32>,
+    /// Passkeys the user already has, which the authenticator shouldn't make another of.
+    pub exclude_credentials: Vec<Base64Url>,
+    pub timeout_ms: u32,
+}
+
+/// What `navigator.credentials.get` needs to log in with a passkey.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestOptions {
+    pub challenge: Base64Url,
+    pub rp_id: String,
+    /// Empty to let the authenticator offer whatever passkeys it has for the site.
+    pub allow_credentials: Vec<Base64Url>,
+    pub timeout_ms: u32,
+}
+
+/// What the authenticator made, from `navigator.credentials.create`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistrationResponse {
+    /// The credential's `rawId`.
+    pub id: Base64Url,
+    pub client_data_json: Base64Url,
+    pub attestation_object: Base64Url,
+}
+
+/// What the authenticator signed, from `navigator.credentials.get`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssertionResponse {
+    /// The credential's `rawId`.
+    pub id: Base64Url,
+    pub client_data_json: Base64Url,
+    pub authenticator_data: Base64Url,
+    pub signature: Base64Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_handle: Option<Base64Url>,
+}
+
+/// Response of `POST /api/passkeys/register`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistrationStart {
+    /// Hand this back with the response.
+    pub ceremony: String,
+    pub options: CreationOptions,
+}
+
+/// Body of `POST /api/passkeys/register/finish`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistrationFinish {
+    pub ceremony: String,
+    /// Something to tell passkeys apart by, like the device it's on.
+    pub name: String,
+    pub response: RegistrationResponse,
+}
+
+/// Response of `POST /api/passkeys/login`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginStart {
+    pub ceremony: String,
+    pub options: RequestOptions,
+}
+
+/// Body of `POST /api/passkeys/login/finish`, which answers with a
+/// [`Session`](crate::auth::Session) just like a password login.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginFinish {
+    pub ceremony: String,
+    pub response: AssertionResponse,
+}
+
+/// A registered passkey, as `GET /api/passkeys` lists them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Passkey {
+    pub id: Base64Url,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
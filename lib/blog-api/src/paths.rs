@@ -0,0 +1,61 @@
+//! Where the endpoints live.
+
+/// `GET` lists posts, `POST` creates one.
+pub const POSTS: &str = "/api/posts";
+
+/// `GET` reads, `PUT` saves and `DELETE` deletes a single post.
+pub fn post(slug: &str) -> String {
+    format!("{POSTS}/{slug}")
+}
+
+/// `GET` reads a post as it's being edited, whether or not it's published. Needs a login.
+pub fn edit(slug: &str) -> String {
+    format!("{POSTS}/{slug}/edit")
+}
+
+/// `GET` lists the saved versions of a post, newest first.
+pub fn revisions(slug: &str) -> String {
+    format!("{POSTS}/{slug}/revisions")
+}
+
+/// `GET` reads one saved version of a post.
+pub fn revision(slug: &str, number: u32) -> String {
+    format!("{POSTS}/{slug}/revisions/{number}")
+}
+
+/// `GET` compares two saved versions of a post.
+pub fn diff(slug: &str) -> String {
+    format!("{POSTS}/{slug}/diff")
+}
+
+/// `GET` reads a series and its posts.
+pub fn series(slug: &str) -> String {
+    format!("/api/series/{slug}")
+}
+
+/// `GET` counts the posts with each tag.
+pub const TAGS: &str = "/api/tags";
+
+/// `GET` searches published posts.
+pub const SEARCH: &str = "/api/search";
+
+/// `POST` logs in.
+pub const LOGIN: &str = "/api/login";
+
+/// `GET` reads the current session, `DELETE` logs out.
+pub const SESSION: &str = "/api/session";
+
+/// `GET` lists the logged in user's passkeys.
+pub const PASSKEYS: &str = "/api/passkeys";
+
+/// `POST` starts registering a passkey for the logged in user.
+pub const PASSKEY_REGISTER: &str = "/api/passkeys/register";
+
+/// `POST` finishes registering a passkey.
+pub const PASSKEY_REGISTER_FINISH: &str = "/api/passkeys/register/finish";
+
+/// `POST` starts logging in with a passkey.
+pub const PASSKEY_LOGIN: &str = "/api/passkeys/login";
+
+/// `POST` finishes logging in with a passkey.
+pub const PASSKEY_LOGIN_FINISH: &str = "/api/passkeys/login/finish";
@@ -0,0 +1,184 @@
+//! Posts, and listings of them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A post as it shows up in listings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostSummary {
+    pub slug: String,
+    pub title: String,
+    pub summary: Option<String>,
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub series: Option<PostSeries>,
+    pub author: String,
+    /// In the future for scheduled posts, which only whoever's logged in gets to see.
+    pub published_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+    pub draft: bool,
+}
+
+/// A whole post.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Post {
+    pub slug: String,
+    pub title: String,
+    /// The Markdown source of the post.
+    pub body: String,
+    /// The post rendered from `body`, already sanitized.
+    pub html: String,
+    /// The headings of the post, in order.
+    pub toc: Vec<Heading>,
+    pub reading_minutes: usize,
+    pub summary: Option<String>,
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub series: Option<PostSeries>,
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub draft: bool,
+    /// The published posts either side of this one. Only filled in for published posts.
+    #[serde(default)]
+    pub neighbors: Neighbors,
+    /// Every published part of the post's series, in order, this one included. Only filled in for
+    /// published posts.
+    #[serde(default)]
+    pub series_parts: Vec<SeriesPart>,
+}
+
+/// A heading within a post.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Heading {
+    /// 1 for `#`, 2 for `##` and so on.
+    pub level: u8,
+    /// The id of the heading, so `#id` links to it.
+    pub id: String,
+    pub title: String,
+}
+
+/// The posts published right before and right after a post.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Neighbors {
+    pub newer: Option<PostLink>,
+    pub older: Option<PostLink>,
+}
+
+/// Just enough of a post to link to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostLink {
+    pub slug: String,
+    pub title: String,
+}
+
+/// Where a post sits in a series.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostSeries {
+    /// What the series goes by, in `/blog/series/:slug`. Same rules as post slugs.
+    pub slug: String,
+    /// Saving a published post with a different title renames the whole series. Drafts keep
+    /// theirs until they're published.
+    pub title: String,
+    /// Parts are read in order, from 1.
+    pub part: u32,
+}
+
+/// One part of a series, to link to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeriesPart {
+    pub part: u32,
+    pub slug: String,
+    pub title: String,
+}
+
+/// Response of `GET /api/series/:slug`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Series {
+    pub slug: String,
+    pub title: String,
+    /// The published parts, in reading order.
+    pub posts: Vec<PostSummary>,
+}
+
+/// A tag and how many published posts have it. `GET /api/tags` responds with every tag,
+/// alphabetically.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub posts: usize,
+}
+
+/// Where a listing left off.
+///
+/// Only the server knows what's inside, clients just hand back what they were given.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Cursor(String);
+impl Cursor {
+    pub fn new(cursor: impl Into<String>) -> Self {
+        Self(cursor.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+/// Query parameters of `GET /api/posts`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListParams {
+    /// Continue from the `next` of a previous page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<Cursor>,
+    /// How many posts to return. The server caps this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Only list posts with this tag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+/// Response of `GET /api/posts`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostPage {
+    pub posts: Vec<PostSummary>,
+    /// Pass this as `after` to get the next page. Missing on the last page.
+    pub next: Option<Cursor>,
+}
+
+/// Whether a post can go by `slug`. Slugs end up in URLs, so they're limited to lowercase ascii
+/// letters, digits and dashes, and can't start or end with a dash.
+pub fn is_valid_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && !slug.starts_with('-')
+        && !slug.ends_with('-')
+        && slug.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+}
+
+/// Body of `POST /api/posts` and `PUT /api/posts/:slug`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostInput {
+    pub slug: String,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub series: Option<PostSeries>,
+    /// Whether to keep the post hidden. Publishing happens by saving with this unset.
+    #[serde(default = "PostInput::default_draft")]
+    pub draft: bool,
+    /// When to publish the post, if not right away. Readers don't see it until then. Leaving this
+    /// out when publishing a scheduled post publishes it right away.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_at: Option<DateTime<Utc>>,
+}
+impl PostInput {
+    fn default_draft() -> bool {
+        true
+    }
+}
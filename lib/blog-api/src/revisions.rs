@@ -0,0 +1,77 @@
+//! Saved versions of posts, and what changed between them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::posts::PostSeries;
+
+/// A saved version of a post, as it shows up in its history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevisionSummary {
+    /// Counts up from 1 with every save of the post.
+    pub number: u32,
+    pub title: String,
+    pub author: String,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// A whole saved version of a post, enough to bring it back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Revision {
+    pub number: u32,
+    pub slug: String,
+    pub title: String,
+    /// The Markdown source of the post.
+    pub body: String,
+    pub summary: Option<String>,
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub series: Option<PostSeries>,
+    pub author: String,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Query parameters of `GET /api/posts/:slug/diff`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffParams {
+    /// The revision to compare against. Defaults to the one right before `to`, or to nothing at all
+    /// if `to` is the first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<u32>,
+    /// Defaults to the latest revision.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<u32>,
+}
+
+/// Response of `GET /api/posts/:slug/diff`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevisionDiff {
+    /// Missing when comparing against nothing.
+    pub from: Option<RevisionSummary>,
+    pub to: RevisionSummary,
+    /// Only the fields that changed, in the order the editor shows them.
+    pub fields: Vec<FieldDiff>,
+}
+
+/// The changes to one field of a post.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    /// `title`, `slug`, `summary`, `tags`, `series` or `body`.
+    pub field: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// A line of a diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub change: Change,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Same,
+    Added,
+    Removed,
+}
@@ -0,0 +1,37 @@
+//! Searching through published posts.
+
+use serde::{Deserialize, Serialize};
+
+use crate::posts::PostSummary;
+
+/// Query parameters of `GET /api/search`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchParams {
+    /// Words to look for. Posts have to have all of them, the last one possibly just started.
+    #[serde(default)]
+    pub q: String,
+    /// How many posts to return. The server caps this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// A post that matched, as `GET /api/search` lists them, best match first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub post: PostSummary,
+    /// The post's title, with whatever matched marked.
+    pub title: Vec<Fragment>,
+    /// The bit of the post that matched best, with whatever matched marked. Plain text, without any
+    /// Markdown.
+    pub snippet: Vec<Fragment>,
+}
+
+/// A run of text in a search result. Results come as runs rather than HTML, so nothing in a post
+/// can sneak markup onto the results page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fragment {
+    pub text: String,
+    /// Whether this is what was searched for.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub matched: bool,
+}
@@ -0,0 +1,256 @@
+//! Everything has to survive a trip through JSON, and keep the shape the other side expects.
+
+use std::fmt::Debug;
+
+use chrono::{TimeZone, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+
+use crate::{
+    auth::{LoginRequest, Session},
+    error::ErrorBody,
+    passkeys::{
+        AssertionResponse, Base64Url, CreationOptions, LoginFinish, LoginStart, Passkey, RegistrationFinish,
+        RegistrationResponse, RegistrationStart, RequestOptions,
+    },
+    posts::{
+        Cursor, Heading, ListParams, Neighbors, Post, PostInput, PostLink, PostPage, PostSeries, PostSummary, Series,
+        SeriesPart, TagCount,
+    },
+    revisions::{Change, DiffLine, DiffParams, FieldDiff, Revision, RevisionDiff, RevisionSummary},
+    search::{Fragment, SearchHit, SearchParams},
+};
+
+fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T) {
+    let json = serde_json::to_string(&value).expect("serializes");
+    let back: T = serde_json::from_str(json.as_str()).expect("deserializes");
+    assert_eq!(back, value, "{json}");
+}
+
+fn series(part: u32) -> PostSeries {
+    PostSeries { slug: "getting-started".to_owned(), title: "Getting started".to_owned(), part }
+}
+
+fn summary() -> PostSummary {
+    PostSummary {
+        slug: "hello-world".to_owned(),
+        title: "Hello, world".to_owned(),
+        summary: Some("The first post.".to_owned()),
+        tags: vec!["rust".to_owned(), "wasm".to_owned()],
+        series: Some(series(1)),
+        author: "Benjamin Xu".to_owned(),
+        published_at: Some(Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap()),
+        updated_at: Utc.with_ymd_and_hms(2024, 5, 2, 8, 0, 0).unwrap(),
+        draft: false,
+    }
+}
+
+#[test]
+fn posts_round_trip() {
+    round_trip(summary());
+    round_trip(PostSummary { summary: None, series: None, published_at: None, draft: true, ..summary() });
+    round_trip(Post {
+        slug: "hello-world".to_owned(),
+        title: "Hello, world".to_owned(),
+        body: "# Hello\n\n<script>\"quoted\"</script>".to_owned(),
+        html: "<h1 id=\"hello\">Hello</h1>".to_owned(),
+        toc: vec![Heading { level: 1, id: "hello".to_owned(), title: "Hello".to_owned() }],
+        reading_minutes: 1,
+        summary: None,
+        tags: vec![],
+        series: Some(series(2)),
+        author: "Benjamin Xu".to_owned(),
+        created_at: Utc.with_ymd_and_hms(2024, 4, 30, 0, 0, 0).unwrap(),
+        updated_at: Utc.with_ymd_and_hms(2024, 5, 2, 8, 0, 0).unwrap(),
+        published_at: None,
+        draft: true,
+        neighbors: Neighbors {
+            newer: None,
+            older: Some(PostLink { slug: "before".to_owned(), title: "Before".to_owned() }),
+        },
+        series_parts: vec![
+            SeriesPart { part: 1, slug: "before".to_owned(), title: "Before".to_owned() },
+            SeriesPart { part: 2, slug: "hello-world".to_owned(), title: "Hello, world".to_owned() },
+        ],
+    });
+    round_trip(Series { slug: "getting-started".to_owned(), title: "Getting started".to_owned(), posts: vec![summary()] });
+    round_trip(vec![TagCount { tag: "rust".to_owned(), posts: 3 }]);
+    round_trip(PostPage { posts: vec![summary(), summary()], next: Some(Cursor::new("1714566600000000-3")) });
+    round_trip(PostPage { posts: vec![], next: None });
+    round_trip(PostInput {
+        slug: "hello-world".to_owned(),
+        title: "Hello, world".to_owned(),
+        body: "Hi!".to_owned(),
+        summary: Some("Short.".to_owned()),
+        tags: vec!["rust".to_owned()],
+        series: Some(series(1)),
+        draft: false,
+        publish_at: None,
+    });
+    round_trip(PostInput {
+        slug: "later".to_owned(),
+        title: "Later".to_owned(),
+        body: "Soon.".to_owned(),
+        summary: None,
+        tags: vec![],
+        series: None,
+        draft: false,
+        publish_at: Some(Utc.with_ymd_and_hms(2024, 6, 1, 9, 30, 0).unwrap()),
+    });
+    round_trip(ListParams::default());
+    round_trip(ListParams { after: Some(Cursor::new("abc")), limit: Some(5), tag: Some("rust".to_owned()) });
+}
+
+fn revision_summary(number: u32) -> RevisionSummary {
+    RevisionSummary {
+        number,
+        title: "Hello, world".to_owned(),
+        author: "Benjamin Xu".to_owned(),
+        saved_at: Utc.with_ymd_and_hms(2024, 5, 2, 8, 0, 0).unwrap(),
+    }
+}
+
+#[test]
+fn revisions_round_trip() {
+    round_trip(vec![revision_summary(2), revision_summary(1)]);
+    round_trip(Revision {
+        number: 1,
+        slug: "hello-world".to_owned(),
+        title: "Hello, world".to_owned(),
+        body: "Hi!".to_owned(),
+        summary: None,
+        tags: vec!["rust".to_owned()],
+        series: Some(series(1)),
+        author: "Benjamin Xu".to_owned(),
+        saved_at: Utc.with_ymd_and_hms(2024, 5, 2, 8, 0, 0).unwrap(),
+    });
+    round_trip(DiffParams::default());
+    round_trip(DiffParams { from: Some(1), to: Some(3) });
+    round_trip(RevisionDiff {
+        from: Some(revision_summary(1)),
+        to: revision_summary(2),
+        fields: vec![FieldDiff {
+            field: "body".to_owned(),
+            lines: vec![
+                DiffLine { change: Change::Same, text: "Hi!".to_owned() },
+                DiffLine { change: Change::Removed, text: "Bye.".to_owned() },
+                DiffLine { change: Change::Added, text: "See you.".to_owned() },
+            ],
+        }],
+    });
+    round_trip(RevisionDiff { from: None, to: revision_summary(1), fields: vec![] });
+}
+
+#[test]
+fn errors_and_auth_round_trip() {
+    round_trip(ErrorBody { status: 404, error: "Not Found".to_owned(), message: None });
+    round_trip(ErrorBody { status: 409, error: "Conflict".to_owned(), message: Some("Taken.".to_owned()) });
+    round_trip(LoginRequest { username: "ben".to_owned(), password: "hunter2".to_owned() });
+    round_trip(Session {
+        username: "ben".to_owned(),
+        expires_at: Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap(),
+        csrf_token: "token".to_owned(),
+    });
+}
+
+fn bytes(bytes: &[u8]) -> Base64Url {
+    Base64Url(bytes.to_vec())
+}
+
+#[test]
+fn passkeys_round_trip() {
+    let options = CreationOptions {
+        challenge: bytes(&[1, 2, 3]),
+        rp_id: "benxu.dev".to_owned(),
+        rp_name: "Benjamin Xu".to_owned(),
+        user_id: bytes(&[0, 0, 0, 1]),
+        user_name: "ben".to_owned(),
+        user_display_name: "Benjamin Xu".to_owned(),
+        algorithms: vec![-7],
+        exclude_credentials: vec![bytes(&[9; 16])],
+        timeout_ms: 60_000,
+    };
+    round_trip(RegistrationStart { ceremony: "c".to_owned(), options });
+    round_trip(RegistrationFinish {
+        ceremony: "c".to_owned(),
+        name: "Laptop".to_owned(),
+        response: RegistrationResponse {
+            id: bytes(&[9; 16]),
+            client_data_json: bytes(b"{}"),
+            attestation_object: bytes(&[0xa0]),
+        },
+    });
+    let options = RequestOptions {
+        challenge: bytes(&[4, 5, 6]),
+        rp_id: "benxu.dev".to_owned(),
+        allow_credentials: vec![],
+        timeout_ms: 60_000,
+    };
+    round_trip(LoginStart { ceremony: "c".to_owned(), options });
+    let response = AssertionResponse {
+        id: bytes(&[9; 16]),
+        client_data_json: bytes(b"{}"),
+        authenticator_data: bytes(&[0; 37]),
+        signature: bytes(&[0x30]),
+        user_handle: Some(bytes(&[0, 0, 0, 1])),
+    };
+    round_trip(LoginFinish { ceremony: "c".to_owned(), response: response.clone() });
+    round_trip(LoginFinish { ceremony: "c".to_owned(), response: AssertionResponse { user_handle: None, ..response } });
+    round_trip(Passkey {
+        id: bytes(&[9; 16]),
+        name: "Laptop".to_owned(),
+        created_at: Utc.with_ymd_and_hms(2024, 5, 2, 8, 0, 0).unwrap(),
+        last_used_at: None,
+    });
+}
+
+#[test]
+fn search_round_trip() {
+    round_trip(SearchParams::default());
+    round_trip(SearchParams { q: "rust \"wasm\"".to_owned(), limit: Some(5) });
+    round_trip(vec![SearchHit {
+        post: summary(),
+        title: vec![Fragment { text: "Hello, world".to_owned(), matched: false }],
+        snippet: vec![
+            Fragment { text: "Say ".to_owned(), matched: false },
+            Fragment { text: "hello".to_owned(), matched: true },
+            Fragment { text: " <b>back</b>".to_owned(), matched: false },
+        ],
+    }]);
+}
+
+#[test]
+fn wire_format() {
+    // Cursors are plain strings on the wire.
+    let page = PostPage { posts: vec![], next: Some(Cursor::new("abc")) };
+    assert_eq!(serde_json::to_value(&page).unwrap(), json!({ "posts": [], "next": "abc" }));
+
+    // Unset parameters stay out of the query string.
+    assert_eq!(serde_json::to_value(ListParams::default()).unwrap(), json!({}));
+
+    // Timestamps are RFC 3339.
+    let value = serde_json::to_value(summary()).unwrap();
+    assert_eq!(value["published_at"], json!("2024-05-01T12:30:00Z"));
+
+    // Posts are saved as drafts unless asked otherwise.
+    let input: PostInput = serde_json::from_value(json!({ "slug": "a", "title": "A", "body": "" })).unwrap();
+    assert!(input.draft);
+    assert_eq!(input.summary, None);
+    assert!(input.tags.is_empty());
+    assert_eq!(input.series, None);
+
+    // Bytes are unpadded base64url, and anything else is refused.
+    assert_eq!(serde_json::to_value(bytes(&[0xfb, 0xff])).unwrap(), json!("-_8"));
+    assert!(serde_json::from_value::<Base64Url>(json!("+/8=")).is_err());
+
+    // Only matches say so.
+    let fragment = Fragment { text: "a".to_owned(), matched: false };
+    assert_eq!(serde_json::to_value(fragment).unwrap(), json!({ "text": "a" }));
+
+    // Changes are plain words.
+    assert_eq!(serde_json::to_value(Change::Added).unwrap(), json!("added"));
+
+    // The error envelope matches what the servers send.
+    let error: ErrorBody = serde_json::from_value(json!({ "status": 404, "error": "Not Found", "message": null })).unwrap();
+    assert_eq!(error.status, 404);
+}
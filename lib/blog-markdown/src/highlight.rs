@@ -0,0 +1,162 @@
+//! Syntax highlighting for fenced code blocks, done at render time.
+//!
+//! Highlighted code is made of `<span class="hl-...">`s, styled by [`stylesheet`]. Every line is
+//! wrapped in a `<span class="line">`, so that lines can be numbered and picked out.
+//!
+//! The fence's info string takes the language, then any of:
+//! - `numbered` to number the lines.
+//! - `highlight=2-4,7` to pick out lines, counting from 1.
+//!
+//! ````markdown
+//! ```rust numbered highlight=2
+//! fn main() {
+//!     println!("Hello!");
+//! }
+//! ```
+//! ````
+
+use std::{fmt::Write, ops::RangeInclusive, sync::OnceLock};
+
+use syntect::{
+    highlighting::ThemeSet,
+    html::{css_for_theme_with_class_style, line_tokens_to_classed_spans, ClassStyle},
+    parsing::{ParseState, Scope, ScopeStack, SyntaxReference, SyntaxSet},
+    util::LinesWithEndings,
+};
+
+/// Prefixed so that highlighting can't clash with the rest of the site's classes.
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+/// The site is always dark, so the theme is too.
+const THEME: &str = "base16-ocean.dark";
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// The stylesheet for highlighted code.
+pub fn stylesheet() -> &'static str {
+    static STYLESHEET: OnceLock<String> = OnceLock::new();
+    STYLESHEET.get_or_init(|| {
+        let themes = ThemeSet::load_defaults();
+        css_for_theme_with_class_style(&themes.themes[THEME], CLASS_STYLE).expect("bundled theme converts to css")
+    })
+}
+
+/// What the info string of a fence asked for.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Fence<'a> {
+    pub language: Option<&'a str>,
+    pub numbered: bool,
+    pub highlighted: Vec<RangeInclusive<usize>>,
+}
+impl<'a> Fence<'a> {
+    /// Parses an info string. Anything not understood is ignored.
+    pub fn parse(info: &'a str) -> Self {
+        let mut fence = Fence::default();
+        for (idx, token) in info.split_whitespace().enumerate() {
+            if token == "numbered" {
+                fence.numbered = true;
+            } else if let Some(ranges) = token.strip_prefix("highlight=") {
+                fence.highlighted.extend(ranges.split(',').filter_map(parse_range));
+            } else if idx == 0 {
+                fence.language = Some(token);
+            }
+        }
+        fence
+    }
+
+    fn is_highlighted(&self, line: usize) -> bool {
+        self.highlighted.iter().any(|range| range.contains(&line))
+    }
+}
+
+fn parse_range(range: &str) -> Option<RangeInclusive<usize>> {
+    match range.split_once('-') {
+        Some((start, end)) => Some(start.parse().ok()?..=end.parse().ok()?),
+        None => range.parse().ok().map(|line| line..=line),
+    }
+}
+
+fn find_syntax(language: &str) -> Option<&'static SyntaxReference> {
+    let syntaxes = syntaxes();
+    let language = match language.to_ascii_lowercase().as_str() {
+        "shell" | "console" | "zsh" => "bash".to_owned(),
+        other => other.to_owned(),
+    };
+    syntaxes.find_syntax_by_token(language.as_str())
+}
+
+/// The opening tags for everything the last line left open.
+fn reopen(stack: &ScopeStack) -> String {
+    let mut out = String::new();
+    for scope in stack.as_slice() {
+        out.push_str(r#"<span class=""#);
+        out.push_str(classes(*scope).as_str());
+        out.push_str(r#"">"#);
+    }
+    out
+}
+
+/// The classes for a scope, matching what syntect itself emits.
+fn classes(scope: Scope) -> String {
+    scope.build_string()
+        .split('.')
+        .map(|atom| format!("hl-{atom}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Renders a fenced code block.
+pub fn code_block(info: &str, code: &str) -> String {
+    let fence = Fence::parse(info);
+    let syntax = fence.language.and_then(find_syntax);
+
+    let mut out = String::from(r#"<pre class="code"#);
+    if fence.numbered {
+        out.push_str(" numbered");
+    }
+    out.push_str(r#""><code"#);
+    if let Some(language) = fence.language.filter(|_| syntax.is_some()) {
+        let _ = write!(out, r#" class="language-{}""#, escape(language));
+    }
+    out.push('>');
+
+    let mut parse_state = syntax.map(ParseState::new);
+    let mut stack = ScopeStack::new();
+    for (idx, line) in LinesWithEndings::from(code).enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        out.push_str(if fence.is_highlighted(idx + 1) { r#"<span class="line highlighted">"# } else { r#"<span class="line">"# });
+
+        let text = line.trim_end_matches(['\n', '\r']);
+        // Lines the parser chokes on come out plain, rather than losing the whole block.
+        let ops = parse_state.as_mut().and_then(|state| state.parse_line(line, syntaxes()).ok());
+        let highlighted = ops.and_then(|ops| {
+            let mut html = reopen(&stack);
+            // Spans get closed at the end of every line, so the line ending doesn't need to be
+            // part of any of them.
+            let ops: Vec<_> = ops.into_iter().map(|(idx, op)| (idx.min(text.len()), op)).collect();
+            let (spans, _) = line_tokens_to_classed_spans(text, ops.as_slice(), CLASS_STYLE, &mut stack).ok()?;
+            html.push_str(spans.as_str());
+            html.push_str("</span>".repeat(stack.len()).as_str());
+            Some(html)
+        });
+        match highlighted {
+            Some(html) => out.push_str(html.as_str()),
+            None => {
+                out.push_str(escape(text).as_str());
+                stack = ScopeStack::new();
+            },
+        }
+        out.push_str("</span>");
+    }
+    out.push_str("</code></pre>");
+    out
+}
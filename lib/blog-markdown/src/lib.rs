@@ -0,0 +1,281 @@
+//! Renders blog posts from Markdown.
+//!
+//! Posts are CommonMark with the GitHub extensions: tables, footnotes, task lists and
+//! strikethrough. Raw HTML is allowed, but everything goes through a sanitizer on the way out.
+//!
+//! The server uses this to render posts and the editor uses it for its live preview, so this has
+//! to keep building for `wasm32-unknown-unknown`. Code blocks are only highlighted with the
+//! `highlight` feature, which the server turns on.
+
+use std::{borrow::Cow, collections::{HashMap, HashSet}, fmt::Write};
+
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "highlight")]
+pub mod highlight;
+#[cfg(test)]
+mod tests;
+
+/// How fast readers are assumed to read, for [`Rendered::reading_minutes`].
+pub const WORDS_PER_MINUTE: usize = 200;
+
+/// A heading, as listed in the table of contents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TocEntry {
+    /// 1 for `#`, 2 for `##` and so on.
+    pub level: u8,
+    /// The anchor of the heading, without the `#`.
+    pub id: String,
+    /// The text of the heading, without any formatting.
+    pub title: String,
+}
+
+/// A rendered post.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rendered {
+    /// Sanitized HTML, safe to put on the page as is.
+    pub html: String,
+    /// Every heading, in order.
+    pub toc: Vec<TocEntry>,
+    pub words: usize,
+    /// Never less than a minute.
+    pub reading_minutes: usize,
+}
+impl Rendered {
+    /// The table of contents as nested lists, or nothing if the post has no headings.
+    pub fn toc_html(&self) -> String {
+        toc_html(self.toc.as_slice())
+    }
+}
+
+fn options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+}
+
+/// Renders a post.
+pub fn render(source: &str) -> Rendered {
+    let mut ids = Ids::default();
+    // Footnotes keep the ids their labels give them, so headings have to steer clear of those,
+    // including ones further down.
+    for event in Parser::new_ext(source, options()) {
+        if let Event::FootnoteReference(label) | Event::Start(Tag::FootnoteDefinition(label)) = event {
+            ids.reserve(footnote_id(label).into_string());
+        }
+    }
+    let mut toc = vec![];
+    let mut words = 0;
+    // Where the open heading starts in `events`, and its text so far.
+    let mut heading: Option<(usize, String)> = None;
+    // The info string and code of the open fenced code block, if it's getting highlighted.
+    let mut code_block: Option<(CowStr, String)> = None;
+
+    let mut events = vec![];
+    for event in Parser::new_ext(source, options()) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => {
+                heading = Some((events.len(), String::new()));
+                events.push(event);
+            },
+            Event::End(TagEnd::Heading(level)) => {
+                let (start, title) = heading.take().expect("headings are closed after being opened");
+                let id = ids.claim(slugify(title.as_str()));
+                if let Event::Start(Tag::Heading { id: heading_id, .. }) = &mut events[start] {
+                    *heading_id = Some(id.clone().into());
+                }
+                events.push(Event::InlineHtml(format!(r##"<a class="anchor" href="#{id}" aria-hidden="true">#</a>"##).into()));
+                events.push(event);
+                toc.push(TocEntry { level: level as u8, id, title: title.trim().to_owned() });
+            },
+            #[cfg(feature = "highlight")]
+            Event::Start(Tag::CodeBlock(pulldown_cmark::CodeBlockKind::Fenced(info))) => {
+                code_block = Some((info, String::new()));
+            },
+            #[cfg(feature = "highlight")]
+            Event::End(TagEnd::CodeBlock) if code_block.is_some() => {
+                let (info, code) = code_block.take().expect("checked by the guard");
+                events.push(Event::Html(highlight::code_block(info.as_ref(), code.as_str()).into()));
+            },
+            Event::Text(ref text) | Event::Code(ref text) => {
+                words += text.split_whitespace().count();
+                if let Some((_, title)) = heading.as_mut() {
+                    title.push_str(text);
+                }
+                if let Some((_, code)) = code_block.as_mut() {
+                    code.push_str(text);
+                } else {
+                    events.push(event);
+                }
+            },
+            // Footnotes get their label as their id, which would otherwise collide with headings.
+            Event::FootnoteReference(label) => events.push(Event::FootnoteReference(footnote_id(label))),
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                events.push(Event::Start(Tag::FootnoteDefinition(footnote_id(label))));
+            },
+            event => events.push(event),
+        }
+    }
+
+    let mut html = String::with_capacity(source.len() * 3 / 2);
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+
+    Rendered {
+        html: sanitizer().clean(html.as_str()).to_string(),
+        toc,
+        words,
+        reading_minutes: words.div_ceil(WORDS_PER_MINUTE).max(1),
+    }
+}
+
+/// Just the words of a post, for searching through. Blocks end up on lines of their own, and raw
+/// HTML is left out.
+///
+/// The SQLite store keeps this in its search index, changes here need its `SEARCH_INDEX_VERSION`
+/// bumped.
+pub fn plain_text(source: &str) -> String {
+    let mut text = String::with_capacity(source.len());
+    for event in Parser::new_ext(source, options()) {
+        match event {
+            Event::Text(words) | Event::Code(words) => text.push_str(words.as_ref()),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::End(
+                TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::CodeBlock | TagEnd::Item | TagEnd::TableCell,
+            ) if !text.ends_with(char::is_whitespace) => text.push('\n'),
+            _ => {},
+        }
+    }
+    text.trim_end().to_owned()
+}
+
+fn footnote_id(label: CowStr) -> CowStr {
+    format!("fn-{label}").into()
+}
+
+/// Turns heading text into something that reads well after a `#`.
+fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if (c.is_whitespace() || c == '-' || c == '_') && !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "section".to_owned()
+    } else {
+        slug.to_owned()
+    }
+}
+
+/// Hands out heading ids, numbering repeats the way GitHub does, but never one already in use.
+#[derive(Default)]
+struct Ids {
+    taken: HashSet<String>,
+    /// The last number each slug got, so that numbering carries on from there.
+    numbered: HashMap<String, usize>,
+}
+impl Ids {
+    /// Keeps an id from being handed out.
+    fn reserve(&mut self, id: String) {
+        self.taken.insert(id);
+    }
+
+    fn claim(&mut self, slug: String) -> String {
+        let number = self.numbered.entry(slug.clone()).or_insert(0);
+        let mut id = slug.clone();
+        while self.taken.contains(&id) {
+            *number += 1;
+            id = format!("{slug}-{number}");
+        }
+        self.taken.insert(id.clone());
+        id
+    }
+}
+
+/// Renders a table of contents as nested `<ol>`s.
+///
+/// Skipped levels (a `###` straight after a `#`) don't get empty lists of their own: a heading
+/// nests under the closest heading before it with a lower level.
+pub fn toc_html(toc: &[TocEntry]) -> String {
+    let Some(top) = toc.iter().map(|entry| entry.level).min() else {
+        return String::new();
+    };
+    let mut out = String::from(r#"<nav class="toc"><ol>"#);
+    // The level of the last entry in each open list, innermost last.
+    let mut open = vec![top];
+    for (idx, entry) in toc.iter().enumerate() {
+        if idx > 0 {
+            if entry.level > open[open.len() - 1] {
+                out.push_str("<ol>");
+                open.push(entry.level);
+            } else {
+                out.push_str("</li>");
+                while open.len() > 1 && open[open.len() - 2] >= entry.level {
+                    out.push_str("</ol></li>");
+                    open.pop();
+                }
+            }
+        }
+        *open.last_mut().expect("the outermost list stays open") = entry.level;
+        let _ = write!(out, r##"<li><a href="#{}">{}</a>"##, entry.id, escape(entry.title.as_str()));
+    }
+    out.push_str("</li>");
+    out.push_str(&"</ol></li>".repeat(open.len() - 1));
+    out.push_str("</ol></nav>");
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Strips anything dangerous, while keeping what the renderer itself produces.
+fn sanitizer() -> ammonia::Builder<'static> {
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["type", "checked", "disabled"])
+        .add_tag_attributes("th", ["style"])
+        .add_tag_attributes("td", ["style"])
+        .add_tag_attributes("code", ["class"])
+        .add_tag_attributes("pre", ["class"])
+        .add_tag_attributes("span", ["class"])
+        .add_tag_attributes("a", ["aria-hidden"])
+        .add_tag_attributes("div", ["id"])
+        .add_allowed_classes("a", ["anchor"])
+        .add_allowed_classes("sup", ["footnote-reference", "footnote-definition-label"])
+        .add_allowed_classes("div", ["footnote-definition"])
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("input", "type") => (value == "checkbox").then_some(Cow::Borrowed(value)),
+            ("th" | "td", "style") => {
+                matches!(value, "text-align: left" | "text-align: center" | "text-align: right")
+                    .then_some(Cow::Borrowed(value))
+            },
+            // Highlighted code.
+            ("pre" | "span", "class") => {
+                let allowed: Vec<_> = value.split_whitespace()
+                    .filter(|class| {
+                        matches!((element, *class), ("pre", "code" | "numbered") | ("span", "line" | "highlighted"))
+                            || (element == "span" && class.starts_with("hl-"))
+                    })
+                    .collect();
+                (!allowed.is_empty()).then(|| Cow::Owned(allowed.join(" ")))
+            },
+            ("code", "class") => {
+                let language = value.strip_prefix("language-")?;
+                language.chars().all(|c| c.is_ascii_alphanumeric() || "+#-_".contains(c))
+                    .then_some(Cow::Borrowed(value))
+            },
+            _ => Some(Cow::Borrowed(value)),
+        });
+    for heading in ["h1", "h2", "h3", "h4", "h5", "h6"] {
+        builder.add_tag_attributes(heading, ["id"]);
+    }
+    builder
+}
+
@@ -0,0 +1,172 @@
+use crate::{plain_text, render, toc_html, TocEntry};
+
+#[test]
+fn extensions() {
+    let rendered = render("\
+| Left | Center |
+|:-----|:------:|
+| a    | b      |
+
+- [x] done
+- [ ] todo
+
+~~gone~~ and a footnote[^note].
+
+[^note]: The note.
+");
+    let html = rendered.html.as_str();
+    assert!(html.contains(r#"<th style="text-align: left">Left</th>"#), "{html}");
+    assert!(html.contains(r#"<td style="text-align: center">b</td>"#), "{html}");
+    assert!(html.contains(r#"<input disabled="" type="checkbox" checked="">"#), "{html}");
+    assert!(html.contains("<del>gone</del>"), "{html}");
+    assert!(html.contains(r##"<sup class="footnote-reference"><a href="#fn-note""##), "{html}");
+    assert!(html.contains(r#"<div class="footnote-definition" id="fn-note">"#), "{html}");
+}
+
+#[test]
+fn sanitizes() {
+    let rendered = render("\
+<script>alert(1)</script>
+
+<img src=x onerror=alert(1)> [link](javascript:alert(1)) <kbd>Ctrl</kbd>
+
+<p style=\"color: red\">styled</p>
+");
+    let html = rendered.html.as_str();
+    assert!(!html.contains("script"), "{html}");
+    assert!(!html.contains("onerror"), "{html}");
+    assert!(!html.contains("javascript:"), "{html}");
+    assert!(!html.contains("style="), "{html}");
+    assert!(html.contains("<kbd>Ctrl</kbd>"), "{html}");
+}
+
+#[test]
+fn code_classes_are_limited() {
+    let html = render("```rust\nfn main() {}\n```\n\n```\"><script>\nx\n```\n").html;
+    assert!(html.contains(r#"<code class="language-rust">"#), "{html}");
+    assert!(!html.contains("script"), "{html}");
+}
+
+#[test]
+fn headings() {
+    let rendered = render("\
+# Getting started
+## Install `cargo`
+## Install `cargo`
+### Why?!
+# Done
+");
+    let ids: Vec<_> = rendered.toc.iter().map(|e| (e.level, e.id.as_str(), e.title.as_str())).collect();
+    assert_eq!(ids, [
+        (1, "getting-started", "Getting started"),
+        (2, "install-cargo", "Install cargo"),
+        (2, "install-cargo-1", "Install cargo"),
+        (3, "why", "Why?!"),
+        (1, "done", "Done"),
+    ]);
+    assert!(rendered.html.contains(
+        r##"<h2 id="install-cargo-1">Install <code>cargo</code><a class="anchor" href="#install-cargo-1" aria-hidden="true" rel="noopener noreferrer">#</a></h2>"##
+    ), "{}", rendered.html);
+}
+
+#[test]
+fn heading_ids_never_repeat() {
+    let rendered = render("## X
+## X 1
+## X
+## Fn note
+
+A footnote[^note].
+
+[^note]: Shares its id with nothing.
+");
+    let ids: Vec<_> = rendered.toc.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["x", "x-1", "x-2", "fn-note-1"]);
+    assert_eq!(rendered.html.matches(r#"id="fn-note""#).count(), 1, "{}", rendered.html);
+}
+
+#[test]
+fn table_of_contents() {
+    let entry = |level, id: &str| TocEntry { level, id: id.to_owned(), title: id.to_owned() };
+    assert_eq!(toc_html(&[]), "");
+    assert_eq!(
+        toc_html(&[entry(2, "a"), entry(3, "b"), entry(4, "c"), entry(2, "d")]),
+        r##"<nav class="toc"><ol><li><a href="#a">a</a><ol><li><a href="#b">b</a><ol><li><a href="#c">c</a></li></ol></li></ol></li><li><a href="#d">d</a></li></ol></nav>"##,
+    );
+    // Jumping straight from `##` to `####` only nests once.
+    assert_eq!(
+        toc_html(&[entry(2, "a"), entry(4, "b"), entry(2, "c")]),
+        r##"<nav class="toc"><ol><li><a href="#a">a</a><ol><li><a href="#b">b</a></li></ol></li><li><a href="#c">c</a></li></ol></nav>"##,
+    );
+    // Headings after a skipped level sit next to each other, and whatever comes between two
+    // levels nests under the one above it.
+    assert_eq!(
+        toc_html(&[entry(2, "a"), entry(4, "b"), entry(4, "c"), entry(3, "d"), entry(4, "e")]),
+        r##"<nav class="toc"><ol><li><a href="#a">a</a><ol><li><a href="#b">b</a></li><li><a href="#c">c</a></li><li><a href="#d">d</a><ol><li><a href="#e">e</a></li></ol></li></ol></li></ol></nav>"##,
+    );
+    // Starting below the top level.
+    assert_eq!(
+        toc_html(&[entry(3, "a"), entry(2, "b"), entry(3, "c")]),
+        r##"<nav class="toc"><ol><li><a href="#a">a</a></li><li><a href="#b">b</a><ol><li><a href="#c">c</a></li></ol></li></ol></nav>"##,
+    );
+}
+
+#[test]
+fn reading_time() {
+    assert_eq!(render("").reading_minutes, 1);
+    let long = "word ".repeat(450);
+    let rendered = render(long.as_str());
+    assert_eq!(rendered.words, 450);
+    assert_eq!(rendered.reading_minutes, 3);
+}
+
+#[test]
+fn plain_text_keeps_the_words() {
+    let text = plain_text("\
+# A *title*
+
+Some **bold** text with `code` and [a link](https://example.com),
+over two lines.<br>
+
+```rust
+fn main() {}
+```
+
+| a | b |
+|---|---|
+");
+    assert_eq!(text, "A title\nSome bold text with code and a link, over two lines.\nfn main() {}\na\nb");
+}
+
+#[cfg(feature = "highlight")]
+mod highlight {
+    use crate::{highlight::Fence, render};
+
+    #[test]
+    fn fences() {
+        let fence = Fence::parse("rust numbered highlight=2-4,7");
+        assert_eq!(fence, Fence { language: Some("rust"), numbered: true, highlighted: vec![2..=4, 7..=7] });
+        assert_eq!(Fence::parse("numbered"), Fence { numbered: true, ..Fence::default() });
+        assert_eq!(Fence::parse(""), Fence::default());
+    }
+
+    #[test]
+    fn highlights() {
+        let html = render("```rust numbered highlight=2\n/* one\ntwo */\nfn main() {}\n```\n").html;
+        assert!(html.starts_with(r#"<pre class="code numbered"><code class="language-rust">"#), "{html}");
+        // The comment spans two lines, and each line gets its own balanced spans.
+        let lines: Vec<_> = html.lines().collect();
+        assert_eq!(lines.len(), 3, "{html}");
+        for line in &lines {
+            assert_eq!(line.matches("<span").count(), line.matches("</span>").count(), "{line}");
+        }
+        assert!(lines[1].starts_with(r#"<span class="line highlighted"><span class="hl-source hl-rust"><span class="hl-comment hl-block hl-rust">two "#), "{html}");
+        assert!(lines[2].contains(r#"<span class="hl-storage hl-type hl-function hl-rust">fn</span>"#), "{html}");
+    }
+
+    #[test]
+    fn unknown_languages_stay_plain() {
+        let html = render("```nonsense\n<b>x</b>\n```\n").html;
+        assert_eq!(html, "<pre class=\"code\"><code><span class=\"line\">&lt;b&gt;x&lt;/b&gt;</span></code></pre>");
+    }
+}
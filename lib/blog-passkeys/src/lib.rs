@@ -0,0 +1,278 @@
+//! The server side of WebAuthn, as much of it as logging in with passkeys takes.
+//!
+//! [`RelyingParty`] makes the options for both ceremonies and checks what comes back from the
+//! browser. Remembering challenges and storing credentials is up to the caller.
+//!
+//! Only ES256 keys are accepted, which every passkey provider supports. Attestation isn't asked
+//! for or checked: a personal site has no list of authenticators to trust anyway.
+
+use std::fmt::{self, Display, Formatter};
+
+use blog_api::passkeys::{AssertionResponse, Base64Url, CreationOptions, RegistrationResponse, RequestOptions};
+use ciborium::Value;
+use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use rand::{rngs::OsRng, RngCore};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+#[cfg(test)]
+mod tests;
+
+/// The COSE identifier of ECDSA with P-256 and SHA-256.
+pub const ES256: i32 = -7;
+
+/// How long the browser gives the user to finish a ceremony.
+pub const TIMEOUT_MS: u32 = 5 * 60 * 1000;
+
+/// The user was there, say by touching the authenticator.
+const USER_PRESENT: u8 = 0x01;
+/// The user proved who they are to the authenticator, with a PIN or a fingerprint.
+const USER_VERIFIED: u8 = 0x04;
+/// The authenticator data carries a new credential.
+const ATTESTED_CREDENTIAL: u8 = 0x40;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Something couldn't be read.
+    Malformed(&'static str),
+    /// The response is for another ceremony, another site or another user.
+    Mismatch(&'static str),
+    /// The authenticator didn't check that the user is who they say they are.
+    NotVerified,
+    /// The key isn't an ES256 one.
+    UnsupportedKey,
+    BadSignature,
+    /// The authenticator's counter went backwards, so there might be a clone of it around.
+    CounterWentBack,
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::Malformed(what) => write!(f, "malformed {what}"),
+            Error::Mismatch(what) => write!(f, "{what} doesn't match"),
+            Error::NotVerified => write!(f, "the authenticator didn't verify the user"),
+            Error::UnsupportedKey => write!(f, "only ES256 keys are supported"),
+            Error::BadSignature => write!(f, "the signature doesn't check out"),
+            Error::CounterWentBack => write!(f, "the authenticator's signature counter went backwards"),
+        }
+    }
+}
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A fresh challenge for a ceremony.
+pub fn challenge() -> Vec<u8> {
+    let mut challenge = vec![0; 32];
+    OsRng.fill_bytes(challenge.as_mut_slice());
+    challenge
+}
+
+/// A registered passkey, as far as checking logins goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credential {
+    pub id: Vec<u8>,
+    /// An uncompressed SEC1 P-256 point.
+    pub public_key: Vec<u8>,
+    /// How many times the authenticator says it's been used. Stays at 0 for most synced passkeys.
+    pub sign_count: u32,
+}
+
+/// Someone a passkey gets made for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserEntity {
+    /// Handed back by the authenticator when logging in. Shouldn't say anything about the user.
+    pub id: Vec<u8>,
+    pub name: String,
+    pub display_name: String,
+}
+
+/// The site passkeys get made for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelyingParty {
+    /// The domain passkeys are tied to.
+    pub id: String,
+    /// Shown by the browser when making a passkey.
+    pub name: String,
+    /// Where the pages asking for passkeys are served from, like `https://benxu.dev`.
+    pub origin: String,
+}
+impl RelyingParty {
+    /// A site served from `origin`, tying passkeys to its host.
+    pub fn new(origin: impl Into<String>, name: impl Into<String>) -> Self {
+        let origin = origin.into();
+        let host = origin.split_once("://").map_or(origin.as_str(), |(_, rest)| rest);
+        let id = host.split([':', '/']).next().unwrap_or_default().to_owned();
+        Self { id, name: name.into(), origin }
+    }
+
+    /// Options for making a passkey for `user`, who already has the passkeys `existing`.
+    pub fn creation_options(&self, challenge: &[u8], user: &UserEntity, existing: &[Vec<u8>]) -> CreationOptions {
+        CreationOptions {
+            challenge: challenge.to_vec().into(),
+            rp_id: self.id.clone(),
+            rp_name: self.name.clone(),
+            user_id: user.id.clone().into(),
+            user_name: user.name.clone(),
+            user_display_name: user.display_name.clone(),
+            algorithms: vec![ES256],
+            exclude_credentials: existing.iter().cloned().map(Base64Url).collect(),
+            timeout_ms: TIMEOUT_MS,
+        }
+    }
+
+    /// Options for logging in with any passkey for the site.
+    pub fn request_options(&self, challenge: &[u8]) -> RequestOptions {
+        RequestOptions {
+            challenge: challenge.to_vec().into(),
+            rp_id: self.id.clone(),
+            allow_credentials: vec![],
+            timeout_ms: TIMEOUT_MS,
+        }
+    }
+
+    /// Checks a new passkey made for `challenge`, returning what's needed to check logins with it.
+    pub fn finish_registration(&self, challenge: &[u8], response: &RegistrationResponse) -> Result<Credential> {
+        self.check_client_data(response.client_data_json.0.as_slice(), "webauthn.create", challenge)?;
+
+        let attestation: Value = ciborium::from_reader(response.attestation_object.0.as_slice())
+            .map_err(|_| Error::Malformed("attestation object"))?;
+        let auth_data = map_entry(&attestation, &Value::from("authData"))
+            .and_then(Value::as_bytes)
+            .ok_or(Error::Malformed("attestation object"))?;
+        let data = self.check_authenticator_data(auth_data)?;
+        if data.flags & ATTESTED_CREDENTIAL == 0 {
+            return Err(Error::Malformed("authenticator data"));
+        }
+
+        let (id, public_key) = attested_credential(data.rest)?;
+        if id != response.id.0.as_slice() {
+            return Err(Error::Mismatch("credential id"));
+        }
+        Ok(Credential {
+            id: id.to_vec(),
+            public_key,
+            sign_count: data.sign_count,
+        })
+    }
+
+    /// Checks a login with `credential` for `challenge`, returning the authenticator's new
+    /// signature count. `user_id` is the id of whoever `credential` belongs to.
+    pub fn finish_login(
+        &self,
+        challenge: &[u8],
+        credential: &Credential,
+        user_id: &[u8],
+        response: &AssertionResponse,
+    ) -> Result<u32> {
+        if response.id.0 != credential.id {
+            return Err(Error::Mismatch("credential id"));
+        }
+        if response.user_handle.as_ref().is_some_and(|handle| handle.0 != user_id) {
+            return Err(Error::Mismatch("user handle"));
+        }
+        let client_data = response.client_data_json.0.as_slice();
+        self.check_client_data(client_data, "webauthn.get", challenge)?;
+        let auth_data = response.authenticator_data.0.as_slice();
+        let data = self.check_authenticator_data(auth_data)?;
+
+        let key = VerifyingKey::from_sec1_bytes(credential.public_key.as_slice()).map_err(|_| Error::UnsupportedKey)?;
+        let signature = Signature::from_der(response.signature.0.as_slice()).map_err(|_| Error::Malformed("signature"))?;
+        let signed = [auth_data, &Sha256::digest(client_data)[..]].concat();
+        key.verify(signed.as_slice(), &signature).map_err(|_| Error::BadSignature)?;
+
+        // Authenticators without a counter always say 0.
+        if (data.sign_count != 0 || credential.sign_count != 0) && data.sign_count <= credential.sign_count {
+            return Err(Error::CounterWentBack);
+        }
+        Ok(data.sign_count)
+    }
+
+    fn check_client_data(&self, json: &[u8], kind: &str, challenge: &[u8]) -> Result<()> {
+        #[derive(Deserialize)]
+        struct ClientData {
+            #[serde(rename = "type")]
+            kind: String,
+            challenge: String,
+            origin: String,
+        }
+
+        let data: ClientData = serde_json::from_slice(json).map_err(|_| Error::Malformed("client data"))?;
+        if data.kind != kind {
+            return Err(Error::Mismatch("ceremony type"));
+        }
+        if data.challenge != Base64Url(challenge.to_vec()).encode() {
+            return Err(Error::Mismatch("challenge"));
+        }
+        if data.origin != self.origin {
+            return Err(Error::Mismatch("origin"));
+        }
+        Ok(())
+    }
+
+    fn check_authenticator_data<'a>(&self, data: &'a [u8]) -> Result<AuthenticatorData<'a>> {
+        let data = AuthenticatorData::parse(data)?;
+        if data.rp_id_hash != &Sha256::digest(self.id.as_bytes())[..] {
+            return Err(Error::Mismatch("relying party"));
+        }
+        if data.flags & USER_PRESENT == 0 || data.flags & USER_VERIFIED == 0 {
+            return Err(Error::NotVerified);
+        }
+        Ok(data)
+    }
+}
+
+/// The part of a response the authenticator itself vouches for.
+struct AuthenticatorData<'a> {
+    rp_id_hash: &'a [u8],
+    flags: u8,
+    sign_count: u32,
+    /// The attested credential and extensions, if any.
+    rest: &'a [u8],
+}
+impl<'a> AuthenticatorData<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self> {
+        let malformed = Error::Malformed("authenticator data");
+        let (rp_id_hash, data) = data.split_at_checked(32).ok_or(malformed.clone())?;
+        let (&flags, data) = data.split_first().ok_or(malformed.clone())?;
+        let (sign_count, rest) = data.split_first_chunk::<4>().ok_or(malformed)?;
+        Ok(Self {
+            rp_id_hash,
+            flags,
+            sign_count: u32::from_be_bytes(*sign_count),
+            rest,
+        })
+    }
+}
+
+/// Reads the id and public key of a newly made credential.
+fn attested_credential(data: &[u8]) -> Result<(&[u8], Vec<u8>)> {
+    let malformed = Error::Malformed("attested credential");
+    // The authenticator's model comes first, which doesn't matter without attestation.
+    let data = data.get(16..).ok_or(malformed.clone())?;
+    let (length, data) = data.split_first_chunk::<2>().ok_or(malformed.clone())?;
+    let (id, data) = data.split_at_checked(u16::from_be_bytes(*length).into()).ok_or(malformed)?;
+    // Extensions might follow the key, reading just the one value leaves them be.
+    let key: Value = ciborium::from_reader(data).map_err(|_| Error::Malformed("public key"))?;
+    Ok((id, es256_key(&key)?))
+}
+
+/// Turns an ES256 COSE key into an uncompressed SEC1 point.
+fn es256_key(key: &Value) -> Result<Vec<u8>> {
+    let int = |label: i64| map_entry(key, &Value::from(label)).and_then(Value::as_integer).and_then(|i| i64::try_from(i).ok());
+    let bytes = |label: i64| map_entry(key, &Value::from(label)).and_then(Value::as_bytes);
+    // EC2 keys, of ES256, on P-256.
+    if int(1) != Some(2) || int(3) != Some(ES256.into()) || int(-1) != Some(1) {
+        return Err(Error::UnsupportedKey);
+    }
+    let (Some(x), Some(y)) = (bytes(-2), bytes(-3)) else {
+        return Err(Error::Malformed("public key"));
+    };
+    let point = [&[0x04], x.as_slice(), y.as_slice()].concat();
+    VerifyingKey::from_sec1_bytes(point.as_slice()).map_err(|_| Error::Malformed("public key"))?;
+    Ok(point)
+}
+
+fn map_entry<'a>(map: &'a Value, key: &Value) -> Option<&'a Value> {
+    map.as_map()?.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
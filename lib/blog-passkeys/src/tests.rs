@@ -0,0 +1,272 @@
+use blog_api::passkeys::{AssertionResponse, Base64Url, RegistrationResponse};
+use ciborium::Value;
+use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+
+use crate::*;
+
+const ORIGIN: &str = "https://benxu.dev";
+
+fn site() -> RelyingParty {
+    RelyingParty::new(ORIGIN, "Ben Xu")
+}
+
+fn user() -> UserEntity {
+    UserEntity { id: 7u64.to_be_bytes().to_vec(), name: "ben".to_owned(), display_name: "Ben Xu".to_owned() }
+}
+
+/// Does what a browser and an authenticator would, and can be made to misbehave.
+struct SoftAuthenticator {
+    key: SigningKey,
+    credential_id: Vec<u8>,
+    user_id: Vec<u8>,
+    rp_id: String,
+    origin: String,
+    flags: u8,
+    /// Stays at 0 when the authenticator doesn't count, like most synced passkeys.
+    counting: bool,
+    sign_count: u32,
+}
+impl SoftAuthenticator {
+    fn new() -> Self {
+        Self {
+            key: SigningKey::random(&mut OsRng),
+            credential_id: challenge(),
+            user_id: user().id,
+            rp_id: "benxu.dev".to_owned(),
+            origin: ORIGIN.to_owned(),
+            flags: USER_PRESENT | USER_VERIFIED,
+            counting: true,
+            sign_count: 0,
+        }
+    }
+
+    fn client_data(&self, kind: &str, challenge: &[u8]) -> Vec<u8> {
+        serde_json::json!({
+            "type": kind,
+            "challenge": Base64Url(challenge.to_vec()).encode(),
+            "origin": self.origin,
+            "crossOrigin": false,
+        }).to_string().into_bytes()
+    }
+
+    fn authenticator_data(&self, flags: u8) -> Vec<u8> {
+        [
+            &Sha256::digest(self.rp_id.as_bytes())[..],
+            &[flags],
+            &self.sign_count.to_be_bytes(),
+        ].concat()
+    }
+
+    fn cose_key(&self) -> Vec<u8> {
+        let point = self.key.verifying_key().to_encoded_point(false);
+        let key = Value::Map(vec![
+            (1.into(), 2.into()),
+            (3.into(), ES256.into()),
+            ((-1).into(), 1.into()),
+            ((-2).into(), Value::Bytes(point.x().unwrap().to_vec())),
+            ((-3).into(), Value::Bytes(point.y().unwrap().to_vec())),
+        ]);
+        let mut bytes = vec![];
+        ciborium::into_writer(&key, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn register(&self, challenge: &[u8]) -> RegistrationResponse {
+        let auth_data = [
+            self.authenticator_data(self.flags | ATTESTED_CREDENTIAL).as_slice(),
+            &[0; 16],
+            &(self.credential_id.len() as u16).to_be_bytes(),
+            self.credential_id.as_slice(),
+            self.cose_key().as_slice(),
+        ].concat();
+        let attestation = Value::Map(vec![
+            ("fmt".into(), "none".into()),
+            ("attStmt".into(), Value::Map(vec![])),
+            ("authData".into(), Value::Bytes(auth_data)),
+        ]);
+        let mut attestation_object = vec![];
+        ciborium::into_writer(&attestation, &mut attestation_object).unwrap();
+        RegistrationResponse {
+            id: self.credential_id.clone().into(),
+            client_data_json: self.client_data("webauthn.create", challenge).into(),
+            attestation_object: attestation_object.into(),
+        }
+    }
+
+    fn assert(&mut self, challenge: &[u8]) -> AssertionResponse {
+        if self.counting {
+            self.sign_count += 1;
+        }
+        let client_data = self.client_data("webauthn.get", challenge);
+        let auth_data = self.authenticator_data(self.flags);
+        let signature: Signature = self.key.sign([auth_data.as_slice(), &Sha256::digest(client_data.as_slice())[..]].concat().as_slice());
+        AssertionResponse {
+            id: self.credential_id.clone().into(),
+            client_data_json: client_data.into(),
+            authenticator_data: auth_data.into(),
+            signature: signature.to_der().as_bytes().to_vec().into(),
+            user_handle: Some(self.user_id.clone().into()),
+        }
+    }
+}
+
+#[test]
+fn rp_id_is_the_origins_host() {
+    assert_eq!(site().id, "benxu.dev");
+    assert_eq!(RelyingParty::new("http://localhost:8080", "Test").id, "localhost");
+}
+
+#[test]
+fn options_carry_the_challenge() {
+    let challenge = challenge();
+    let options = site().creation_options(challenge.as_slice(), &user(), &[vec![1, 2, 3]]);
+    assert_eq!(options.challenge.0, challenge);
+    assert_eq!(options.rp_id, "benxu.dev");
+    assert_eq!(options.user_id.0, user().id);
+    assert_eq!(options.algorithms, vec![ES256]);
+    assert_eq!(options.exclude_credentials, vec![Base64Url(vec![1, 2, 3])]);
+    assert_eq!(site().request_options(challenge.as_slice()).challenge.0, challenge);
+}
+
+#[test]
+fn registers_and_logs_in() {
+    let site = site();
+    let mut authenticator = SoftAuthenticator::new();
+    let challenge = challenge();
+    let credential = site.finish_registration(challenge.as_slice(), &authenticator.register(challenge.as_slice())).unwrap();
+    assert_eq!(credential.id, authenticator.credential_id);
+    assert_eq!(credential.public_key, authenticator.key.verifying_key().to_encoded_point(false).as_bytes());
+    assert_eq!(credential.sign_count, 0);
+
+    let challenge = super::challenge();
+    let response = authenticator.assert(challenge.as_slice());
+    assert_eq!(site.finish_login(challenge.as_slice(), &credential, user().id.as_slice(), &response), Ok(1));
+}
+
+#[test]
+fn registration_checks_the_ceremony() {
+    let site = site();
+    let challenge = challenge();
+    let authenticator = SoftAuthenticator::new();
+
+    let other = super::challenge();
+    let response = authenticator.register(other.as_slice());
+    assert_eq!(site.finish_registration(challenge.as_slice(), &response), Err(Error::Mismatch("challenge")));
+
+    let mut response = authenticator.register(challenge.as_slice());
+    response.client_data_json = authenticator.client_data("webauthn.get", challenge.as_slice()).into();
+    assert_eq!(site.finish_registration(challenge.as_slice(), &response), Err(Error::Mismatch("ceremony type")));
+
+    let mut response = authenticator.register(challenge.as_slice());
+    response.id = vec![1, 2, 3].into();
+    assert_eq!(site.finish_registration(challenge.as_slice(), &response), Err(Error::Mismatch("credential id")));
+
+    let mut response = authenticator.register(challenge.as_slice());
+    response.attestation_object = vec![0xff, 0x00].into();
+    assert_eq!(site.finish_registration(challenge.as_slice(), &response), Err(Error::Malformed("attestation object")));
+}
+
+#[test]
+fn registration_checks_the_site() {
+    let site = site();
+    let challenge = challenge();
+
+    let mut phished = SoftAuthenticator::new();
+    phished.origin = "https://benxu.dev.example.com".to_owned();
+    assert_eq!(
+        site.finish_registration(challenge.as_slice(), &phished.register(challenge.as_slice())),
+        Err(Error::Mismatch("origin")),
+    );
+
+    let mut elsewhere = SoftAuthenticator::new();
+    elsewhere.rp_id = "example.com".to_owned();
+    assert_eq!(
+        site.finish_registration(challenge.as_slice(), &elsewhere.register(challenge.as_slice())),
+        Err(Error::Mismatch("relying party")),
+    );
+}
+
+#[test]
+fn registration_needs_user_verification() {
+    let mut authenticator = SoftAuthenticator::new();
+    authenticator.flags = USER_PRESENT;
+    let challenge = challenge();
+    assert_eq!(
+        site().finish_registration(challenge.as_slice(), &authenticator.register(challenge.as_slice())),
+        Err(Error::NotVerified),
+    );
+}
+
+#[test]
+fn login_checks_the_signature() {
+    let site = site();
+    let mut authenticator = SoftAuthenticator::new();
+    let challenge = challenge();
+    let credential = site.finish_registration(challenge.as_slice(), &authenticator.register(challenge.as_slice())).unwrap();
+    let user_id = user().id;
+
+    let mut response = authenticator.assert(challenge.as_slice());
+    // Claiming the user was verified after the fact.
+    let mut auth_data = response.authenticator_data.0.clone();
+    auth_data[32] |= 0x08;
+    response.authenticator_data = auth_data.into();
+    assert_eq!(site.finish_login(challenge.as_slice(), &credential, user_id.as_slice(), &response), Err(Error::BadSignature));
+
+    let mut impostor = SoftAuthenticator::new();
+    impostor.credential_id = authenticator.credential_id.clone();
+    let response = impostor.assert(challenge.as_slice());
+    assert_eq!(site.finish_login(challenge.as_slice(), &credential, user_id.as_slice(), &response), Err(Error::BadSignature));
+
+    let mut response = authenticator.assert(challenge.as_slice());
+    response.signature = vec![1, 2, 3].into();
+    assert_eq!(site.finish_login(challenge.as_slice(), &credential, user_id.as_slice(), &response), Err(Error::Malformed("signature")));
+}
+
+#[test]
+fn login_checks_the_ceremony() {
+    let site = site();
+    let mut authenticator = SoftAuthenticator::new();
+    let challenge = challenge();
+    let credential = site.finish_registration(challenge.as_slice(), &authenticator.register(challenge.as_slice())).unwrap();
+    let user_id = user().id;
+
+    let response = authenticator.assert(super::challenge().as_slice());
+    assert_eq!(site.finish_login(challenge.as_slice(), &credential, user_id.as_slice(), &response), Err(Error::Mismatch("challenge")));
+
+    let mut response = authenticator.assert(challenge.as_slice());
+    response.user_handle = Some(vec![0; 8].into());
+    assert_eq!(site.finish_login(challenge.as_slice(), &credential, user_id.as_slice(), &response), Err(Error::Mismatch("user handle")));
+
+    let mut response = authenticator.assert(challenge.as_slice());
+    response.id = vec![1, 2, 3].into();
+    assert_eq!(site.finish_login(challenge.as_slice(), &credential, user_id.as_slice(), &response), Err(Error::Mismatch("credential id")));
+
+    authenticator.flags = USER_PRESENT;
+    let response = authenticator.assert(challenge.as_slice());
+    assert_eq!(site.finish_login(challenge.as_slice(), &credential, user_id.as_slice(), &response), Err(Error::NotVerified));
+}
+
+#[test]
+fn login_catches_cloned_authenticators() {
+    let site = site();
+    let mut authenticator = SoftAuthenticator::new();
+    let challenge = challenge();
+    let mut credential = site.finish_registration(challenge.as_slice(), &authenticator.register(challenge.as_slice())).unwrap();
+    let user_id = user().id;
+
+    let response = authenticator.assert(challenge.as_slice());
+    credential.sign_count = site.finish_login(challenge.as_slice(), &credential, user_id.as_slice(), &response).unwrap();
+    // Replaying the same response, or a clone lagging behind.
+    assert_eq!(site.finish_login(challenge.as_slice(), &credential, user_id.as_slice(), &response), Err(Error::CounterWentBack));
+
+    // Authenticators that don't count are fine.
+    let mut uncounting = SoftAuthenticator::new();
+    uncounting.counting = false;
+    let credential = site.finish_registration(challenge.as_slice(), &uncounting.register(challenge.as_slice())).unwrap();
+    uncounting.assert(challenge.as_slice());
+    let mut uncounted = uncounting.assert(challenge.as_slice());
+    uncounted.user_handle = None;
+    assert_eq!(site.finish_login(challenge.as_slice(), &credential, user_id.as_slice(), &uncounted), Ok(0));
+}
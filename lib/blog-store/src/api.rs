@@ -0,0 +1,170 @@
+//! Conversions into the types the API speaks, shared by every server handing out posts.
+
+use blog_api::{
+    passkeys,
+    posts::{self as api, Heading, PostLink, PostPage, PostSummary, SeriesPart},
+    revisions::{self, RevisionSummary},
+    search,
+};
+use chrono::DateTime;
+
+use crate::{Cursor, Neighbors, Page, Passkey, Post, PostSeries, Revision, SearchHit, Series, TagCount};
+
+impl Post {
+    pub fn summary(&self) -> PostSummary {
+        PostSummary {
+            slug: self.slug.clone(),
+            title: self.title.clone(),
+            summary: self.summary.clone(),
+            tags: self.tags.clone(),
+            series: self.series.clone().map(PostSeries::into_api),
+            author: self.author.clone(),
+            published_at: self.published_at,
+            updated_at: self.updated_at,
+            draft: self.draft,
+        }
+    }
+
+    /// Renders the post into what the API hands out.
+    pub fn into_api(self) -> api::Post {
+        let rendered = blog_markdown::render(self.body.as_str());
+        api::Post {
+            slug: self.slug,
+            title: self.title,
+            body: self.body,
+            html: rendered.html,
+            toc: rendered.toc.into_iter()
+                .map(|entry| Heading { level: entry.level, id: entry.id, title: entry.title })
+                .collect(),
+            reading_minutes: rendered.reading_minutes,
+            summary: self.summary,
+            tags: self.tags,
+            series: self.series.map(PostSeries::into_api),
+            author: self.author,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            published_at: self.published_at,
+            draft: self.draft,
+            neighbors: api::Neighbors::default(),
+            series_parts: vec![],
+        }
+    }
+
+    pub fn link(&self) -> PostLink {
+        PostLink { slug: self.slug.clone(), title: self.title.clone() }
+    }
+}
+
+impl PostSeries {
+    pub fn into_api(self) -> api::PostSeries {
+        api::PostSeries { slug: self.slug, title: self.title, part: self.part }
+    }
+
+    pub fn from_api(series: api::PostSeries) -> Self {
+        Self { slug: series.slug, title: series.title, part: series.part }
+    }
+}
+
+impl Series {
+    pub fn into_api(self) -> api::Series {
+        api::Series {
+            slug: self.slug,
+            title: self.title,
+            posts: self.posts.iter().map(Post::summary).collect(),
+        }
+    }
+
+    /// Links to every part, for the box on each of them.
+    pub fn parts(&self) -> Vec<SeriesPart> {
+        self.posts.iter()
+            .map(|post| SeriesPart {
+                part: post.series.as_ref().map_or(0, |series| series.part),
+                slug: post.slug.clone(),
+                title: post.title.clone(),
+            })
+            .collect()
+    }
+}
+
+impl TagCount {
+    pub fn into_api(self) -> api::TagCount {
+        api::TagCount { tag: self.tag, posts: self.posts }
+    }
+}
+
+impl SearchHit {
+    pub fn into_api(self) -> search::SearchHit {
+        search::SearchHit { post: self.post.summary(), title: self.title, snippet: self.snippet }
+    }
+}
+
+impl Cursor {
+    /// API cursors are opaque to clients. Inside, they're the timestamp in microseconds and the
+    /// post id, joined by a dash.
+    pub fn encode(self) -> api::Cursor {
+        api::Cursor::new(format!("{}-{}", self.at.timestamp_micros(), self.id))
+    }
+
+    /// Reads a cursor made by [`Cursor::encode`].
+    pub fn decode(cursor: &api::Cursor) -> Option<Self> {
+        let (at, id) = cursor.as_str().rsplit_once('-')?;
+        Some(Self {
+            at: DateTime::from_timestamp_micros(at.parse().ok()?)?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+impl Page {
+    pub fn into_api(self) -> PostPage {
+        PostPage {
+            posts: self.posts.iter().map(Post::summary).collect(),
+            next: self.next.map(Cursor::encode),
+        }
+    }
+}
+
+impl Neighbors {
+    pub fn into_api(self) -> api::Neighbors {
+        api::Neighbors {
+            newer: self.newer.as_ref().map(Post::link),
+            older: self.older.as_ref().map(Post::link),
+        }
+    }
+}
+
+impl Revision {
+    pub fn summary(&self) -> RevisionSummary {
+        RevisionSummary {
+            number: self.number,
+            title: self.fields.title.clone(),
+            author: self.fields.author.clone(),
+            saved_at: self.saved_at,
+        }
+    }
+
+    pub fn into_api(self) -> revisions::Revision {
+        revisions::Revision {
+            number: self.number,
+            slug: self.fields.slug,
+            title: self.fields.title,
+            body: self.fields.body,
+            summary: self.fields.summary,
+            tags: self.fields.tags,
+            series: self.fields.series.map(PostSeries::into_api),
+            author: self.fields.author,
+            saved_at: self.saved_at,
+        }
+    }
+}
+
+impl Passkey {
+    pub fn into_api(self) -> passkeys::Passkey {
+        passkeys::Passkey {
+            id: self.id.into(),
+            name: self.name,
+            created_at: self.created_at,
+            last_used_at: self.last_used_at,
+        }
+    }
+}
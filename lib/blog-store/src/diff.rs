@@ -0,0 +1,59 @@
+//! Comparing revisions, line by line.
+
+use blog_api::revisions::{Change, DiffLine, FieldDiff, RevisionDiff};
+use similar::{ChangeTag, TextDiff};
+
+use crate::{PostFields, Revision};
+
+/// The fields worth comparing as text, in the order the editor shows them.
+fn texts(fields: &PostFields) -> [(&'static str, String); 6] {
+    [
+        ("title", fields.title.clone()),
+        ("slug", fields.slug.clone()),
+        ("summary", fields.summary.clone().unwrap_or_default()),
+        ("tags", fields.tags.join(", ")),
+        ("series", fields.series.as_ref()
+            .map(|series| format!("{} ({}), part {}", series.title, series.slug, series.part))
+            .unwrap_or_default()),
+        ("body", fields.body.clone()),
+    ]
+}
+
+fn lines(before: &str, after: &str) -> Vec<DiffLine> {
+    // Going by `lines` rather than `TextDiff::from_lines` means a missing newline at the end doesn't
+    // count as a change.
+    let before: Vec<_> = before.lines().collect();
+    let after: Vec<_> = after.lines().collect();
+    TextDiff::from_slices(before.as_slice(), after.as_slice())
+        .iter_all_changes()
+        .map(|change| DiffLine {
+            change: match change.tag() {
+                ChangeTag::Equal => Change::Same,
+                ChangeTag::Insert => Change::Added,
+                ChangeTag::Delete => Change::Removed,
+            },
+            text: change.value().to_owned(),
+        })
+        .collect()
+}
+
+impl Revision {
+    /// What changed going from `from` to this revision. Compared to nothing, everything's added.
+    pub fn diff(&self, from: Option<&Revision>) -> RevisionDiff {
+        let before = from.map(|from| texts(&from.fields)).unwrap_or_default();
+        let fields = texts(&self.fields)
+            .into_iter()
+            .zip(before)
+            .filter(|((_, after), (_, before))| after != before)
+            .map(|((field, after), (_, before))| FieldDiff {
+                field: field.to_owned(),
+                lines: lines(before.as_str(), after.as_str()),
+            })
+            .collect();
+        RevisionDiff {
+            from: from.map(Revision::summary),
+            to: self.summary(),
+            fields,
+        }
+    }
+}
@@ -0,0 +1,321 @@
+//! Storage for blog posts, and for the people writing them.
+//!
+//! Everything goes through [`PostRepository`] and [`UserRepository`]. [`SqlitePostStore`] is what
+//! the servers use, [`MemoryPostStore`] keeps everything in a `Vec` and exists for tests.
+//!
+//! Every save of a post is also kept as a [`Revision`], which [`Revision::diff`] compares.
+//!
+//! Posts can be parts of a [`Series`], which exists for as long as it has any posts in it.
+//!
+//! Published posts can be searched through by their title, body and tags, see
+//! [`PostRepository::search`].
+//!
+//! Stored posts turn into the API's types through [`Post::summary`], [`Post::into_api`],
+//! [`Page::into_api`] and [`Neighbors::into_api`], series through [`Series::into_api`],
+//! [`Series::parts`] and [`PostSeries::into_api`], tags through [`TagCount::into_api`], search
+//! results through [`SearchHit::into_api`], revisions through [`Revision::summary`] and
+//! [`Revision::into_api`], passkeys through [`Passkey::into_api`].
+
+use std::fmt::{self, Display, Formatter};
+
+use chrono::{DateTime, Utc};
+
+mod api;
+mod diff;
+mod memory;
+mod search;
+mod sqlite;
+#[cfg(test)]
+mod tests;
+mod users;
+
+pub use memory::MemoryPostStore;
+pub use search::SearchHit;
+pub use sqlite::SqlitePostStore;
+pub use users::{Passkey, Session, User, UserFields, UserRepository};
+
+/// A stored post.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Post {
+    pub id: i64,
+    pub slug: String,
+    pub title: String,
+    /// The source the post is written in, before rendering.
+    pub body: String,
+    pub summary: Option<String>,
+    pub tags: Vec<String>,
+    pub series: Option<PostSeries>,
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// When the post was first published. Kept when a post is unpublished, so republishing doesn't
+    /// move it to the top of the blog. In the future for posts scheduled to go live then.
+    pub published_at: Option<DateTime<Utc>>,
+    pub draft: bool,
+}
+impl Post {
+    /// Whether readers get to see the post.
+    pub fn is_published(&self) -> bool {
+        !self.draft && self.published_at.is_some_and(|at| at <= Utc::now())
+    }
+
+    /// Whether the post is set to go live later.
+    pub fn is_scheduled(&self) -> bool {
+        !self.draft && self.published_at.is_some_and(|at| at > Utc::now())
+    }
+
+    /// The post's position in listings: newest first, by publication date if it has one.
+    pub fn cursor(&self) -> Cursor {
+        Cursor {
+            at: self.published_at.unwrap_or(self.created_at),
+            id: self.id,
+        }
+    }
+}
+
+/// The fields of a post an author controls. Used both to create and to edit posts.
+///
+/// Posts always start out as drafts, use [`PostRepository::publish`] to make them visible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostFields {
+    pub slug: String,
+    pub title: String,
+    pub body: String,
+    pub summary: Option<String>,
+    pub tags: Vec<String>,
+    pub series: Option<PostSeries>,
+    pub author: String,
+}
+impl PostFields {
+    /// Checks the slugs, drops repeated tags and names untitled series after their slug.
+    fn validated(mut self) -> Result<Self> {
+        validate_slug(self.slug.as_str())?;
+        let mut seen = std::collections::HashSet::new();
+        self.tags.retain(|tag| seen.insert(tag.clone()));
+        if let Some(series) = self.series.as_mut() {
+            validate_slug(series.slug.as_str())?;
+            series.title = series.title.trim().to_owned();
+            if series.title.is_empty() {
+                series.title = series.slug.clone();
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Where a post sits in a series.
+///
+/// Series go by their slug. Saving a published post with a new title for its series renames the
+/// series, drafts keep theirs to themselves until they're published.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostSeries {
+    pub slug: String,
+    pub title: String,
+    /// Parts are read in order, from 1.
+    pub part: u32,
+}
+
+/// A series and its published posts, in reading order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Series {
+    pub slug: String,
+    pub title: String,
+    pub posts: Vec<Post>,
+}
+
+/// How many published posts have a tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagCount {
+    pub tag: String,
+    pub posts: usize,
+}
+
+/// A saved version of a post.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    /// Counts up from 1 with every save of the post.
+    pub number: u32,
+    /// The post as it was saved, `author` being whoever saved it.
+    pub fields: PostFields,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Marks a place in a listing. Listings are ordered newest first, with the id breaking ties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cursor {
+    pub at: DateTime<Utc>,
+    pub id: i64,
+}
+
+/// Which posts to list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListQuery {
+    /// Only list posts with this tag.
+    pub tag: Option<String>,
+    /// List drafts alongside published posts.
+    pub include_drafts: bool,
+    /// Only list posts after this one.
+    pub after: Option<Cursor>,
+    pub limit: usize,
+}
+impl Default for ListQuery {
+    fn default() -> Self {
+        Self {
+            tag: None,
+            include_drafts: false,
+            after: None,
+            limit: 20,
+        }
+    }
+}
+impl ListQuery {
+    fn matches(&self, post: &Post) -> bool {
+        (self.include_drafts || post.is_published())
+            && self.tag.as_ref().is_none_or(|tag| post.tags.contains(tag))
+            && self.after.is_none_or(|after| post.cursor() < after)
+    }
+}
+
+/// One page of a listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    pub posts: Vec<Post>,
+    /// Where the next page starts, if there is one.
+    pub next: Option<Cursor>,
+}
+impl Page {
+    /// Builds a page out of up to `limit + 1` posts, the extra one signalling there's more.
+    fn from_overfetched(mut posts: Vec<Post>, limit: usize) -> Self {
+        let next = if posts.len() > limit {
+            posts.truncate(limit);
+            posts.last().map(Post::cursor)
+        } else {
+            None
+        };
+        Self { posts, next }
+    }
+}
+
+/// The published posts either side of a post, going by listing order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Neighbors {
+    pub newer: Option<Post>,
+    pub older: Option<Post>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// No post has that slug.
+    NotFound,
+    /// Another post already has that slug.
+    SlugTaken(String),
+    /// Slugs go in URLs, so they're limited to lowercase ascii letters, digits and dashes.
+    InvalidSlug(String),
+    /// The database was last opened by a newer build, which ran this many migrations. Its schema
+    /// is left alone rather than guessed at.
+    NewerSchema(usize),
+    Database(rusqlite::Error),
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::NotFound => write!(f, "post not found"),
+            Error::SlugTaken(slug) => write!(f, "a post with the slug {slug:?} already exists"),
+            Error::InvalidSlug(slug) => write!(f, "{slug:?} is not a valid slug"),
+            Error::NewerSchema(version) => {
+                write!(f, "the database is at schema version {version}, newer than this build knows of")
+            },
+            Error::Database(e) => write!(f, "database error: {e}"),
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Database(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Storage for posts.
+pub trait PostRepository: Send + Sync {
+    /// Stores a new draft.
+    fn create(&self, fields: PostFields) -> Result<Post>;
+
+    fn get(&self, slug: &str) -> Result<Option<Post>>;
+
+    /// Replaces the author controlled fields of a post, including possibly its slug.
+    ///
+    /// Both this and [`PostRepository::create`] keep what was saved as a new [`Revision`], unless
+    /// it's the same as the last one.
+    fn update(&self, slug: &str, fields: PostFields) -> Result<Post>;
+
+    fn delete(&self, slug: &str) -> Result<()>;
+
+    fn list(&self, query: &ListQuery) -> Result<Page>;
+
+    /// The published posts listed right before and right after `post`.
+    fn neighbors(&self, post: &Post) -> Result<Neighbors>;
+
+    /// Makes a post visible to readers from `at` on, which can be in the future.
+    ///
+    /// Publishing right away keeps the original date of posts that were live before. Scheduling
+    /// always moves the date.
+    fn publish(&self, slug: &str, at: DateTime<Utc>) -> Result<Post>;
+
+    /// Turns a post back into a draft.
+    fn unpublish(&self, slug: &str) -> Result<Post>;
+
+    /// Every saved version of a post, newest first.
+    fn revisions(&self, slug: &str) -> Result<Vec<Revision>>;
+
+    /// One saved version of a post, `None` if the post never had that many. Revisions are numbered
+    /// from 1 with no gaps.
+    fn revision(&self, slug: &str, number: u32) -> Result<Option<Revision>>;
+
+    /// The newest saved version of a post.
+    fn latest_revision(&self, slug: &str) -> Result<Option<Revision>>;
+
+    /// The series going by `slug`, `None` if no post is part of it.
+    fn series(&self, slug: &str) -> Result<Option<Series>>;
+
+    /// Every tag of a published post, alphabetically.
+    fn tags(&self) -> Result<Vec<TagCount>>;
+
+    /// Published posts with every word of `text` in their title, body or tags, the last word
+    /// possibly unfinished. Best matches come first, titles and tags counting for more than the
+    /// body.
+    fn search(&self, text: &str, limit: usize) -> Result<Vec<SearchHit>>;
+
+    /// When the next scheduled post goes live, if there is one.
+    fn next_publication(&self) -> Result<Option<DateTime<Utc>>>;
+
+    /// Changes whenever a post does, including through other stores on the same database.
+    fn version(&self) -> Result<u64>;
+}
+
+pub fn validate_slug(slug: &str) -> Result<()> {
+    if blog_api::posts::is_valid_slug(slug) {
+        Ok(())
+    } else {
+        Err(Error::InvalidSlug(slug.to_owned()))
+    }
+}
+
+/// The current time, at the precision timestamps are stored with.
+fn now() -> DateTime<Utc> {
+    truncate(Utc::now())
+}
+
+/// Drops anything finer than microseconds, which is all the database keeps.
+fn truncate(at: DateTime<Utc>) -> DateTime<Utc> {
+    DateTime::from_timestamp_micros(at.timestamp_micros()).expect("timestamp is in range")
+}
@@ -0,0 +1,363 @@
+//! A [`PostRepository`] that only lives as long as the process.
+
+use std::sync::{Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    now, search::Terms, truncate, Error, ListQuery, Neighbors, Page, Passkey, Post, PostFields, PostRepository,
+    PostSeries, Result, Revision, SearchHit, Series, Session, TagCount, User, UserFields, UserRepository,
+};
+
+#[derive(Debug, Default)]
+struct Posts {
+    posts: Vec<Post>,
+    /// Every post's revisions by post id, oldest first.
+    revisions: Vec<(i64, Revision)>,
+    next_id: i64,
+    /// Counts changes.
+    version: u64,
+}
+
+#[derive(Debug, Default)]
+struct Users {
+    users: Vec<User>,
+    passkeys: Vec<Passkey>,
+    sessions: Vec<Session>,
+    next_id: i64,
+}
+
+/// Keeps posts and users in memory, for tests.
+#[derive(Debug, Default)]
+pub struct MemoryPostStore {
+    posts: Mutex<Posts>,
+    users: Mutex<Users>,
+}
+impl MemoryPostStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Posts> {
+        // Nothing here leaves the posts half modified, so a poisoned lock is still usable.
+        self.posts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_users(&self) -> MutexGuard<'_, Users> {
+        self.users.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Posts {
+    fn find(&mut self, slug: &str) -> Result<&mut Post> {
+        self.posts.iter_mut().find(|p| p.slug == slug).ok_or(Error::NotFound)
+    }
+
+    fn revisions(&self, id: i64) -> impl DoubleEndedIterator<Item = &Revision> {
+        self.revisions.iter().filter(move |(post_id, _)| *post_id == id).map(|(_, revision)| revision)
+    }
+
+    fn save_revision(&mut self, id: i64, fields: PostFields, at: DateTime<Utc>) {
+        let last = self.revisions(id).next_back();
+        if last.is_some_and(|last| last.fields == fields) {
+            return;
+        }
+        let number = last.map_or(1, |last| last.number + 1);
+        self.revisions.push((id, Revision { number, fields, saved_at: at }));
+    }
+
+    /// The title readers see for the series `slug`, the one its published posts go by.
+    fn series_title(&self, slug: &str) -> Option<String> {
+        let in_series = || self.posts.iter().filter(|p| p.series.as_ref().is_some_and(|series| series.slug == slug));
+        // Drafts' titles only count while there's nothing else.
+        let mut titled = in_series().filter(|p| !p.draft).chain(in_series());
+        titled.find_map(|p| p.series.as_ref()).map(|series| series.title.clone())
+    }
+
+    /// Gives `series` the title it has, on every published post and on the drafts that went by
+    /// `old`. Drafts with a title of their own keep it.
+    fn rename_series(&mut self, series: Option<&PostSeries>, old: Option<String>) {
+        let Some(series) = series else {
+            return;
+        };
+        for post in self.posts.iter_mut() {
+            let draft = post.draft;
+            if let Some(theirs) = post.series.as_mut().filter(|theirs| theirs.slug == series.slug) {
+                if !draft || Some(&theirs.title) == old.as_ref() {
+                    theirs.title = series.title.clone();
+                }
+            }
+        }
+    }
+
+    fn ensure_free(&self, slug: &str, except: Option<i64>) -> Result<()> {
+        if self.posts.iter().any(|p| p.slug == slug && Some(p.id) != except) {
+            Err(Error::SlugTaken(slug.to_owned()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl PostRepository for MemoryPostStore {
+    fn create(&self, fields: PostFields) -> Result<Post> {
+        let fields = fields.validated()?;
+        let mut posts = self.lock();
+        posts.ensure_free(fields.slug.as_str(), None)?;
+        posts.next_id += 1;
+        posts.version += 1;
+        let now = now();
+        let id = posts.next_id;
+        posts.save_revision(id, fields.clone(), now);
+        let post = Post {
+            id,
+            slug: fields.slug,
+            title: fields.title,
+            body: fields.body,
+            summary: fields.summary,
+            tags: fields.tags,
+            series: fields.series,
+            author: fields.author,
+            created_at: now,
+            updated_at: now,
+            published_at: None,
+            draft: true,
+        };
+        posts.posts.push(post.clone());
+        Ok(post)
+    }
+
+    fn get(&self, slug: &str) -> Result<Option<Post>> {
+        Ok(self.lock().posts.iter().find(|p| p.slug == slug).cloned())
+    }
+
+    fn update(&self, slug: &str, fields: PostFields) -> Result<Post> {
+        let fields = fields.validated()?;
+        let mut posts = self.lock();
+        let id = posts.find(slug)?.id;
+        posts.ensure_free(fields.slug.as_str(), Some(id))?;
+        posts.version += 1;
+        let now = now();
+        posts.save_revision(id, fields.clone(), now);
+        let old_title = fields.series.as_ref().and_then(|series| posts.series_title(series.slug.as_str()));
+        let post = posts.find(slug)?;
+        post.slug = fields.slug;
+        post.title = fields.title;
+        post.body = fields.body;
+        post.summary = fields.summary;
+        post.tags = fields.tags;
+        post.series = fields.series;
+        post.author = fields.author;
+        post.updated_at = now;
+        let post = post.clone();
+        if !post.draft {
+            posts.rename_series(post.series.as_ref(), old_title);
+        }
+        Ok(post)
+    }
+
+    fn delete(&self, slug: &str) -> Result<()> {
+        let mut posts = self.lock();
+        let id = posts.find(slug)?.id;
+        posts.posts.retain(|p| p.id != id);
+        posts.version += 1;
+        posts.revisions.retain(|(post_id, _)| *post_id != id);
+        Ok(())
+    }
+
+    fn list(&self, query: &ListQuery) -> Result<Page> {
+        let posts = self.lock();
+        let mut matching: Vec<_> = posts.posts.iter().filter(|p| query.matches(p)).cloned().collect();
+        matching.sort_by_key(|p| std::cmp::Reverse(p.cursor()));
+        matching.truncate(query.limit.saturating_add(1));
+        Ok(Page::from_overfetched(matching, query.limit))
+    }
+
+    fn neighbors(&self, post: &Post) -> Result<Neighbors> {
+        let posts = self.lock();
+        let published = || posts.posts.iter().filter(|p| p.is_published());
+        let at = post.cursor();
+        Ok(Neighbors {
+            newer: published().filter(|p| p.cursor() > at).min_by_key(|p| p.cursor()).cloned(),
+            older: published().filter(|p| p.cursor() < at).max_by_key(|p| p.cursor()).cloned(),
+        })
+    }
+
+    fn publish(&self, slug: &str, at: DateTime<Utc>) -> Result<Post> {
+        let mut posts = self.lock();
+        posts.version += 1;
+        let series = posts.find(slug)?.series.clone();
+        let old_title = series.and_then(|series| posts.series_title(series.slug.as_str()));
+        let post = posts.find(slug)?;
+        let now = now();
+        let at = truncate(at);
+        let was_live = post.published_at.is_some_and(|published_at| published_at <= now);
+        if !(at <= now && was_live) {
+            post.published_at = Some(at);
+        }
+        let was_draft = post.draft;
+        post.draft = false;
+        post.updated_at = now;
+        let post = post.clone();
+        // The title the draft was saved with goes out along with it.
+        if was_draft {
+            posts.rename_series(post.series.as_ref(), old_title);
+        }
+        Ok(post)
+    }
+
+    fn unpublish(&self, slug: &str) -> Result<Post> {
+        let mut posts = self.lock();
+        posts.version += 1;
+        let post = posts.find(slug)?;
+        post.draft = true;
+        post.updated_at = now();
+        Ok(post.clone())
+    }
+
+    fn revisions(&self, slug: &str) -> Result<Vec<Revision>> {
+        let mut posts = self.lock();
+        let id = posts.find(slug)?.id;
+        Ok(posts.revisions(id).rev().cloned().collect())
+    }
+
+    fn revision(&self, slug: &str, number: u32) -> Result<Option<Revision>> {
+        let mut posts = self.lock();
+        let id = posts.find(slug)?.id;
+        let revision = posts.revisions(id).find(|revision| revision.number == number).cloned();
+        Ok(revision)
+    }
+
+    fn latest_revision(&self, slug: &str) -> Result<Option<Revision>> {
+        let mut posts = self.lock();
+        let id = posts.find(slug)?.id;
+        let revision = posts.revisions(id).next_back().cloned();
+        Ok(revision)
+    }
+
+    fn series(&self, slug: &str) -> Result<Option<Series>> {
+        let posts = self.lock();
+        let in_series = || posts.posts.iter().filter(|p| p.series.as_ref().is_some_and(|series| series.slug == slug));
+        let Some(title) = posts.series_title(slug) else {
+            return Ok(None);
+        };
+        let mut published: Vec<_> = in_series().filter(|p| p.is_published()).cloned().collect();
+        published.sort_by_key(|p| (p.series.as_ref().map(|series| series.part), p.published_at, p.id));
+        Ok(Some(Series { slug: slug.to_owned(), title, posts: published }))
+    }
+
+    fn tags(&self) -> Result<Vec<TagCount>> {
+        let mut counts = std::collections::BTreeMap::<&str, usize>::new();
+        let posts = self.lock();
+        for tag in posts.posts.iter().filter(|p| p.is_published()).flat_map(|p| p.tags.iter()) {
+            *counts.entry(tag.as_str()).or_default() += 1;
+        }
+        Ok(counts.into_iter().map(|(tag, posts)| TagCount { tag: tag.to_owned(), posts }).collect())
+    }
+
+    fn search(&self, text: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let Some(terms) = Terms::parse(text) else {
+            return Ok(vec![]);
+        };
+        let posts = self.lock();
+        let mut hits: Vec<_> = posts.posts.iter()
+            .filter(|p| p.is_published())
+            .filter_map(|p| {
+                let body = blog_markdown::plain_text(p.body.as_str());
+                let tags = p.tags.join(", ");
+                if !terms.found_in(&[p.title.as_str(), body.as_str(), tags.as_str()]) {
+                    return None;
+                }
+                // Weighted like the SQLite store's ranking, if nowhere near as clever.
+                let score = 10 * terms.count(p.title.as_str()) + terms.count(body.as_str()) + 5 * terms.count(tags.as_str());
+                let hit = SearchHit {
+                    post: p.clone(),
+                    title: terms.highlight(p.title.as_str()),
+                    snippet: terms.snippet(body.as_str()),
+                };
+                Some((score, hit))
+            })
+            .collect();
+        hits.sort_by_key(|(score, hit)| std::cmp::Reverse((*score, hit.post.id)));
+        Ok(hits.into_iter().take(limit).map(|(_, hit)| hit).collect())
+    }
+
+    fn next_publication(&self) -> Result<Option<DateTime<Utc>>> {
+        Ok(self.lock().posts.iter().filter(|p| p.is_scheduled()).filter_map(|p| p.published_at).min())
+    }
+
+    fn version(&self) -> Result<u64> {
+        Ok(self.lock().version)
+    }
+}
+
+impl UserRepository for MemoryPostStore {
+    fn save_user(&self, fields: UserFields) -> Result<User> {
+        let mut users = self.lock_users();
+        if let Some(user) = users.users.iter_mut().find(|u| u.username == fields.username) {
+            user.name = fields.name;
+            user.password_hash = fields.password_hash;
+            return Ok(user.clone());
+        }
+        users.next_id += 1;
+        let user = User {
+            id: users.next_id,
+            username: fields.username,
+            name: fields.name,
+            password_hash: fields.password_hash,
+        };
+        users.users.push(user.clone());
+        Ok(user)
+    }
+
+    fn user(&self, username: &str) -> Result<Option<User>> {
+        Ok(self.lock_users().users.iter().find(|u| u.username == username).cloned())
+    }
+
+    fn create_session(&self, session: &Session) -> Result<()> {
+        let mut users = self.lock_users();
+        users.sessions.retain(|s| s.expires_at > session.created_at);
+        users.sessions.push(session.clone());
+        Ok(())
+    }
+
+    fn session(&self, id: &str, now: DateTime<Utc>) -> Result<Option<(Session, User)>> {
+        let users = self.lock_users();
+        let Some(session) = users.sessions.iter().find(|s| s.id == id && s.expires_at > now) else {
+            return Ok(None);
+        };
+        let user = users.users.iter().find(|u| u.id == session.user_id);
+        Ok(user.map(|user| (session.clone(), user.clone())))
+    }
+
+    fn delete_session(&self, id: &str) -> Result<()> {
+        self.lock_users().sessions.retain(|s| s.id != id);
+        Ok(())
+    }
+
+    fn add_passkey(&self, passkey: &Passkey) -> Result<()> {
+        self.lock_users().passkeys.push(passkey.clone());
+        Ok(())
+    }
+
+    fn passkey(&self, id: &[u8]) -> Result<Option<(Passkey, User)>> {
+        let users = self.lock_users();
+        let Some(passkey) = users.passkeys.iter().find(|p| p.id == id) else {
+            return Ok(None);
+        };
+        let user = users.users.iter().find(|u| u.id == passkey.user_id);
+        Ok(user.map(|user| (passkey.clone(), user.clone())))
+    }
+
+    fn passkeys(&self, user_id: i64) -> Result<Vec<Passkey>> {
+        Ok(self.lock_users().passkeys.iter().filter(|p| p.user_id == user_id).cloned().collect())
+    }
+
+    fn record_passkey_use(&self, id: &[u8], sign_count: u32, at: DateTime<Utc>) -> Result<()> {
+        if let Some(passkey) = self.lock_users().passkeys.iter_mut().find(|p| p.id == id) {
+            passkey.sign_count = sign_count;
+            passkey.last_used_at = Some(truncate(at));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+//! Full-text search, and the bits of it both stores share.
+//!
+//! The SQLite store leaves the searching to an FTS5 index. The memory store makes do with
+//! matching words one by one, which agrees with it on anything but stemming and exact ranking.
+
+use std::ops::Range;
+
+use blog_api::search::Fragment;
+
+use crate::Post;
+
+/// Marks where a match starts in text handed back by the index. Control characters, which posts
+/// hardly ever have, and which [`unmarked`] keeps out of the index when they do.
+pub(crate) const MATCH_START: char = '\u{2}';
+pub(crate) const MATCH_END: char = '\u{3}';
+
+/// About how many words a snippet runs for.
+pub(crate) const SNIPPET_WORDS: usize = 24;
+
+/// A post that matched a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub post: Post,
+    /// The title, with whatever matched marked.
+    pub title: Vec<Fragment>,
+    /// The part of the body that matched best, as plain text, with whatever matched marked.
+    pub snippet: Vec<Fragment>,
+}
+
+/// What to search for: every word, lowercased, the last one possibly unfinished.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Terms {
+    words: Vec<String>,
+    /// Whether the search ended mid-word, so that the last word only has to start a word.
+    prefix: bool,
+}
+impl Terms {
+    /// Reads a search as typed. Anything but letters and digits separates words, so there's no
+    /// syntax to get wrong. `None` if there's nothing to search for.
+    pub fn parse(text: &str) -> Option<Self> {
+        let words: Vec<_> = words(text).map(|range| text[range].to_lowercase()).collect();
+        if words.is_empty() {
+            return None;
+        }
+        Some(Self { words, prefix: text.ends_with(char::is_alphanumeric) })
+    }
+
+    /// The search as an FTS5 query, every word quoted so that none of them reads as an operator.
+    pub fn fts_query(&self) -> String {
+        let mut query = self.words.iter().map(|word| format!("\"{word}\"")).collect::<Vec<_>>().join(" ");
+        if self.prefix {
+            query.push('*');
+        }
+        query
+    }
+
+    fn matches(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        let (last, rest) = self.words.split_last().expect("there's always a word");
+        rest.contains(&word) || if self.prefix { word.starts_with(last.as_str()) } else { word == *last }
+    }
+
+    /// Whether every word shows up somewhere in `texts`.
+    pub fn found_in(&self, texts: &[&str]) -> bool {
+        let found: Vec<_> = texts.iter().flat_map(|text| words(text).map(|range| text[range].to_lowercase())).collect();
+        let (last, rest) = self.words.split_last().expect("there's always a word");
+        rest.iter().all(|word| found.contains(word))
+            && found.iter().any(|word| if self.prefix { word.starts_with(last.as_str()) } else { word == last })
+    }
+
+    /// How many words of `text` match.
+    pub fn count(&self, text: &str) -> usize {
+        words(text).filter(|range| self.matches(&text[range.clone()])).count()
+    }
+
+    /// All of `text`, with matches marked.
+    pub fn highlight(&self, text: &str) -> Vec<Fragment> {
+        mark(text, words(text).filter(|range| self.matches(&text[range.clone()])))
+    }
+
+    /// Around [`SNIPPET_WORDS`] words of `text` starting a little before the first match, or from
+    /// the start if nothing matches.
+    pub fn snippet(&self, text: &str) -> Vec<Fragment> {
+        let all: Vec<_> = words(text).collect();
+        let first = all.iter().position(|range| self.matches(&text[range.clone()])).unwrap_or(0);
+        let start = first.saturating_sub(SNIPPET_WORDS / 4);
+        let end = (start + SNIPPET_WORDS).min(all.len());
+        let Some(shown) = all.get(start..end).filter(|shown| !shown.is_empty()) else {
+            return vec![];
+        };
+        let from = if start == 0 { 0 } else { shown[0].start };
+        let to = if end == all.len() { text.len() } else { shown[shown.len() - 1].end };
+        let matched = shown.iter()
+            .filter(|range| self.matches(&text[(*range).clone()]))
+            .map(|range| range.start - from..range.end - from);
+        let mut fragments = mark(&text[from..to], matched);
+        if start > 0 {
+            fragments.insert(0, plain("…"));
+        }
+        if end < all.len() {
+            fragments.push(plain("…"));
+        }
+        merge(fragments)
+    }
+}
+
+/// Where the words of `text` are: runs of letters and digits.
+fn words(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut start = None;
+    text.char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+        .filter_map(move |(idx, c)| match (start, c.is_alphanumeric()) {
+            (None, true) => {
+                start = Some(idx);
+                None
+            },
+            (Some(from), false) => {
+                start = None;
+                Some(from..idx)
+            },
+            _ => None,
+        })
+}
+
+fn plain(text: &str) -> Fragment {
+    Fragment { text: text.to_owned(), matched: false }
+}
+
+/// `text` split up at `matched`, which have to be in order.
+fn mark(text: &str, matched: impl IntoIterator<Item = Range<usize>>) -> Vec<Fragment> {
+    let mut fragments = vec![];
+    let mut at = 0;
+    for range in matched {
+        if range.start > at {
+            fragments.push(plain(&text[at..range.start]));
+        }
+        fragments.push(Fragment { text: text[range.clone()].to_owned(), matched: true });
+        at = range.end;
+    }
+    if at < text.len() {
+        fragments.push(plain(&text[at..]));
+    }
+    merge(fragments)
+}
+
+/// Joins neighboring fragments that are marked the same, and drops empty ones.
+fn merge(fragments: Vec<Fragment>) -> Vec<Fragment> {
+    let mut merged: Vec<Fragment> = vec![];
+    for fragment in fragments.into_iter().filter(|fragment| !fragment.text.is_empty()) {
+        match merged.last_mut() {
+            Some(last) if last.matched == fragment.matched => last.text.push_str(fragment.text.as_str()),
+            _ => merged.push(fragment),
+        }
+    }
+    merged
+}
+
+/// `text` without [`MATCH_START`] or [`MATCH_END`], so that the only marks in what the index hands
+/// back are its own.
+pub(crate) fn unmarked(text: &str) -> String {
+    text.replace([MATCH_START, MATCH_END], "")
+}
+
+/// Splits text the index marked with [`MATCH_START`] and [`MATCH_END`] into fragments.
+pub(crate) fn fragments(marked: &str) -> Vec<Fragment> {
+    let mut fragments = vec![];
+    let mut rest = marked;
+    while let Some(start) = rest.find(MATCH_START) {
+        fragments.push(plain(&rest[..start]));
+        rest = &rest[start + MATCH_START.len_utf8()..];
+        let end = rest.find(MATCH_END).unwrap_or(rest.len());
+        fragments.push(Fragment { text: rest[..end].to_owned(), matched: true });
+        rest = rest.get(end + MATCH_END.len_utf8()..).unwrap_or_default();
+    }
+    fragments.push(plain(rest));
+    merge(fragments)
+}
@@ -0,0 +1,841 @@
+//! A [`PostRepository`] backed by SQLite.
+
+use std::{
+    path::Path,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, ErrorCode, OptionalExtension, Row, Transaction, TransactionBehavior};
+
+use crate::{
+    now,
+    search::{self, Terms, MATCH_END, MATCH_START, SNIPPET_WORDS},
+    truncate, Error, ListQuery, Neighbors, Page, Passkey, Post, PostFields, PostRepository, PostSeries, Result,
+    Revision, SearchHit, Series, Session, TagCount, User, UserFields, UserRepository,
+};
+
+/// Schema changes, applied in order. The database's `user_version` records how many have run.
+///
+/// Never edit an entry once it's shipped, add a new one instead.
+const MIGRATIONS: &[&str] = &[
+    // 1: Posts and their tags.
+    "
+    CREATE TABLE posts (
+        id INTEGER PRIMARY KEY,
+        slug TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        body TEXT NOT NULL,
+        summary TEXT,
+        author TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        published_at INTEGER,
+        draft INTEGER NOT NULL DEFAULT 1
+    );
+    CREATE INDEX posts_listing ON posts (COALESCE(published_at, created_at) DESC, id DESC);
+    CREATE TABLE post_tags (
+        post_id INTEGER NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (post_id, tag)
+    );
+    CREATE INDEX post_tags_tag ON post_tags (tag);
+    ",
+    // 2: Revisions, starting with what every post has now.
+    "
+    CREATE TABLE post_revisions (
+        post_id INTEGER NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+        number INTEGER NOT NULL,
+        slug TEXT NOT NULL,
+        title TEXT NOT NULL,
+        body TEXT NOT NULL,
+        summary TEXT,
+        author TEXT NOT NULL,
+        saved_at INTEGER NOT NULL,
+        PRIMARY KEY (post_id, number)
+    );
+    CREATE TABLE post_revision_tags (
+        post_id INTEGER NOT NULL,
+        number INTEGER NOT NULL,
+        tag TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (post_id, number, position),
+        FOREIGN KEY (post_id, number) REFERENCES post_revisions (post_id, number) ON DELETE CASCADE
+    );
+    INSERT INTO post_revisions (post_id, number, slug, title, body, summary, author, saved_at)
+        SELECT id, 1, slug, title, body, summary, author, updated_at FROM posts;
+    INSERT INTO post_revision_tags (post_id, number, tag, position)
+        SELECT post_id, 1, tag, position FROM post_tags;
+    ",
+    // 3: Users, and their sessions.
+    "
+    CREATE TABLE users (
+        id INTEGER PRIMARY KEY,
+        username TEXT NOT NULL UNIQUE,
+        name TEXT NOT NULL,
+        password_hash TEXT NOT NULL
+    );
+    CREATE TABLE sessions (
+        id TEXT PRIMARY KEY,
+        user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        csrf_token TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        expires_at INTEGER NOT NULL
+    );
+    CREATE INDEX sessions_expiry ON sessions (expires_at);
+    ",
+    // 4: Passkeys.
+    "
+    CREATE TABLE passkeys (
+        id BLOB PRIMARY KEY,
+        user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        public_key BLOB NOT NULL,
+        sign_count INTEGER NOT NULL,
+        created_at INTEGER NOT NULL,
+        last_used_at INTEGER
+    );
+    CREATE INDEX passkeys_user ON passkeys (user_id);
+    ",
+    // 5: Series, which revisions keep a copy of.
+    "
+    CREATE TABLE series (
+        id INTEGER PRIMARY KEY,
+        slug TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL
+    );
+    ALTER TABLE posts ADD COLUMN series_id INTEGER REFERENCES series (id) ON DELETE SET NULL;
+    ALTER TABLE posts ADD COLUMN series_part INTEGER;
+    CREATE INDEX posts_series ON posts (series_id, series_part);
+    ALTER TABLE post_revisions ADD COLUMN series_slug TEXT;
+    ALTER TABLE post_revisions ADD COLUMN series_title TEXT;
+    ALTER TABLE post_revisions ADD COLUMN series_part INTEGER;
+    ",
+    // 6: The search index, by post id. Filled in from Rust, which knows what the text of a post
+    // is, see `SEARCH_INDEX_VERSION`.
+    "
+    CREATE VIRTUAL TABLE post_search USING fts5 (
+        title,
+        body,
+        tags,
+        tokenize = 'porter unicode61 remove_diacritics 2'
+    );
+    CREATE TRIGGER posts_unindex AFTER DELETE ON posts BEGIN
+        DELETE FROM post_search WHERE rowid = old.id;
+    END;
+    ",
+    // 7: The series title each post was saved with, which drafts keep to themselves until they're
+    // published.
+    "
+    ALTER TABLE posts ADD COLUMN series_title TEXT;
+    UPDATE posts SET series_title = (SELECT title FROM series WHERE id = posts.series_id);
+    ",
+    // 8: Which version of `index_post` filled the search index, none yet.
+    "
+    CREATE TABLE search_index (version INTEGER NOT NULL);
+    INSERT INTO search_index (version) VALUES (0);
+    ",
+];
+
+/// Bump this whenever what [`index_post`] or `blog_markdown::plain_text` produces changes.
+/// A bump indexes every post again the next time a server starts.
+const SEARCH_INDEX_VERSION: i64 = 1;
+
+const POST_COLUMNS: &str = "id, slug, title, body, summary, author, created_at, updated_at, published_at, draft";
+
+const REVISION_COLUMNS: &str =
+    "post_id, number, slug, title, body, summary, author, saved_at, series_slug, series_title, series_part";
+
+const USER_COLUMNS: &str = "id, username, name, password_hash";
+
+const PASSKEY_COLUMNS: &str = "id, user_id, name, public_key, sign_count, created_at, last_used_at";
+
+/// Stores posts, and the users writing them, in an SQLite database.
+///
+/// Access is serialized through a single connection, which is plenty for a personal blog.
+#[derive(Debug)]
+pub struct SqlitePostStore {
+    conn: Mutex<Connection>,
+}
+impl SqlitePostStore {
+    /// Opens the database at `path`, creating it if needed, and brings its schema up to date.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Opens a fresh database that disappears when the store is dropped.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Self> {
+        // Both servers open the database, possibly at the same time, so they wait on each other
+        // before anything else.
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        // Readers shouldn't block the writer or vice versa either.
+        conn.pragma_update_and_check(None, "journal_mode", "wal", |_| Ok(()))?;
+        migrate(&mut conn)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        // Transactions roll back on drop, so the connection's fine even if a panic poisoned the lock.
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Runs every migration the database hasn't seen yet.
+fn migrate(conn: &mut Connection) -> Result<()> {
+    // Taking the write lock up front, a second server starting up waits its turn instead of failing
+    // to upgrade its read.
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let version: usize = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version > MIGRATIONS.len() {
+        return Err(Error::NewerSchema(version));
+    }
+    for migration in MIGRATIONS.iter().skip(version) {
+        tx.execute_batch(migration)?;
+    }
+    tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+    // Indexes from newer servers are left alone, same as newer schemas.
+    let indexed: i64 = tx.query_row("SELECT version FROM search_index", [], |row| row.get(0))?;
+    if indexed < SEARCH_INDEX_VERSION {
+        reindex(&tx)?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Fills the search index in again, from scratch.
+fn reindex(tx: &Transaction) -> Result<()> {
+    let posts: Vec<(i64, String, String)> = tx
+        .prepare("SELECT id, title, body FROM posts")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    tx.execute("DELETE FROM post_search", [])?;
+    for (id, title, body) in posts {
+        let tags: Vec<String> = tx
+            .prepare_cached("SELECT tag FROM post_tags WHERE post_id = ?1 ORDER BY position")?
+            .query_map([id], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        index_post(tx, id, title.as_str(), body.as_str(), tags.as_slice())?;
+    }
+    tx.execute("UPDATE search_index SET version = ?1", [SEARCH_INDEX_VERSION])?;
+    Ok(())
+}
+
+fn to_micros(at: DateTime<Utc>) -> i64 {
+    at.timestamp_micros()
+}
+
+fn from_micros(micros: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_micros(micros).expect("stored timestamps are in range")
+}
+
+/// Reads a post, minus its tags and series, from a row selected with [`POST_COLUMNS`].
+fn read_post(row: &Row) -> rusqlite::Result<Post> {
+    Ok(Post {
+        id: row.get(0)?,
+        slug: row.get(1)?,
+        title: row.get(2)?,
+        body: row.get(3)?,
+        summary: row.get(4)?,
+        author: row.get(5)?,
+        created_at: from_micros(row.get(6)?),
+        updated_at: from_micros(row.get(7)?),
+        published_at: row.get::<_, Option<i64>>(8)?.map(from_micros),
+        draft: row.get(9)?,
+        tags: vec![],
+        series: None,
+    })
+}
+
+/// Fills in what [`read_post`] leaves out.
+fn load_details(conn: &Connection, post: &mut Post) -> Result<()> {
+    let mut stmt = conn.prepare_cached("SELECT tag FROM post_tags WHERE post_id = ?1 ORDER BY position")?;
+    post.tags = stmt.query_map([post.id], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+    let mut stmt = conn.prepare_cached(
+        "SELECT s.slug, CASE WHEN p.draft THEN p.series_title ELSE s.title END, p.series_part
+        FROM posts p JOIN series s ON s.id = p.series_id
+        WHERE p.id = ?1",
+    )?;
+    post.series = stmt.query_row([post.id], |row| {
+        Ok(PostSeries { slug: row.get(0)?, title: row.get(1)?, part: row.get(2)? })
+    }).optional()?;
+    Ok(())
+}
+
+fn save_tags(tx: &Transaction, id: i64, tags: &[String]) -> Result<()> {
+    tx.execute("DELETE FROM post_tags WHERE post_id = ?1", [id])?;
+    let mut stmt = tx.prepare_cached("INSERT INTO post_tags (post_id, tag, position) VALUES (?1, ?2, ?3)")?;
+    for (position, tag) in tags.iter().enumerate() {
+        stmt.execute(params![id, tag, position])?;
+    }
+    Ok(())
+}
+
+/// Puts the post `id` in `series`, or takes it out of whatever series it was in, clearing out
+/// series left without posts. Drafts keep their title for the series to themselves, only `rename`
+/// passes it on to the series.
+fn save_series(tx: &Transaction, id: i64, series: Option<&PostSeries>, rename: bool) -> Result<()> {
+    let series_id = match series {
+        Some(series) => Some(tx.query_row(
+            "INSERT INTO series (slug, title) VALUES (?1, ?2)
+            ON CONFLICT (slug) DO UPDATE SET title = title
+            RETURNING id",
+            params![series.slug, series.title],
+            |row| row.get::<_, i64>(0),
+        )?),
+        None => None,
+    };
+    tx.execute(
+        "UPDATE posts SET series_id = ?2, series_part = ?3, series_title = ?4 WHERE id = ?1",
+        params![id, series_id, series.map(|series| series.part), series.map(|series| series.title.as_str())],
+    )?;
+    if let Some(series) = series.filter(|_| rename) {
+        rename_series(tx, series)?;
+    }
+    drop_empty_series(tx)
+}
+
+/// Deletes the series no post is part of anymore.
+fn drop_empty_series(tx: &Transaction) -> Result<()> {
+    tx.execute("DELETE FROM series WHERE id NOT IN (SELECT series_id FROM posts WHERE series_id IS NOT NULL)", [])?;
+    Ok(())
+}
+
+/// Gives `series` the title it has, on every published post and on the drafts that went by the old
+/// one. Drafts with a title of their own keep it.
+fn rename_series(tx: &Transaction, series: &PostSeries) -> Result<()> {
+    tx.execute(
+        "UPDATE posts SET series_title = ?2
+        WHERE series_id = (SELECT id FROM series WHERE slug = ?1)
+            AND (draft = 0 OR series_title = (SELECT title FROM series WHERE slug = ?1))",
+        params![series.slug, series.title],
+    )?;
+    tx.execute("UPDATE series SET title = ?2 WHERE slug = ?1", params![series.slug, series.title])?;
+    Ok(())
+}
+
+/// Puts the post `id` in the search index, replacing whatever was there for it. The body goes in as
+/// plain text, so that neither searches nor snippets trip over Markdown.
+///
+/// Changing what goes in means bumping [`SEARCH_INDEX_VERSION`].
+fn index_post(tx: &Transaction, id: i64, title: &str, body: &str, tags: &[String]) -> Result<()> {
+    tx.execute("DELETE FROM post_search WHERE rowid = ?1", [id])?;
+    tx.execute(
+        "INSERT INTO post_search (rowid, title, body, tags) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            id,
+            search::unmarked(title),
+            search::unmarked(blog_markdown::plain_text(body).as_str()),
+            search::unmarked(tags.join(", ").as_str()),
+        ],
+    )?;
+    Ok(())
+}
+
+fn find(conn: &Connection, slug: &str) -> Result<Option<Post>> {
+    let post = conn
+        .query_row(format!("SELECT {POST_COLUMNS} FROM posts WHERE slug = ?1").as_str(), [slug], read_post)
+        .optional()?;
+    let Some(mut post) = post else {
+        return Ok(None);
+    };
+    load_details(conn, &mut post)?;
+    Ok(Some(post))
+}
+
+/// Reads a revision, minus its tags, from a row selected with [`REVISION_COLUMNS`], along with the
+/// id of its post.
+fn read_revision(row: &Row) -> rusqlite::Result<(i64, Revision)> {
+    let revision = Revision {
+        number: row.get(1)?,
+        fields: PostFields {
+            slug: row.get(2)?,
+            title: row.get(3)?,
+            body: row.get(4)?,
+            summary: row.get(5)?,
+            author: row.get(6)?,
+            tags: vec![],
+            series: match (row.get(8)?, row.get(9)?, row.get(10)?) {
+                (Some(slug), Some(title), Some(part)) => Some(PostSeries { slug, title, part }),
+                _ => None,
+            },
+        },
+        saved_at: from_micros(row.get(7)?),
+    };
+    Ok((row.get(0)?, revision))
+}
+
+fn load_revision_tags(conn: &Connection, id: i64, revision: &mut Revision) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT tag FROM post_revision_tags WHERE post_id = ?1 AND number = ?2 ORDER BY position",
+    )?;
+    revision.fields.tags = stmt
+        .query_map(params![id, revision.number], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(())
+}
+
+/// Picks out the newest revision, for [`find_revisions`].
+const LATEST_REVISION: &str = "AND number = (SELECT MAX(number) FROM post_revisions WHERE post_id = ?1)";
+
+/// Reads the revisions of the post with the id `?1` that `filter` picks, newest first.
+fn find_revisions(conn: &Connection, filter: &str, args: impl rusqlite::Params) -> Result<Vec<Revision>> {
+    let mut stmt = conn.prepare_cached(format!(
+        "SELECT {REVISION_COLUMNS} FROM post_revisions WHERE post_id = ?1 {filter} ORDER BY number DESC"
+    ).as_str())?;
+    let mut revisions = stmt.query_map(args, read_revision)?.collect::<rusqlite::Result<Vec<_>>>()?;
+    for (id, revision) in revisions.iter_mut() {
+        load_revision_tags(conn, *id, revision)?;
+    }
+    Ok(revisions.into_iter().map(|(_, revision)| revision).collect())
+}
+
+/// Keeps `fields` as the post `id`'s next revision, if they changed since the last one.
+fn save_revision(tx: &Transaction, id: i64, fields: &PostFields, at: i64) -> Result<()> {
+    let last = find_revisions(tx, LATEST_REVISION, [id])?.pop();
+    if last.as_ref().is_some_and(|last| last.fields == *fields) {
+        return Ok(());
+    }
+    let number = last.map_or(1, |last| last.number + 1);
+    tx.execute(
+        format!(
+            "INSERT INTO post_revisions ({REVISION_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"
+        ).as_str(),
+        params![
+            id,
+            number,
+            fields.slug,
+            fields.title,
+            fields.body,
+            fields.summary,
+            fields.author,
+            at,
+            fields.series.as_ref().map(|series| series.slug.as_str()),
+            fields.series.as_ref().map(|series| series.title.as_str()),
+            fields.series.as_ref().map(|series| series.part),
+        ],
+    )?;
+    let mut stmt = tx.prepare_cached(
+        "INSERT INTO post_revision_tags (post_id, number, tag, position) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (position, tag) in fields.tags.iter().enumerate() {
+        stmt.execute(params![id, number, tag, position])?;
+    }
+    Ok(())
+}
+
+/// Reads a user from a row selected with [`USER_COLUMNS`].
+fn read_user(row: &Row) -> rusqlite::Result<User> {
+    Ok(User {
+        id: row.get(0)?,
+        username: row.get(1)?,
+        name: row.get(2)?,
+        password_hash: row.get(3)?,
+    })
+}
+
+/// Reads a passkey from a row selected with [`PASSKEY_COLUMNS`].
+fn read_passkey(row: &Row) -> rusqlite::Result<Passkey> {
+    Ok(Passkey {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        name: row.get(2)?,
+        public_key: row.get(3)?,
+        sign_count: row.get(4)?,
+        created_at: from_micros(row.get(5)?),
+        last_used_at: row.get::<_, Option<i64>>(6)?.map(from_micros),
+    })
+}
+
+/// Turns the unique constraint on `slug` into a nicer error.
+fn slug_taken(slug: &str) -> impl FnOnce(rusqlite::Error) -> Error + '_ {
+    move |e| match e.sqlite_error_code() {
+        Some(ErrorCode::ConstraintViolation) => Error::SlugTaken(slug.to_owned()),
+        _ => Error::Database(e),
+    }
+}
+
+impl PostRepository for SqlitePostStore {
+    fn create(&self, fields: PostFields) -> Result<Post> {
+        let fields = fields.validated()?;
+        let mut conn = self.lock();
+        let tx = conn.transaction()?;
+        let now = to_micros(now());
+        tx.execute(
+            "INSERT INTO posts (slug, title, body, summary, author, created_at, updated_at, draft)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, 1)",
+            params![fields.slug, fields.title, fields.body, fields.summary, fields.author, now],
+        ).map_err(slug_taken(fields.slug.as_str()))?;
+        let id = tx.last_insert_rowid();
+        save_tags(&tx, id, fields.tags.as_slice())?;
+        save_series(&tx, id, fields.series.as_ref(), false)?;
+        index_post(&tx, id, fields.title.as_str(), fields.body.as_str(), fields.tags.as_slice())?;
+        save_revision(&tx, id, &fields, now)?;
+        let post = find(&tx, fields.slug.as_str())?.ok_or(Error::NotFound)?;
+        tx.commit()?;
+        Ok(post)
+    }
+
+    fn get(&self, slug: &str) -> Result<Option<Post>> {
+        find(&self.lock(), slug)
+    }
+
+    fn update(&self, slug: &str, fields: PostFields) -> Result<Post> {
+        let fields = fields.validated()?;
+        let mut conn = self.lock();
+        let tx = conn.transaction()?;
+        let saved = find(&tx, slug)?.ok_or(Error::NotFound)?;
+        let id = saved.id;
+        let now = to_micros(now());
+        tx.execute(
+            "UPDATE posts SET slug = ?2, title = ?3, body = ?4, summary = ?5, author = ?6, updated_at = ?7
+            WHERE id = ?1",
+            params![id, fields.slug, fields.title, fields.body, fields.summary, fields.author, now],
+        ).map_err(slug_taken(fields.slug.as_str()))?;
+        save_tags(&tx, id, fields.tags.as_slice())?;
+        save_series(&tx, id, fields.series.as_ref(), !saved.draft)?;
+        index_post(&tx, id, fields.title.as_str(), fields.body.as_str(), fields.tags.as_slice())?;
+        save_revision(&tx, id, &fields, now)?;
+        let post = find(&tx, fields.slug.as_str())?.ok_or(Error::NotFound)?;
+        tx.commit()?;
+        Ok(post)
+    }
+
+    fn delete(&self, slug: &str) -> Result<()> {
+        let mut conn = self.lock();
+        let tx = conn.transaction()?;
+        if tx.execute("DELETE FROM posts WHERE slug = ?1", [slug])? == 0 {
+            return Err(Error::NotFound);
+        }
+        drop_empty_series(&tx)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn list(&self, query: &ListQuery) -> Result<Page> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare_cached(format!(
+            "SELECT {POST_COLUMNS} FROM posts p
+            WHERE (?1 OR (draft = 0 AND published_at <= ?6))
+                AND (?2 IS NULL OR EXISTS (SELECT 1 FROM post_tags t WHERE t.post_id = p.id AND t.tag = ?2))
+                AND (?3 IS NULL OR (COALESCE(published_at, created_at), id) < (?3, ?4))
+            ORDER BY COALESCE(published_at, created_at) DESC, id DESC
+            LIMIT ?5"
+        ).as_str())?;
+        let mut posts = stmt.query_map(
+            params![
+                query.include_drafts,
+                query.tag,
+                query.after.map(|c| to_micros(c.at)),
+                query.after.map(|c| c.id),
+                // Grab one extra to find out whether there's another page.
+                query.limit.saturating_add(1).min(i64::MAX as usize) as i64,
+                to_micros(now()),
+            ],
+            read_post,
+        )?.collect::<rusqlite::Result<Vec<_>>>()?;
+        for post in posts.iter_mut() {
+            load_details(&conn, post)?;
+        }
+        Ok(Page::from_overfetched(posts, query.limit))
+    }
+
+    fn neighbors(&self, post: &Post) -> Result<Neighbors> {
+        let conn = self.lock();
+        let at = post.cursor();
+        let neighbor = |comparison: &str, order: &str| -> Result<Option<Post>> {
+            let post = conn.query_row(
+                format!(
+                    "SELECT {POST_COLUMNS} FROM posts
+                    WHERE draft = 0 AND published_at <= ?3 AND (published_at, id) {comparison} (?1, ?2)
+                    ORDER BY published_at {order}, id {order}
+                    LIMIT 1"
+                ).as_str(),
+                params![to_micros(at.at), at.id, to_micros(now())],
+                read_post,
+            ).optional()?;
+            let Some(mut post) = post else {
+                return Ok(None);
+            };
+            load_details(&conn, &mut post)?;
+            Ok(Some(post))
+        };
+        Ok(Neighbors {
+            newer: neighbor(">", "ASC")?,
+            older: neighbor("<", "DESC")?,
+        })
+    }
+
+    fn publish(&self, slug: &str, at: DateTime<Utc>) -> Result<Post> {
+        let mut conn = self.lock();
+        let tx = conn.transaction()?;
+        let saved = find(&tx, slug)?.ok_or(Error::NotFound)?;
+        tx.execute(
+            "UPDATE posts SET
+                draft = 0,
+                published_at = CASE WHEN ?2 <= ?3 AND published_at <= ?3 THEN published_at ELSE ?2 END,
+                updated_at = ?3
+            WHERE id = ?1",
+            params![saved.id, to_micros(truncate(at)), to_micros(now())],
+        )?;
+        // The title the draft was saved with goes out along with it.
+        if let Some(series) = saved.series.as_ref().filter(|_| saved.draft) {
+            rename_series(&tx, series)?;
+        }
+        let post = find(&tx, slug)?.ok_or(Error::NotFound)?;
+        tx.commit()?;
+        Ok(post)
+    }
+
+    fn unpublish(&self, slug: &str) -> Result<Post> {
+        let conn = self.lock();
+        let changed = conn.execute(
+            "UPDATE posts SET draft = 1, updated_at = ?2 WHERE slug = ?1",
+            params![slug, to_micros(now())],
+        )?;
+        if changed == 0 {
+            return Err(Error::NotFound);
+        }
+        find(&conn, slug)?.ok_or(Error::NotFound)
+    }
+
+    fn revisions(&self, slug: &str) -> Result<Vec<Revision>> {
+        let conn = self.lock();
+        let id = find(&conn, slug)?.ok_or(Error::NotFound)?.id;
+        find_revisions(&conn, "", [id])
+    }
+
+    fn revision(&self, slug: &str, number: u32) -> Result<Option<Revision>> {
+        let conn = self.lock();
+        let id = find(&conn, slug)?.ok_or(Error::NotFound)?.id;
+        Ok(find_revisions(&conn, "AND number = ?2", params![id, number])?.pop())
+    }
+
+    fn latest_revision(&self, slug: &str) -> Result<Option<Revision>> {
+        let conn = self.lock();
+        let id = find(&conn, slug)?.ok_or(Error::NotFound)?.id;
+        Ok(find_revisions(&conn, LATEST_REVISION, [id])?.pop())
+    }
+
+    fn series(&self, slug: &str) -> Result<Option<Series>> {
+        let conn = self.lock();
+        let found = conn.query_row(
+            "SELECT id, slug, title FROM series WHERE slug = ?1",
+            [slug],
+            |row| Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get(2)?)),
+        ).optional()?;
+        let Some((id, slug, title)) = found else {
+            return Ok(None);
+        };
+        let mut stmt = conn.prepare_cached(format!(
+            "SELECT {POST_COLUMNS} FROM posts
+            WHERE series_id = ?1 AND draft = 0 AND published_at <= ?2
+            ORDER BY series_part, published_at, id"
+        ).as_str())?;
+        let mut posts = stmt.query_map(params![id, to_micros(now())], read_post)?.collect::<rusqlite::Result<Vec<_>>>()?;
+        for post in posts.iter_mut() {
+            load_details(&conn, post)?;
+        }
+        Ok(Some(Series { slug, title, posts }))
+    }
+
+    fn tags(&self) -> Result<Vec<TagCount>> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare_cached(
+            "SELECT t.tag, COUNT(*) FROM post_tags t JOIN posts p ON p.id = t.post_id
+            WHERE p.draft = 0 AND p.published_at <= ?1
+            GROUP BY t.tag
+            ORDER BY t.tag",
+        )?;
+        let tags = stmt.query_map([to_micros(now())], |row| {
+            Ok(TagCount { tag: row.get(0)?, posts: row.get(1)? })
+        })?.collect::<rusqlite::Result<_>>()?;
+        Ok(tags)
+    }
+
+    fn search(&self, text: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let Some(terms) = Terms::parse(text) else {
+            return Ok(vec![]);
+        };
+        let conn = self.lock();
+        // The index has columns named like some of the post's.
+        let columns = POST_COLUMNS.split(", ").map(|column| format!("p.{column}")).collect::<Vec<_>>().join(", ");
+        let mut stmt = conn.prepare_cached(format!(
+            "SELECT {columns},
+                highlight(post_search, 0, ?4, ?5),
+                snippet(post_search, 1, ?4, ?5, '…', {SNIPPET_WORDS})
+            FROM post_search JOIN posts p ON p.id = post_search.rowid
+            WHERE post_search MATCH ?1 AND p.draft = 0 AND p.published_at <= ?2
+            ORDER BY bm25(post_search, 10.0, 1.0, 5.0), p.id DESC
+            LIMIT ?3"
+        ).as_str())?;
+        let mut hits = stmt.query_map(
+            params![
+                terms.fts_query(),
+                to_micros(now()),
+                limit.min(i64::MAX as usize) as i64,
+                MATCH_START.to_string(),
+                MATCH_END.to_string(),
+            ],
+            |row| Ok(SearchHit {
+                post: read_post(row)?,
+                title: search::fragments(row.get_ref(10)?.as_str()?),
+                snippet: search::fragments(row.get_ref(11)?.as_str()?),
+            }),
+        )?.collect::<rusqlite::Result<Vec<_>>>()?;
+        for hit in hits.iter_mut() {
+            load_details(&conn, &mut hit.post)?;
+        }
+        Ok(hits)
+    }
+
+    fn next_publication(&self) -> Result<Option<DateTime<Utc>>> {
+        let next: Option<i64> = self.lock().query_row(
+            "SELECT MIN(published_at) FROM posts WHERE draft = 0 AND published_at > ?1",
+            [to_micros(now())],
+            |row| row.get(0),
+        )?;
+        Ok(next.map(from_micros))
+    }
+
+    fn version(&self) -> Result<u64> {
+        let conn = self.lock();
+        // The first moves with commits on other connections, the second with those on this one.
+        let others: i64 = conn.pragma_query_value(None, "data_version", |row| row.get(0))?;
+        Ok((others as u64).wrapping_add(conn.total_changes()))
+    }
+}
+
+impl UserRepository for SqlitePostStore {
+    fn save_user(&self, fields: UserFields) -> Result<User> {
+        let conn = self.lock();
+        let user = conn.query_row(
+            format!(
+                "INSERT INTO users (username, name, password_hash) VALUES (?1, ?2, ?3)
+                ON CONFLICT (username) DO UPDATE SET name = excluded.name, password_hash = excluded.password_hash
+                RETURNING {USER_COLUMNS}"
+            ).as_str(),
+            params![fields.username, fields.name, fields.password_hash],
+            read_user,
+        )?;
+        Ok(user)
+    }
+
+    fn user(&self, username: &str) -> Result<Option<User>> {
+        let conn = self.lock();
+        let user = conn
+            .query_row(format!("SELECT {USER_COLUMNS} FROM users WHERE username = ?1").as_str(), [username], read_user)
+            .optional()?;
+        Ok(user)
+    }
+
+    fn create_session(&self, session: &Session) -> Result<()> {
+        let mut conn = self.lock();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM sessions WHERE expires_at <= ?1", [to_micros(session.created_at)])?;
+        tx.execute(
+            "INSERT INTO sessions (id, user_id, csrf_token, created_at, expires_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                session.id,
+                session.user_id,
+                session.csrf_token,
+                to_micros(session.created_at),
+                to_micros(session.expires_at),
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn session(&self, id: &str, now: DateTime<Utc>) -> Result<Option<(Session, User)>> {
+        let conn = self.lock();
+        let found = conn.query_row(
+            "SELECT s.id, s.user_id, s.csrf_token, s.created_at, s.expires_at, u.id, u.username, u.name, u.password_hash
+            FROM sessions s JOIN users u ON u.id = s.user_id
+            WHERE s.id = ?1 AND s.expires_at > ?2",
+            params![id, to_micros(now)],
+            |row| {
+                let session = Session {
+                    id: row.get(0)?,
+                    user_id: row.get(1)?,
+                    csrf_token: row.get(2)?,
+                    created_at: from_micros(row.get(3)?),
+                    expires_at: from_micros(row.get(4)?),
+                };
+                let user = User {
+                    id: row.get(5)?,
+                    username: row.get(6)?,
+                    name: row.get(7)?,
+                    password_hash: row.get(8)?,
+                };
+                Ok((session, user))
+            },
+        ).optional()?;
+        Ok(found)
+    }
+
+    fn delete_session(&self, id: &str) -> Result<()> {
+        self.lock().execute("DELETE FROM sessions WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn add_passkey(&self, passkey: &Passkey) -> Result<()> {
+        self.lock().execute(
+            format!("INSERT INTO passkeys ({PASSKEY_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)").as_str(),
+            params![
+                passkey.id,
+                passkey.user_id,
+                passkey.name,
+                passkey.public_key,
+                passkey.sign_count,
+                to_micros(passkey.created_at),
+                passkey.last_used_at.map(to_micros),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn passkey(&self, id: &[u8]) -> Result<Option<(Passkey, User)>> {
+        let conn = self.lock();
+        let found = conn.query_row(
+            "SELECT p.id, p.user_id, p.name, p.public_key, p.sign_count, p.created_at, p.last_used_at,
+                u.id, u.username, u.name, u.password_hash
+            FROM passkeys p JOIN users u ON u.id = p.user_id
+            WHERE p.id = ?1",
+            [id],
+            |row| {
+                let user = User {
+                    id: row.get(7)?,
+                    username: row.get(8)?,
+                    name: row.get(9)?,
+                    password_hash: row.get(10)?,
+                };
+                Ok((read_passkey(row)?, user))
+            },
+        ).optional()?;
+        Ok(found)
+    }
+
+    fn passkeys(&self, user_id: i64) -> Result<Vec<Passkey>> {
+        let conn = self.lock();
+        let mut stmt = conn.prepare(
+            format!("SELECT {PASSKEY_COLUMNS} FROM passkeys WHERE user_id = ?1 ORDER BY created_at, rowid").as_str(),
+        )?;
+        let passkeys = stmt.query_map([user_id], read_passkey)?.collect::<rusqlite::Result<_>>()?;
+        Ok(passkeys)
+    }
+
+    fn record_passkey_use(&self, id: &[u8], sign_count: u32, at: DateTime<Utc>) -> Result<()> {
+        self.lock().execute(
+            "UPDATE passkeys SET sign_count = ?2, last_used_at = ?3 WHERE id = ?1",
+            params![id, sign_count, to_micros(at)],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,519 @@
+//! Behavior both stores have to agree on.
+
+use chrono::{Duration, Utc};
+
+use blog_api::{revisions::{Change, DiffLine}, search::Fragment};
+
+use crate::{
+    Error, ListQuery, MemoryPostStore, Neighbors, Passkey, PostFields, PostRepository, PostSeries, Session,
+    SqlitePostStore, TagCount, UserFields, UserRepository,
+};
+
+fn fields(slug: &str, tags: &[&str]) -> PostFields {
+    PostFields {
+        slug: slug.to_owned(),
+        title: format!("Title of {slug}"),
+        body: "Some *body*.".to_owned(),
+        summary: None,
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        series: None,
+        author: "Ben".to_owned(),
+    }
+}
+
+fn in_series(slug: &str, series: &str, part: u32) -> PostFields {
+    PostFields {
+        series: Some(PostSeries { slug: series.to_owned(), title: format!("Series {series}"), part }),
+        ..fields(slug, &[])
+    }
+}
+
+/// Both repositories, which every store is.
+trait Store: PostRepository + UserRepository {}
+impl<S: PostRepository + UserRepository> Store for S {}
+
+/// Runs a test against every store.
+fn each_store(test: impl Fn(&dyn Store)) {
+    test(&MemoryPostStore::new());
+    test(&SqlitePostStore::open_in_memory().expect("database opens"));
+}
+
+#[test]
+fn create_get_update_delete() {
+    each_store(|store| {
+        let created = store.create(fields("first", &["rust", "rust", "wasm"])).unwrap();
+        assert!(created.draft);
+        assert_eq!(created.published_at, None);
+        assert_eq!(created.tags, ["rust", "wasm"]);
+        assert_eq!(store.get("first").unwrap(), Some(created.clone()));
+
+        let mut changed = fields("renamed", &["c++"]);
+        changed.summary = Some("Short.".to_owned());
+        let updated = store.update("first", changed).unwrap();
+        assert_eq!(updated.id, created.id);
+        assert_eq!(updated.created_at, created.created_at);
+        assert_eq!(updated.summary.as_deref(), Some("Short."));
+        assert_eq!(updated.tags, ["c++"]);
+        assert_eq!(store.get("first").unwrap(), None);
+        assert_eq!(store.get("renamed").unwrap(), Some(updated));
+
+        store.delete("renamed").unwrap();
+        assert_eq!(store.get("renamed").unwrap(), None);
+        assert!(matches!(store.delete("renamed"), Err(Error::NotFound)));
+    });
+}
+
+#[test]
+fn slugs_are_checked() {
+    each_store(|store| {
+        assert!(matches!(store.create(fields("Not A Slug", &[])), Err(Error::InvalidSlug(_))));
+        store.create(fields("taken", &[])).unwrap();
+        store.create(fields("other", &[])).unwrap();
+        assert!(matches!(store.create(fields("taken", &[])), Err(Error::SlugTaken(_))));
+        assert!(matches!(store.update("other", fields("taken", &[])), Err(Error::SlugTaken(_))));
+        assert!(matches!(store.update("missing", fields("missing", &[])), Err(Error::NotFound)));
+    });
+}
+
+#[test]
+fn publishing_keeps_the_first_date() {
+    each_store(|store| {
+        store.create(fields("post", &[])).unwrap();
+        let first = Utc::now() - Duration::days(3);
+        let published = store.publish("post", first).unwrap();
+        assert!(published.is_published());
+
+        assert!(!store.unpublish("post").unwrap().is_published());
+        let republished = store.publish("post", Utc::now()).unwrap();
+        assert_eq!(republished.published_at, published.published_at);
+        assert!(matches!(store.publish("missing", Utc::now()), Err(Error::NotFound)));
+    });
+}
+
+#[test]
+fn scheduled_posts_wait_their_turn() {
+    each_store(|store| {
+        store.create(fields("live", &[])).unwrap();
+        let live = store.publish("live", Utc::now() - Duration::days(1)).unwrap();
+        store.create(fields("later", &[])).unwrap();
+        let version = store.version().unwrap();
+        let at = Utc::now() + Duration::days(1);
+        let scheduled = store.publish("later", at).unwrap();
+        assert_ne!(store.version().unwrap(), version);
+        assert!(scheduled.is_scheduled());
+        assert!(!scheduled.is_published());
+        assert_eq!(scheduled.published_at, Some(crate::truncate(at)));
+
+        let listed: Vec<_> = store.list(&ListQuery::default()).unwrap().posts.into_iter().map(|p| p.slug).collect();
+        assert_eq!(listed, ["live"]);
+        assert_eq!(store.list(&ListQuery { include_drafts: true, ..ListQuery::default() }).unwrap().posts.len(), 2);
+        assert_eq!(store.neighbors(&live).unwrap(), Neighbors::default());
+        assert_eq!(store.next_publication().unwrap(), Some(crate::truncate(at)));
+
+        // Rescheduling moves the date, publishing right away brings it to now.
+        let sooner = Utc::now() + Duration::hours(1);
+        assert_eq!(store.publish("later", sooner).unwrap().published_at, Some(crate::truncate(sooner)));
+        let now = store.publish("later", Utc::now()).unwrap();
+        assert!(now.is_published());
+        assert_eq!(store.next_publication().unwrap(), None);
+
+        // Scheduling a post that was live before moves it too.
+        store.unpublish("live").unwrap();
+        assert!(store.publish("live", at).unwrap().is_scheduled());
+    });
+}
+
+#[test]
+fn listing_filters_and_paginates() {
+    each_store(|store| {
+        let start = Utc::now() - Duration::days(30);
+        for day in 0..5 {
+            let slug = format!("post-{day}");
+            let tags: &[&str] = if day % 2 == 0 { &["even"] } else { &[] };
+            store.create(fields(slug.as_str(), tags)).unwrap();
+            store.publish(slug.as_str(), start + Duration::days(day)).unwrap();
+        }
+        store.create(fields("draft", &["even"])).unwrap();
+
+        let slugs = |query: &ListQuery| -> Vec<String> {
+            store.list(query).unwrap().posts.into_iter().map(|p| p.slug).collect()
+        };
+
+        let mut query = ListQuery { limit: 2, ..ListQuery::default() };
+        let first = store.list(&query).unwrap();
+        assert_eq!(first.posts.iter().map(|p| p.slug.as_str()).collect::<Vec<_>>(), ["post-4", "post-3"]);
+        query.after = first.next;
+        assert_eq!(slugs(&query), ["post-2", "post-1"]);
+        query.after = store.list(&query).unwrap().next;
+        let last = store.list(&query).unwrap();
+        assert_eq!(last.posts.len(), 1);
+        assert_eq!(last.next, None);
+
+        let tagged = ListQuery { tag: Some("even".to_owned()), ..ListQuery::default() };
+        assert_eq!(slugs(&tagged), ["post-4", "post-2", "post-0"]);
+        let with_drafts = ListQuery { include_drafts: true, ..tagged };
+        assert_eq!(slugs(&with_drafts), ["draft", "post-4", "post-2", "post-0"]);
+        let everything = ListQuery { limit: usize::MAX, ..with_drafts };
+        assert_eq!(store.list(&everything).unwrap().next, None);
+    });
+}
+
+#[test]
+fn neighbors_skip_drafts() {
+    each_store(|store| {
+        let start = Utc::now() - Duration::days(30);
+        for (day, slug) in ["oldest", "middle", "hidden", "newest"].into_iter().enumerate() {
+            store.create(fields(slug, &[])).unwrap();
+            store.publish(slug, start + Duration::days(day as i64)).unwrap();
+        }
+        store.unpublish("hidden").unwrap();
+
+        let slug = |post: Option<crate::Post>| post.map(|p| p.slug);
+        let middle = store.neighbors(&store.get("middle").unwrap().unwrap()).unwrap();
+        assert_eq!(slug(middle.newer).as_deref(), Some("newest"));
+        assert_eq!(slug(middle.older).as_deref(), Some("oldest"));
+
+        let newest = store.neighbors(&store.get("newest").unwrap().unwrap()).unwrap();
+        assert_eq!(slug(newest.newer), None);
+        assert_eq!(slug(newest.older).as_deref(), Some("middle"));
+    });
+}
+
+#[test]
+fn series_keep_their_order() {
+    each_store(|store| {
+        let start = Utc::now() - Duration::days(30);
+        // Published in a different order than they're read in.
+        for (day, (slug, part)) in [("two", 2), ("one", 1), ("three", 3), ("unfinished", 4)].into_iter().enumerate() {
+            store.create(in_series(slug, "parts", part)).unwrap();
+            store.publish(slug, start + Duration::days(day as i64)).unwrap();
+        }
+        store.unpublish("unfinished").unwrap();
+        store.create(in_series("other", "elsewhere", 1)).unwrap();
+
+        let series = store.series("parts").unwrap().unwrap();
+        assert_eq!(series.title, "Series parts");
+        assert_eq!(series.posts.iter().map(|p| p.slug.as_str()).collect::<Vec<_>>(), ["one", "two", "three"]);
+        assert_eq!(series.parts().iter().map(|part| part.part).collect::<Vec<_>>(), [1, 2, 3]);
+        // Drafts keep their series, just without showing up in it.
+        let elsewhere = store.series("elsewhere").unwrap().unwrap();
+        assert_eq!(elsewhere.posts, []);
+        assert_eq!(store.series("nowhere").unwrap(), None);
+
+        // Saving a part with a new title renames the series everywhere.
+        let mut renamed = in_series("two", "parts", 2);
+        renamed.series.as_mut().unwrap().title = "  ".to_owned();
+        let two = store.update("two", renamed).unwrap();
+        assert_eq!(two.series.as_ref().map(|series| series.title.as_str()), Some("parts"));
+        assert_eq!(store.get("one").unwrap().unwrap().series, two.series.clone().map(|s| PostSeries { part: 1, ..s }));
+
+        // Series go away with their last post.
+        store.update("other", fields("other", &[])).unwrap();
+        assert_eq!(store.series("elsewhere").unwrap(), None);
+        assert_eq!(store.revisions("other").unwrap()[1].fields, in_series("other", "elsewhere", 1));
+
+        let mut invalid = in_series("invalid", "Not a slug", 1);
+        assert!(matches!(store.create(invalid.clone()), Err(Error::InvalidSlug(_))));
+        invalid.series = None;
+        store.create(invalid).unwrap();
+    });
+}
+
+#[test]
+fn deleting_the_last_post_of_a_series_deletes_the_series() {
+    each_store(|store| {
+        store.create(in_series("only", "alone", 1)).unwrap();
+        store.publish("only", Utc::now()).unwrap();
+        assert!(store.series("alone").unwrap().is_some());
+
+        store.delete("only").unwrap();
+        assert_eq!(store.series("alone").unwrap(), None);
+    });
+}
+
+#[test]
+fn drafts_keep_their_series_title_until_published() {
+    each_store(|store| {
+        let titled = |slug: &str, part, title: &str| {
+            let mut fields = in_series(slug, "parts", part);
+            fields.series.as_mut().unwrap().title = title.to_owned();
+            fields
+        };
+        let title = |slug: &str| store.get(slug).unwrap().unwrap().series.unwrap().title;
+        store.create(in_series("one", "parts", 1)).unwrap();
+        store.publish("one", Utc::now()).unwrap();
+        store.create(in_series("follows", "parts", 3)).unwrap();
+
+        store.create(titled("two", 2, "Better")).unwrap();
+        store.update("two", titled("two", 2, "Best")).unwrap();
+        assert_eq!(store.series("parts").unwrap().unwrap().title, "Series parts");
+        assert_eq!(title("one"), "Series parts");
+        assert_eq!(title("two"), "Best");
+
+        store.publish("two", Utc::now()).unwrap();
+        assert_eq!(store.series("parts").unwrap().unwrap().title, "Best");
+        assert_eq!(title("one"), "Best");
+        // Drafts that went by the old title follow along.
+        assert_eq!(title("follows"), "Best");
+
+        // Republishing a draft that was only following along doesn't undo a later rename.
+        store.unpublish("one").unwrap();
+        store.update("two", titled("two", 2, "Final")).unwrap();
+        assert_eq!(title("one"), "Final");
+        store.publish("one", Utc::now()).unwrap();
+        assert_eq!(store.series("parts").unwrap().unwrap().title, "Final");
+    });
+}
+
+#[test]
+fn tags_are_counted() {
+    each_store(|store| {
+        for (slug, tags) in [("a", &["rust", "wasm"][..]), ("b", &["rust"]), ("c", &["zig"]), ("later", &["rust"])] {
+            store.create(fields(slug, tags)).unwrap();
+        }
+        store.publish("a", Utc::now()).unwrap();
+        store.publish("b", Utc::now()).unwrap();
+        store.publish("later", Utc::now() + Duration::days(1)).unwrap();
+
+        let count = |tag: &str, posts| TagCount { tag: tag.to_owned(), posts };
+        assert_eq!(store.tags().unwrap(), [count("rust", 2), count("wasm", 1)]);
+    });
+}
+
+#[test]
+fn search_finds_published_posts() {
+    each_store(|store| {
+        let post = |slug: &str, title: &str, body: &str, tags: &[&str]| PostFields {
+            title: title.to_owned(),
+            body: body.to_owned(),
+            ..fields(slug, tags)
+        };
+        store.create(post("borrow", "Fighting the borrow checker", "It's `&mut` **all** the way down.", &["rust"])).unwrap();
+        store.create(post("notes", "Weekly notes", "Mostly gardening.\n\nThe *borrow* checker came up once.", &[])).unwrap();
+        store.create(post("draft", "Borrow checker draft", "Not yet.", &[])).unwrap();
+        store.create(post("later", "Borrow checker, later", "Not yet either.", &[])).unwrap();
+        store.publish("borrow", Utc::now()).unwrap();
+        store.publish("notes", Utc::now()).unwrap();
+        store.publish("later", Utc::now() + Duration::days(1)).unwrap();
+
+        let slugs = |text: &str| {
+            store.search(text, 10).unwrap().into_iter().map(|hit| hit.post.slug).collect::<Vec<_>>()
+        };
+        // A match in the title beats one in the body.
+        assert_eq!(slugs("borrow"), ["borrow", "notes"]);
+        assert_eq!(slugs("Borrow CHECKER"), ["borrow", "notes"]);
+        assert_eq!(slugs("gardening borrow"), ["notes"]);
+        assert_eq!(slugs("rust"), ["borrow"]);
+        // Still typing the last word, or done with it.
+        assert_eq!(slugs("gard"), ["notes"]);
+        assert_eq!(slugs("gard "), Vec::<String>::new());
+        // Nothing reads as query syntax.
+        assert_eq!(slugs("\"borrow (all* -"), ["borrow"]);
+        assert_eq!(slugs(" ?! "), Vec::<String>::new());
+        assert_eq!(store.search("borrow", 1).unwrap().len(), 1);
+
+        let hit = store.search("borrow", 10).unwrap().remove(0);
+        let text = |text: &str| Fragment { text: text.to_owned(), matched: false };
+        let matched = |text: &str| Fragment { text: text.to_owned(), matched: true };
+        assert_eq!(hit.title, [text("Fighting the "), matched("borrow"), text(" checker")]);
+        assert_eq!(hit.snippet, [text("It's &mut all the way down.")]);
+        let hit = store.search("borrow", 10).unwrap().remove(1);
+        assert_eq!(hit.snippet, [text("Mostly gardening.\nThe "), matched("borrow"), text(" checker came up once.")]);
+
+        // Edits and deletions show up right away.
+        store.update("notes", post("notes", "Weekly notes", "Mostly gardening.", &[])).unwrap();
+        assert_eq!(slugs("borrow"), ["borrow"]);
+        store.delete("borrow").unwrap();
+        assert_eq!(slugs("borrow"), Vec::<String>::new());
+    });
+}
+
+#[test]
+fn saves_are_kept_as_revisions() {
+    each_store(|store| {
+        let first = fields("post", &["rust"]);
+        store.create(first.clone()).unwrap();
+        let mut second = fields("renamed", &["rust", "wasm"]);
+        second.body = "Some *body*.\nAnd more.".to_owned();
+        store.update("post", second.clone()).unwrap();
+        // Saving without changing anything, say to publish, doesn't count.
+        store.update("renamed", second.clone()).unwrap();
+
+        let revisions = store.revisions("renamed").unwrap();
+        assert_eq!(revisions.iter().map(|r| r.number).collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(revisions[0].fields, second);
+        assert_eq!(revisions[1].fields, first);
+        assert_eq!(store.revision("renamed", 1).unwrap().unwrap().fields, first);
+        assert_eq!(store.revision("renamed", 3).unwrap(), None);
+        assert_eq!(store.latest_revision("renamed").unwrap().unwrap().fields, second);
+        assert!(matches!(store.latest_revision("post"), Err(Error::NotFound)));
+        assert!(matches!(store.revisions("post"), Err(Error::NotFound)));
+
+        let diff = revisions[0].diff(Some(&revisions[1]));
+        assert_eq!(diff.from.map(|from| from.number), Some(1));
+        let changed: Vec<_> = diff.fields.iter().map(|field| field.field.as_str()).collect();
+        assert_eq!(changed, ["title", "slug", "tags", "body"]);
+        let same = |text: &str| DiffLine { change: Change::Same, text: text.to_owned() };
+        let added = |text: &str| DiffLine { change: Change::Added, text: text.to_owned() };
+        let removed = |text: &str| DiffLine { change: Change::Removed, text: text.to_owned() };
+        assert_eq!(diff.fields[2].lines, [removed("rust"), added("rust, wasm")]);
+        assert_eq!(diff.fields[3].lines, [same("Some *body*."), added("And more.")]);
+        // Compared to nothing, everything's new.
+        let from_nothing = revisions[1].diff(None);
+        assert_eq!(from_nothing.fields.len(), 4);
+        assert_eq!(from_nothing.fields[0].lines, [added("Title of post")]);
+        assert_eq!(revisions[0].diff(Some(&revisions[0])).fields, []);
+
+        store.delete("renamed").unwrap();
+        assert!(matches!(store.revisions("renamed"), Err(Error::NotFound)));
+    });
+}
+
+#[test]
+fn users_and_sessions() {
+    each_store(|store| {
+        let user = |password_hash: &str| UserFields {
+            username: "ben".to_owned(),
+            name: "Ben".to_owned(),
+            password_hash: password_hash.to_owned(),
+        };
+        let created = store.save_user(user("first")).unwrap();
+        // Saving the same username again changes the password.
+        let changed = store.save_user(user("second")).unwrap();
+        assert_eq!(changed.id, created.id);
+        assert_eq!(store.user("ben").unwrap(), Some(changed.clone()));
+        assert_eq!(store.user("nobody").unwrap(), None);
+
+        let now = Utc::now();
+        let session = |id: &str, expires_at| Session {
+            id: id.to_owned(),
+            user_id: changed.id,
+            csrf_token: format!("csrf-{id}"),
+            created_at: crate::truncate(now),
+            expires_at: crate::truncate(expires_at),
+        };
+        let live = session("live", now + Duration::days(1));
+        store.create_session(&live).unwrap();
+        assert_eq!(store.session("live", now).unwrap(), Some((live.clone(), changed.clone())));
+        assert_eq!(store.session("live", now + Duration::days(2)).unwrap(), None);
+        assert_eq!(store.session("missing", now).unwrap(), None);
+
+        store.delete_session("live").unwrap();
+        assert_eq!(store.session("live", now).unwrap(), None);
+        store.delete_session("live").unwrap();
+    });
+}
+
+#[test]
+fn passkeys() {
+    each_store(|store| {
+        let user = store.save_user(UserFields {
+            username: "ben".to_owned(),
+            name: "Ben".to_owned(),
+            password_hash: "hash".to_owned(),
+        }).unwrap();
+        let now = crate::truncate(Utc::now());
+        let passkey = |id: u8| Passkey {
+            id: vec![id; 16],
+            user_id: user.id,
+            name: format!("key {id}"),
+            public_key: vec![4; 65],
+            sign_count: 0,
+            created_at: now + Duration::seconds(id.into()),
+            last_used_at: None,
+        };
+        store.add_passkey(&passkey(1)).unwrap();
+        store.add_passkey(&passkey(2)).unwrap();
+        assert_eq!(store.passkeys(user.id).unwrap(), [passkey(1), passkey(2)]);
+        assert_eq!(store.passkeys(user.id + 1).unwrap(), []);
+        assert_eq!(store.passkey(&[1; 16]).unwrap(), Some((passkey(1), user.clone())));
+        assert_eq!(store.passkey(&[3; 16]).unwrap(), None);
+
+        let used_at = now + Duration::minutes(5);
+        store.record_passkey_use(&[2; 16], 7, used_at).unwrap();
+        store.record_passkey_use(&[3; 16], 7, used_at).unwrap();
+        let (used, _) = store.passkey(&[2; 16]).unwrap().unwrap();
+        assert_eq!(used, Passkey { sign_count: 7, last_used_at: Some(used_at), ..passkey(2) });
+    });
+}
+
+#[test]
+fn reopening_keeps_posts() {
+    let path = std::env::temp_dir().join(format!("blog-store-{}.sqlite3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    {
+        let store = SqlitePostStore::open(&path).unwrap();
+        store.create(fields("kept", &["tag"])).unwrap();
+    }
+    let store = SqlitePostStore::open(&path).unwrap();
+    assert_eq!(store.get("kept").unwrap().unwrap().tags, ["tag"]);
+    drop(store);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn newer_databases_are_left_alone() {
+    let path = std::env::temp_dir().join(format!("blog-store-newer-{}.sqlite3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    drop(SqlitePostStore::open(&path).unwrap());
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+    conn.pragma_update(None, "user_version", version + 1).unwrap();
+
+    assert!(matches!(SqlitePostStore::open(&path), Err(Error::NewerSchema(v)) if v == version + 1));
+    let after: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+    assert_eq!(after, version + 1);
+    drop(conn);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn outdated_search_indexes_are_rebuilt() {
+    let path = std::env::temp_dir().join(format!("blog-store-search-{}.sqlite3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    {
+        let store = SqlitePostStore::open(&path).unwrap();
+        store.create(fields("old", &["forgotten"])).unwrap();
+        store.publish("old", Utc::now()).unwrap();
+    }
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    // Up to date indexes are trusted as they are, not checked on every start.
+    conn.execute("DELETE FROM post_search", []).unwrap();
+    assert_eq!(SqlitePostStore::open(&path).unwrap().search("forgotten", 10).unwrap(), []);
+    // As if the index was filled in by an older version.
+    conn.execute("UPDATE search_index SET version = 0", []).unwrap();
+    let store = SqlitePostStore::open(&path).unwrap();
+    assert_eq!(store.search("forgotten", 10).unwrap()[0].post.slug, "old");
+    drop((store, conn));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn posts_cant_fake_search_marks() {
+    let store = SqlitePostStore::open_in_memory().unwrap();
+    let mut marked = fields("marked", &[]);
+    marked.title = "Fake \u{2}marks\u{3} here".to_owned();
+    marked.body = "Nothing \u{3}matched\u{2} there.".to_owned();
+    store.create(marked).unwrap();
+    store.publish("marked", Utc::now()).unwrap();
+
+    let hit = store.search("matched", 10).unwrap().remove(0);
+    assert_eq!(hit.title, [Fragment { text: "Fake marks here".to_owned(), matched: false }]);
+    let matched: Vec<_> = hit.snippet.iter().filter(|fragment| fragment.matched).map(|fragment| fragment.text.as_str()).collect();
+    assert_eq!(matched, ["matched"]);
+    assert_eq!(hit.snippet.iter().map(|fragment| fragment.text.as_str()).collect::<String>(), "Nothing matched there.");
+}
+
+#[test]
+fn version_sees_other_connections() {
+    let path = std::env::temp_dir().join(format!("blog-store-version-{}.sqlite3", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    {
+        let reader = SqlitePostStore::open(&path).unwrap();
+        let writer = SqlitePostStore::open(&path).unwrap();
+        let version = reader.version().unwrap();
+        writer.create(fields("written", &[])).unwrap();
+        assert_ne!(reader.version().unwrap(), version);
+    }
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,80 @@
+//! Who gets to write posts, how they log in, and who's logged in right now.
+
+use chrono::{DateTime, Utc};
+
+use crate::Result;
+
+/// Someone who can log in to the editor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    /// What posts are attributed to.
+    pub name: String,
+    /// An argon2 hash in PHC string format.
+    pub password_hash: String,
+}
+
+/// The fields of a user that can be set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserFields {
+    pub username: String,
+    pub name: String,
+    pub password_hash: String,
+}
+
+/// A logged in browser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    /// A hash of the token in the session cookie, so the database alone can't log anyone in.
+    pub id: String,
+    pub user_id: i64,
+    /// Has to come along with every request that changes something.
+    pub csrf_token: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A passkey someone can log in with instead of their password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Passkey {
+    /// The credential id the authenticator picked.
+    pub id: Vec<u8>,
+    pub user_id: i64,
+    /// Something to tell passkeys apart by, like the device it's on.
+    pub name: String,
+    /// An uncompressed SEC1 P-256 point.
+    pub public_key: Vec<u8>,
+    /// The authenticator's signature counter as of the last login.
+    pub sign_count: u32,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Storage for users, their passkeys and their sessions.
+pub trait UserRepository: Send + Sync {
+    /// Adds a user, or replaces the name and password of the one with the same username.
+    fn save_user(&self, fields: UserFields) -> Result<User>;
+
+    fn user(&self, username: &str) -> Result<Option<User>>;
+
+    /// Stores a new session, clearing out expired ones on the way.
+    fn create_session(&self, session: &Session) -> Result<()>;
+
+    /// The session with that id and whose it is, unless it's expired by `now`.
+    fn session(&self, id: &str, now: DateTime<Utc>) -> Result<Option<(Session, User)>>;
+
+    /// Ends a session. Ending one that doesn't exist is fine.
+    fn delete_session(&self, id: &str) -> Result<()>;
+
+    fn add_passkey(&self, passkey: &Passkey) -> Result<()>;
+
+    /// The passkey with that credential id and whose it is.
+    fn passkey(&self, id: &[u8]) -> Result<Option<(Passkey, User)>>;
+
+    /// Everyone of a user's passkeys, oldest first.
+    fn passkeys(&self, user_id: i64) -> Result<Vec<Passkey>>;
+
+    /// Notes a login with a passkey. Doesn't mind passkeys that don't exist.
+    fn record_passkey_use(&self, id: &[u8], sign_count: u32, at: DateTime<Utc>) -> Result<()>;
+}
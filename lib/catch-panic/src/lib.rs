@@ -0,0 +1,85 @@
+//! Turns panics inside handlers into a proper 500 instead of a dropped connection, and tags every
+//! request's logs with an ID.
+//!
+//! Both servers use this, each with their own idea of what a 500 looks like.
+
+use std::{
+    backtrace::Backtrace,
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use axum::{
+    body::Body,
+    http::{HeaderMap, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use futures::FutureExt;
+use trc::Instrument;
+
+#[cfg(test)]
+mod tests;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest request ID taken from a proxy. nginx's are 32 characters.
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Replaces the default panic hook with one that logs through `tracing`.
+///
+/// Since [`catch`] runs every request inside a span carrying its ID, the log line for a panic
+/// ends up tagged with the request that caused it.
+pub fn install_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let backtrace = Backtrace::force_capture();
+        trc::error!("panicked: {info}\n{backtrace}");
+    }));
+}
+
+/// The ID nginx (or another proxy) gave the request through `x-request-id`, otherwise a new one.
+///
+/// The ID ends up in logs and on error pages, so anything but a short run of ascii letters,
+/// digits and dashes gets replaced rather than trusted.
+pub fn request_id(headers: &HeaderMap) -> String {
+    headers.get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_owned)
+        .unwrap_or_else(|| format!("{:016x}", NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed)))
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    (1..=MAX_REQUEST_ID_LEN).contains(&id.len()) && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+/// Runs the rest of the stack inside a span for the request, answering with `on_panic` if it
+/// panics. The request ID is echoed back in the response either way.
+///
+/// `on_panic` is built before the request is handed on, so it can only go by what was known up
+/// front, like whether the client wants JSON.
+pub async fn catch<F, Fut>(req: Request<Body>, next: Next<Body>, on_panic: F) -> Response
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Response>,
+{
+    let request_id = request_id(req.headers());
+    let span = trc::info_span!("request", id = %request_id, method = %req.method(), uri = %req.uri());
+
+    let mut response = match AssertUnwindSafe(next.run(req).instrument(span.clone())).catch_unwind().await {
+        Ok(response) => response,
+        Err(_) => {
+            // The hook has already logged the details, this just ties it off.
+            span.in_scope(|| trc::error!("Handler panicked, responding with a 500."));
+            on_panic().await
+        },
+    };
+
+    if let Ok(value) = HeaderValue::from_str(request_id.as_str()) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
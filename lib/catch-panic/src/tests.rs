@@ -0,0 +1,48 @@
+use axum::{
+    body::Body,
+    http::{HeaderMap, Request, StatusCode},
+    middleware,
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use tower::ServiceExt;
+
+use crate::{catch, request_id, REQUEST_ID_HEADER};
+
+fn with_id(id: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(REQUEST_ID_HEADER, id.parse().unwrap());
+    headers
+}
+
+#[test]
+fn proxy_ids_are_kept_when_sane() {
+    assert_eq!(request_id(&with_id("3f2a-77b1")), "3f2a-77b1");
+
+    for junk in ["<script>", "a b", "id\tnext", &"a".repeat(65), ""] {
+        let id = request_id(&with_id(junk));
+        assert_ne!(id, junk);
+        assert_eq!(id.len(), 16, "{junk:?} got {id:?}");
+    }
+    assert_ne!(request_id(&HeaderMap::new()), request_id(&HeaderMap::new()));
+}
+
+#[tokio::test]
+async fn panics_become_the_fallback() {
+    let app = Router::new()
+        .route("/fine", get(|| async { "fine" }))
+        .route("/panics", get(|| async { panic!("on purpose") as &str }))
+        .layer(middleware::from_fn(|req, next| {
+            catch(req, next, || async { (StatusCode::INTERNAL_SERVER_ERROR, "sorry").into_response() })
+        }));
+    let request = |path: &str| Request::get(path).header(REQUEST_ID_HEADER, "abc").body(Body::empty()).unwrap();
+
+    let fine = app.clone().oneshot(request("/fine")).await.unwrap();
+    assert_eq!(fine.status(), StatusCode::OK);
+    assert_eq!(fine.headers()[REQUEST_ID_HEADER], "abc");
+
+    let panicked = app.oneshot(request("/panics")).await.unwrap();
+    assert_eq!(panicked.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(panicked.headers()[REQUEST_ID_HEADER], "abc");
+}
@@ -1,25 +1,101 @@
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr};
 use serde::Deserialize;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct AddressCfg {
     pub domain: String,
+    /// Other domains pointing at the same server. Pages served from these are never indexed.
+    #[serde(default)]
+    pub alternate_domains: Vec<String>,
     pub ip: IpAddr,
     pub port: u16,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RobotsCfg {
+    /// Paths crawlers are asked to stay out of.
+    pub disallow: Vec<String>,
+    /// Seconds crawlers should wait between requests, if set.
+    pub crawl_delay: Option<u32>,
+}
+
+impl Default for RobotsCfg {
+    fn default() -> Self {
+        Self {
+            disallow: vec!["/blog/editor".to_owned(), "/blog/login".to_owned()],
+            crawl_delay: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct PrimaryServerCfg {
     pub address: AddressCfg,
     pub resource_root: String,
+    #[serde(default)]
+    pub robots: RobotsCfg,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiServerCfg {
+    pub address: AddressCfg,
+    /// Most posts a single page of a listing can hold.
+    #[serde(default = "ApiServerCfg::default_max_page_size")]
+    pub max_page_size: usize,
+    /// Where the pages using passkeys are served from, like `http://localhost:8080` when testing.
+    /// Defaults to `https://` and the primary server's domain.
+    #[serde(default)]
+    pub passkey_origin: Option<String>,
+}
+
+impl ApiServerCfg {
+    fn default_max_page_size() -> usize {
+        50
+    }
+}
+
+impl Default for ApiServerCfg {
+    /// Listens on `localhost:8081`.
+    fn default() -> Self {
+        Self {
+            address: AddressCfg {
+                domain: "localhost".to_owned(),
+                alternate_domains: vec![],
+                ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                port: 8081,
+            },
+            max_page_size: Self::default_max_page_size(),
+            passkey_origin: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ServersCfg {
     pub primary: PrimaryServerCfg,
+    #[serde(default)]
+    pub api: ApiServerCfg,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DatabaseCfg {
+    /// Path to the SQLite database holding the blog.
+    pub path: String,
+}
+
+impl Default for DatabaseCfg {
+    fn default() -> Self {
+        Self {
+            path: "blog.sqlite3".to_owned(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Cfg {
     pub servers: ServersCfg,
+    #[serde(default)]
+    pub database: DatabaseCfg,
 }
@@ -1,19 +1,27 @@
 //! A collection of metadata used during site generation.
+//!
+//! Everything here is built on [`Cow`], so metadata can either borrow from `'static` literals or
+//! own data pulled in at runtime (from a database, config, etc). [`PageMetaData::into_owned`] and
+//! friends detach borrowed metadata entirely.
 
-use chrono::{Datelike, Utc};
+use chrono::{DateTime, Datelike, Utc};
 use maud::{html, Markup, PreEscaped, Render};
-use std::fs;
+use serde::Deserialize;
+use std::{borrow::Cow, fs};
+
+use crate::schema::Schema;
 
 /// Represents a logo.
+#[derive(Debug, Clone, Deserialize)]
 pub struct LogoLink<'a> {
     /// The link the logo will resolve to when clicked.
-    pub url: &'a str,
+    pub url: Cow<'a, str>,
     /// The url of the logo picture.
-    pub logo: &'a str,
+    pub logo: Cow<'a, str>,
     /// Alternative text if the logo cannot be loaded.
-    pub alt_text: &'a str,
+    pub alt_text: Cow<'a, str>,
     /// Text accompanying the logo.
-    pub text: &'a str,
+    pub text: Cow<'a, str>,
 }
 impl<'a> Render for LogoLink<'a> {
     fn render(&self) -> Markup {
@@ -24,71 +32,456 @@ impl<'a> Render for LogoLink<'a> {
         }
     }
 }
+impl<'a> LogoLink<'a> {
+    pub fn into_owned(self) -> LogoLink<'static> {
+        LogoLink {
+            url: Cow::Owned(self.url.into_owned()),
+            logo: Cow::Owned(self.logo.into_owned()),
+            alt_text: Cow::Owned(self.alt_text.into_owned()),
+            text: Cow::Owned(self.text.into_owned()),
+        }
+    }
+}
 
 /// Data used during site generation for things like css, scripts, contact info and menus. Most are
 /// for meta tags.
+///
+/// Start from [`PageMetaData::default`] (or a [`SiteLayout`](crate::layout::SiteLayout)) and
+/// chain the setters below to fill in the rest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct PageMetaData<'a> {
     /// Language of the website.
-    pub lang: &'a str,
+    pub lang: Cow<'a, str>,
     /// Encoding of the website.
-    pub charset: &'a str,
+    pub charset: Cow<'a, str>,
     /// Scripts to include in the website.
-    pub scripts: &'a [Script<'a>],
+    pub scripts: Cow<'a, [Script<'a>]>,
     /// CSS to include in the website.
-    pub css: &'a [Css<'a>],
+    pub css: Cow<'a, [Css<'a>]>,
     /// The title of the website.
-    pub title: &'a str,
+    pub title: Cow<'a, str>,
+    /// The title of this specific page, if it has one.
+    pub page_title: Option<Cow<'a, str>>,
+    /// How [`page_title`](Self::page_title) and [`title`](Self::title) combine. `{page}` and
+    /// `{site}` are replaced with each respectively.
+    pub title_template: Cow<'a, str>,
     /// The description of the website.
-    pub description: &'a str,
+    pub description: Cow<'a, str>,
     /// The copyright data of the website.
     pub copyright: Copyright<'a>,
     /// The favicon
-    pub favicons: &'a [Favicon<'a>],
+    pub favicons: Cow<'a, [Favicon<'a>]>,
     /// The menu of the website.
-    pub menu: Option<&'a Menu<'a>>,
+    pub menu: Option<Menu<'a>>,
     /// The points of contact for the owner of the website.
-    pub contact: Option<&'a Contact<'a>>,
+    pub contact: Option<Contact<'a>>,
     /// The logo of the website.
-    pub logo: Option<&'a Logo<'a>>,
+    pub logo: Option<Logo<'a>>,
     /// The theme color of the website. Affects mobile address name bars.
-    pub theme_color: &'a str,
+    pub theme_color: Cow<'a, str>,
+    /// The canonical URL of the page.
+    pub canonical: Option<Cow<'a, str>>,
+    /// Instructions for crawlers.
+    pub robots: Robots,
+    /// Open Graph and Twitter Card metadata, for link previews.
+    pub social: Option<Social<'a>>,
+    /// Alternate versions of the page, like feeds.
+    pub alternates: Cow<'a, [Alternate<'a>]>,
+    /// Serialized JSON-LD blocks. See [`schema`](crate::schema).
+    pub structured_data: Cow<'a, [Cow<'a, str>]>,
 }
 impl<'a> Default for PageMetaData<'a> {
     fn default() -> Self {
         Self {
-            lang: "en-US",
-            charset: "UTF-8",
-            scripts: &[],
-            css: &[],
-            title: "Benjamin Xu",
-            description: "Benjamin Xu's personal site.",
-            copyright: Copyright {
-                name: &Name {
-                    first: "Benjamin",
-                    middle: Some("Peiyan"),
-                    last: "Xu",
-                    nicknames: &[],
-                },
-                icon: "©",
-                rights_clause: "All rights reserved",
-            },
-            favicons: &[],
+            lang: Cow::Borrowed("en-US"),
+            charset: Cow::Borrowed("UTF-8"),
+            scripts: Cow::Borrowed(&[]),
+            css: Cow::Borrowed(&[]),
+            title: Cow::Borrowed("Benjamin Xu"),
+            page_title: None,
+            title_template: Cow::Borrowed("{page} | {site}"),
+            description: Cow::Borrowed("Benjamin Xu's personal site."),
+            copyright: Copyright::default(),
+            favicons: Cow::Borrowed(&[]),
             menu: None,
             contact: None,
             logo: None,
-            theme_color: "#00003f",
+            theme_color: Cow::Borrowed("#00003f"),
+            canonical: None,
+            robots: Robots::default(),
+            social: None,
+            alternates: Cow::Borrowed(&[]),
+            structured_data: Cow::Borrowed(&[]),
+        }
+    }
+}
+impl<'a> PageMetaData<'a> {
+    pub fn lang(mut self, lang: impl Into<Cow<'a, str>>) -> Self {
+        self.lang = lang.into();
+        self
+    }
+
+    pub fn charset(mut self, charset: impl Into<Cow<'a, str>>) -> Self {
+        self.charset = charset.into();
+        self
+    }
+
+    /// Adds a script after the existing scripts.
+    pub fn script(mut self, script: Script<'a>) -> Self {
+        self.scripts.to_mut().push(script);
+        self
+    }
+
+    /// Adds CSS after the existing CSS.
+    pub fn css(mut self, css: Css<'a>) -> Self {
+        self.css.to_mut().push(css);
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<Cow<'a, str>>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the page specific part of the title. See [`title_template`](Self::title_template).
+    pub fn page_title(mut self, page_title: impl Into<Cow<'a, str>>) -> Self {
+        self.page_title = Some(page_title.into());
+        self
+    }
+
+    pub fn title_template(mut self, title_template: impl Into<Cow<'a, str>>) -> Self {
+        self.title_template = title_template.into();
+        self
+    }
+
+    /// The title as it appears in the `<title>` tag.
+    pub fn full_title(&self) -> Cow<'_, str> {
+        match self.page_title.as_deref() {
+            Some(page) => Cow::Owned(self.title_template.replace("{page}", page).replace("{site}", &self.title)),
+            None => Cow::Borrowed(&self.title),
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<Cow<'a, str>>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn copyright(mut self, copyright: Copyright<'a>) -> Self {
+        self.copyright = copyright;
+        self
+    }
+
+    /// Adds a favicon after the existing favicons.
+    pub fn favicon(mut self, favicon: Favicon<'a>) -> Self {
+        self.favicons.to_mut().push(favicon);
+        self
+    }
+
+    pub fn menu(mut self, menu: Menu<'a>) -> Self {
+        self.menu = Some(menu);
+        self
+    }
+
+    pub fn contact(mut self, contact: Contact<'a>) -> Self {
+        self.contact = Some(contact);
+        self
+    }
+
+    pub fn logo(mut self, logo: Logo<'a>) -> Self {
+        self.logo = Some(logo);
+        self
+    }
+
+    pub fn theme_color(mut self, theme_color: impl Into<Cow<'a, str>>) -> Self {
+        self.theme_color = theme_color.into();
+        self
+    }
+
+    pub fn canonical(mut self, canonical: impl Into<Cow<'a, str>>) -> Self {
+        self.canonical = Some(canonical.into());
+        self
+    }
+
+    pub fn robots(mut self, robots: Robots) -> Self {
+        self.robots = robots;
+        self
+    }
+
+    pub fn social(mut self, social: Social<'a>) -> Self {
+        self.social = Some(social);
+        self
+    }
+
+    /// Adds an alternate version of the page.
+    pub fn alternate(mut self, alternate: Alternate<'a>) -> Self {
+        self.alternates.to_mut().push(alternate);
+        self
+    }
+
+    /// Adds several alternate versions of the page.
+    pub fn alternates(mut self, alternates: impl IntoIterator<Item = Alternate<'a>>) -> Self {
+        self.alternates.to_mut().extend(alternates);
+        self
+    }
+
+    /// Adds a block of structured data.
+    pub fn structured_data(mut self, schema: impl Into<Schema<'a>>) -> Self {
+        self.structured_data.to_mut().push(Cow::Owned(schema.into().to_json_ld()));
+        self
+    }
+
+    /// Renders the full page around the provided content.
+    pub fn render(&self, content: Markup) -> Markup {
+        crate::partials::page(content, self)
+    }
+
+    pub fn into_owned(self) -> PageMetaData<'static> {
+        PageMetaData {
+            lang: Cow::Owned(self.lang.into_owned()),
+            charset: Cow::Owned(self.charset.into_owned()),
+            scripts: Cow::Owned(self.scripts.iter().cloned().map(Script::into_owned).collect()),
+            css: Cow::Owned(self.css.iter().cloned().map(Css::into_owned).collect()),
+            title: Cow::Owned(self.title.into_owned()),
+            page_title: self.page_title.map(|t| Cow::Owned(t.into_owned())),
+            title_template: Cow::Owned(self.title_template.into_owned()),
+            description: Cow::Owned(self.description.into_owned()),
+            copyright: self.copyright.into_owned(),
+            favicons: Cow::Owned(self.favicons.iter().cloned().map(Favicon::into_owned).collect()),
+            menu: self.menu.map(Menu::into_owned),
+            contact: self.contact.map(Contact::into_owned),
+            logo: self.logo.map(Logo::into_owned),
+            theme_color: Cow::Owned(self.theme_color.into_owned()),
+            canonical: self.canonical.map(|c| Cow::Owned(c.into_owned())),
+            robots: self.robots,
+            social: self.social.map(Social::into_owned),
+            alternates: Cow::Owned(self.alternates.iter().cloned().map(Alternate::into_owned).collect()),
+            structured_data: Cow::Owned(self.structured_data.iter().map(|s| Cow::Owned(s.clone().into_owned())).collect()),
+        }
+    }
+}
+
+/// The `robots` meta tag. Both default to allowed, in which case the tag is left out entirely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Robots {
+    /// Whether the page should be indexed.
+    pub index: bool,
+    /// Whether links on the page should be followed.
+    pub follow: bool,
+}
+impl Default for Robots {
+    fn default() -> Self {
+        Self { index: true, follow: true }
+    }
+}
+impl Render for Robots {
+    fn render(&self) -> Markup {
+        let index = if self.index { "index" } else { "noindex" };
+        let follow = if self.follow { "follow" } else { "nofollow" };
+        html! {
+            @if *self != Robots::default() {
+                meta name="robots" content={ (index) ", " (follow) };
+            }
+        }
+    }
+}
+
+/// Open Graph and Twitter Card metadata.
+///
+/// The title, description and URL are shared with the rest of the page, so they're filled in from
+/// [`PageMetaData`] when rendering through [`Social::render_for`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Social<'a> {
+    /// The `og:type` along with any properties specific to it.
+    pub kind: OgKind,
+    /// The preview image.
+    pub image: Option<OgImage<'a>>,
+    /// The name of the site as a whole.
+    pub site_name: Option<Cow<'a, str>>,
+    /// How large the Twitter preview should be.
+    pub twitter_card: TwitterCard,
+    /// The `@handle` of the author.
+    pub twitter_creator: Option<Cow<'a, str>>,
+}
+impl<'a> Social<'a> {
+    pub fn kind(mut self, kind: OgKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn image(mut self, image: OgImage<'a>) -> Self {
+        self.image = Some(image);
+        self
+    }
+
+    pub fn site_name(mut self, site_name: impl Into<Cow<'a, str>>) -> Self {
+        self.site_name = Some(site_name.into());
+        self
+    }
+
+    pub fn twitter_card(mut self, twitter_card: TwitterCard) -> Self {
+        self.twitter_card = twitter_card;
+        self
+    }
+
+    pub fn twitter_creator(mut self, twitter_creator: impl Into<Cow<'a, str>>) -> Self {
+        self.twitter_creator = Some(twitter_creator.into());
+        self
+    }
+
+    /// Renders the tags, pulling the shared parts out of `meta`.
+    pub fn render_for(&self, meta: &PageMetaData) -> Markup {
+        html! {
+            meta property="og:type" content=(self.kind.as_str());
+            meta property="og:title" content=(meta.full_title());
+            meta property="og:description" content=(meta.description);
+            @if let Some(url) = meta.canonical.as_ref() {
+                meta property="og:url" content=(url);
+            }
+            @if let Some(site_name) = self.site_name.as_ref() {
+                meta property="og:site_name" content=(site_name);
+            }
+            @if let Some(image) = self.image.as_ref() {
+                (image)
+            }
+            @if let OgKind::Article { published, modified } = &self.kind {
+                @if let Some(published) = published {
+                    meta property="article:published_time" content=(published.to_rfc3339());
+                }
+                @if let Some(modified) = modified {
+                    meta property="article:modified_time" content=(modified.to_rfc3339());
+                }
+            }
+            meta name="twitter:card" content=(self.twitter_card.as_str());
+            @if let Some(creator) = self.twitter_creator.as_ref() {
+                meta name="twitter:creator" content=(creator);
+            }
+        }
+    }
+
+    pub fn into_owned(self) -> Social<'static> {
+        Social {
+            kind: self.kind,
+            image: self.image.map(OgImage::into_owned),
+            site_name: self.site_name.map(|n| Cow::Owned(n.into_owned())),
+            twitter_card: self.twitter_card,
+            twitter_creator: self.twitter_creator.map(|c| Cow::Owned(c.into_owned())),
         }
     }
 }
 
+/// The `og:type` of a page.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub enum OgKind {
+    #[default]
+    Website,
+    Profile,
+    Article {
+        published: Option<DateTime<Utc>>,
+        modified: Option<DateTime<Utc>>,
+    },
+}
+impl OgKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            OgKind::Website => "website",
+            OgKind::Profile => "profile",
+            OgKind::Article { .. } => "article",
+        }
+    }
+}
+
+/// An `og:image`. The URL needs to be absolute.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OgImage<'a> {
+    pub url: Cow<'a, str>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Description of the image, also used for `twitter:image:alt`.
+    pub alt: Option<Cow<'a, str>>,
+}
+impl<'a> Render for OgImage<'a> {
+    fn render(&self) -> Markup {
+        html! {
+            meta property="og:image" content=(self.url);
+            @if let Some(width) = self.width {
+                meta property="og:image:width" content=(width);
+            }
+            @if let Some(height) = self.height {
+                meta property="og:image:height" content=(height);
+            }
+            @if let Some(alt) = self.alt.as_ref() {
+                meta property="og:image:alt" content=(alt);
+                meta name="twitter:image:alt" content=(alt);
+            }
+        }
+    }
+}
+impl<'a> OgImage<'a> {
+    pub fn into_owned(self) -> OgImage<'static> {
+        OgImage {
+            url: Cow::Owned(self.url.into_owned()),
+            width: self.width,
+            height: self.height,
+            alt: self.alt.map(|alt| Cow::Owned(alt.into_owned())),
+        }
+    }
+}
+
+/// The `twitter:card` style.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TwitterCard {
+    #[default]
+    Summary,
+    SummaryLargeImage,
+}
+impl TwitterCard {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TwitterCard::Summary => "summary",
+            TwitterCard::SummaryLargeImage => "summary_large_image",
+        }
+    }
+}
+
+/// A `<link rel="alternate">`, pointing at another representation of the page.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Alternate<'a> {
+    pub href: Cow<'a, str>,
+    /// The media type of the alternate, like `application/atom+xml`.
+    pub media_type: Cow<'a, str>,
+    pub title: Option<Cow<'a, str>>,
+}
+impl<'a> Render for Alternate<'a> {
+    fn render(&self) -> Markup {
+        html! {
+            link rel="alternate" type=(self.media_type) href=(self.href) title=[self.title.as_ref()];
+        }
+    }
+}
+impl<'a> Alternate<'a> {
+    pub fn into_owned(self) -> Alternate<'static> {
+        Alternate {
+            href: Cow::Owned(self.href.into_owned()),
+            media_type: Cow::Owned(self.media_type.into_owned()),
+            title: self.title.map(|t| Cow::Owned(t.into_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Favicon<'a> {
-    pub link: &'a str,
-    pub media_type: Option<&'a str>,
-    pub sizes: Option<&'a str>
+    pub link: Cow<'a, str>,
+    pub media_type: Option<Cow<'a, str>>,
+    pub sizes: Option<Cow<'a, str>>,
 }
 impl<'a> Render for Favicon<'a> {
     fn render(&self) -> Markup {
-        match (self.media_type, self.sizes) {
+        match (self.media_type.as_deref(), self.sizes.as_deref()) {
             (Some(mt), Some(sz)) => {
                 html! { link rel="icon" href={(self.link)} type={(mt)} sizes={(sz)} {} }
             },
@@ -104,18 +497,37 @@ impl<'a> Render for Favicon<'a> {
         }
     }
 }
+impl<'a> Favicon<'a> {
+    /// A favicon with no media type or sizes.
+    pub fn new(link: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            link: link.into(),
+            media_type: None,
+            sizes: None,
+        }
+    }
+
+    pub fn into_owned(self) -> Favicon<'static> {
+        Favicon {
+            link: Cow::Owned(self.link.into_owned()),
+            media_type: self.media_type.map(|mt| Cow::Owned(mt.into_owned())),
+            sizes: self.sizes.map(|sz| Cow::Owned(sz.into_owned())),
+        }
+    }
+}
 
 /// Information regarding the logo. (This is very simple).
+#[derive(Debug, Clone, Deserialize)]
 pub struct Logo<'a> {
     /// The url to the actual image.
-    pub src: &'a str,
-    pub href: Option<&'a str>,
+    pub src: Cow<'a, str>,
+    pub href: Option<Cow<'a, str>>,
 }
 impl<'a> Render for Logo<'a> {
     fn render(&self) -> Markup {
         html! {
             div.logo {
-                @match self.href {
+                @match self.href.as_deref() {
                     Some(link) => a.logo-wrapper href=(link) {
                         img.logo-img src=(self.src);
                     },
@@ -125,13 +537,22 @@ impl<'a> Render for Logo<'a> {
         }
     }
 }
+impl<'a> Logo<'a> {
+    pub fn into_owned(self) -> Logo<'static> {
+        Logo {
+            src: Cow::Owned(self.src.into_owned()),
+            href: self.href.map(|href| Cow::Owned(href.into_owned())),
+        }
+    }
+}
 
 /// Information regarding the `<script>` tags to include.
+#[derive(Debug, Clone, Deserialize)]
 pub enum Script<'a> {
     /// Represents a script externally linked (in the `public/js` directory).
-    External(&'a str),
+    External(Cow<'a, str>),
     /// Represents a script copy and pasted into the website.
-    Embedded(&'a str),
+    Embedded(Cow<'a, str>),
 }
 impl<'a> Render for Script<'a> {
     fn render(&self) -> Markup {
@@ -142,8 +563,8 @@ impl<'a> Render for Script<'a> {
     }
 }
 impl<'a> Script<'a> {
-    /// A script for hooking in the WASM loading script
-    pub fn wasm_bindgen_loader(js_path: &str, wasm_path: &str, name: &str) -> (String, String) {
+    /// The scripts for hooking in a WASM module: the `wasm-bindgen` glue and the loader calling it.
+    pub fn wasm_bindgen_loader(js_path: &str, wasm_path: &str, name: &str) -> (Script<'static>, Script<'static>) {
         let glue = format!("/{js_path}/{name}.js");
         let load = format!(
             "\
@@ -165,16 +586,24 @@ impl<'a> Script<'a> {
              );\
             ",
         );
-        (glue, load)
+        (Script::External(Cow::Owned(glue)), Script::Embedded(Cow::Owned(load)))
+    }
+
+    pub fn into_owned(self) -> Script<'static> {
+        match self {
+            Script::External(src) => Script::External(Cow::Owned(src.into_owned())),
+            Script::Embedded(src) => Script::Embedded(Cow::Owned(src.into_owned())),
+        }
     }
 }
 
 /// Information regarding the `<style>` tags to include.
+#[derive(Debug, Clone, Deserialize)]
 pub enum Css<'a> {
     /// Above the fold CSS. This get linked in from the resources directory, `/public`.
-    Critical { src: &'a str },
+    Critical { src: Cow<'a, str> },
     /// Under the fold CSS. This get linked in from the resources directory, `/public`.
-    NonCritical { src: &'a str },
+    NonCritical { src: Cow<'a, str> },
 }
 impl<'a> Render for Css<'a> {
     fn render(&self) -> Markup {
@@ -183,20 +612,37 @@ impl<'a> Render for Css<'a> {
                 (src)
             }{} },
             Css::Critical { src } => {
-                let style = fs::read_to_string(src)
+                let style = fs::read_to_string(src.as_ref())
                     .unwrap_or_else(|e| panic!("{:?} is missing ({:?})", src, e));
                 html! { style { (PreEscaped(style)) } }
             }
         }
     }
 }
+impl<'a> Css<'a> {
+    pub fn critical(src: impl Into<Cow<'a, str>>) -> Self {
+        Css::Critical { src: src.into() }
+    }
+
+    pub fn non_critical(src: impl Into<Cow<'a, str>>) -> Self {
+        Css::NonCritical { src: src.into() }
+    }
+
+    pub fn into_owned(self) -> Css<'static> {
+        match self {
+            Css::Critical { src } => Css::Critical { src: Cow::Owned(src.into_owned()) },
+            Css::NonCritical { src } => Css::NonCritical { src: Cow::Owned(src.into_owned()) },
+        }
+    }
+}
 
 /// A email address.
+#[derive(Debug, Clone, Deserialize)]
 pub struct Email<'a> {
     /// The username portion of the email.
-    pub user: &'a str,
+    pub user: Cow<'a, str>,
     /// The domain portion of the email.
-    pub domain: &'a str,
+    pub domain: Cow<'a, str>,
 }
 impl<'a> Render for Email<'a> {
     fn render(&self) -> Markup {
@@ -205,8 +651,17 @@ impl<'a> Render for Email<'a> {
         }
     }
 }
+impl<'a> Email<'a> {
+    pub fn into_owned(self) -> Email<'static> {
+        Email {
+            user: Cow::Owned(self.user.into_owned()),
+            domain: Cow::Owned(self.domain.into_owned()),
+        }
+    }
+}
 
 /// A phone number. This is an enum for globalization.
+#[derive(Debug, Clone, Deserialize)]
 pub enum PhoneNumber<'a> {
     /// A phone number in the US.
     US {
@@ -217,7 +672,7 @@ pub enum PhoneNumber<'a> {
         /// The line number (the four numbers after the area code).
         line_number: u16,
         /// A link to the icon for this number. (Work, Mobile, etc.)
-        icon: &'a str,
+        icon: Cow<'a, str>,
     },
 }
 impl<'a> Render for PhoneNumber<'a> {
@@ -234,58 +689,106 @@ impl<'a> Render for PhoneNumber<'a> {
         }
     }
 }
+impl<'a> PhoneNumber<'a> {
+    pub fn into_owned(self) -> PhoneNumber<'static> {
+        match self {
+            PhoneNumber::US { area_code, prefix, line_number, icon } => PhoneNumber::US {
+                area_code,
+                prefix,
+                line_number,
+                icon: Cow::Owned(icon.into_owned()),
+            },
+        }
+    }
+}
 
 /// A contact card. Comprised of emails and phone numbers.
+#[derive(Debug, Clone, Deserialize)]
 pub struct Contact<'a> {
     /// Emails for this contact.
-    pub email: &'a [Email<'a>],
+    pub email: Cow<'a, [Email<'a>]>,
     /// Phone numbers for this contact.
-    pub phone: &'a [PhoneNumber<'a>],
+    pub phone: Cow<'a, [PhoneNumber<'a>]>,
 }
 impl<'a> Render for Contact<'a> {
     fn render(&self) -> Markup {
         html! {
-            @for email in self.email {
+            @for email in self.email.iter() {
                 p.contact-email { "Email: " (email) }
             }
-            @for phone in self.phone {
+            @for phone in self.phone.iter() {
                 p.contact-phone-number { "Phone: " (phone) }
             }
         }
     }
 }
+impl<'a> Contact<'a> {
+    pub fn into_owned(self) -> Contact<'static> {
+        Contact {
+            email: Cow::Owned(self.email.iter().cloned().map(Email::into_owned).collect()),
+            phone: Cow::Owned(self.phone.iter().cloned().map(PhoneNumber::into_owned).collect()),
+        }
+    }
+}
 
 /// A struct representing names.
+#[derive(Debug, Clone, Deserialize)]
 pub struct Name<'a> {
     /// First name.
-    pub first: &'a str,
+    pub first: Cow<'a, str>,
     /// Middle name.
-    pub middle: Option<&'a str>,
+    pub middle: Option<Cow<'a, str>>,
     /// Last name.
-    pub last: &'a str,
+    pub last: Cow<'a, str>,
     /// A list of nicknames.
-    pub nicknames: &'a [&'a str],
+    #[serde(default)]
+    pub nicknames: Cow<'a, [Cow<'a, str>]>,
 }
 impl<'a> Render for Name<'a> {
     fn render(&self) -> Markup {
         html! {
-            (self.first) " " @if let Some(middle) = self.middle {
+            (self.first) " " @if let Some(middle) = self.middle.as_deref() {
                 @if let Some(initial) = middle.chars().next() {
-                    (initial) ". " 
+                    (initial) ". "
                 }
             } (self.last)
         }
     }
 }
+impl<'a> Name<'a> {
+    pub fn into_owned(self) -> Name<'static> {
+        Name {
+            first: Cow::Owned(self.first.into_owned()),
+            middle: self.middle.map(|middle| Cow::Owned(middle.into_owned())),
+            last: Cow::Owned(self.last.into_owned()),
+            nicknames: Cow::Owned(self.nicknames.iter().map(|n| Cow::Owned(n.clone().into_owned())).collect()),
+        }
+    }
+}
 
 /// Copyright data.
+#[derive(Debug, Clone, Deserialize)]
 pub struct Copyright<'a> {
     /// Person copyrighting the website.
-    pub name: &'a Name<'a>,
+    pub name: Name<'a>,
     /// The copyright icon to be used.
-    pub icon: &'a str,
+    pub icon: Cow<'a, str>,
     /// What rights to grant/refuse.
-    pub rights_clause: &'a str,
+    pub rights_clause: Cow<'a, str>,
+}
+impl<'a> Default for Copyright<'a> {
+    fn default() -> Self {
+        Self {
+            name: Name {
+                first: Cow::Borrowed("Benjamin"),
+                middle: Some(Cow::Borrowed("Peiyan")),
+                last: Cow::Borrowed("Xu"),
+                nicknames: Cow::Borrowed(&[]),
+            },
+            icon: Cow::Borrowed("©"),
+            rights_clause: Cow::Borrowed("All rights reserved"),
+        }
+    }
 }
 impl<'a> Render for Copyright<'a> {
     fn render(&self) -> Markup {
@@ -297,15 +800,25 @@ impl<'a> Render for Copyright<'a> {
         }
     }
 }
+impl<'a> Copyright<'a> {
+    pub fn into_owned(self) -> Copyright<'static> {
+        Copyright {
+            name: self.name.into_owned(),
+            icon: Cow::Owned(self.icon.into_owned()),
+            rights_clause: Cow::Owned(self.rights_clause.into_owned()),
+        }
+    }
+}
 
 /// An entry in the menu.
+#[derive(Debug, Clone, Deserialize)]
 pub struct MenuItem<'a> {
     /// Text to display.
-    pub text: &'a str,
+    pub text: Cow<'a, str>,
     /// Where the entry links to, if it links to one.
-    pub link: Option<&'a str>,
+    pub link: Option<Cow<'a, str>>,
     /// A child menu, if one exists.
-    pub children: Option<&'a Menu<'a>>,
+    pub children: Option<Menu<'a>>,
 }
 impl<'a> MenuItem<'a> {
     /// Render a link to [`Markup`] if present.
@@ -318,13 +831,21 @@ impl<'a> MenuItem<'a> {
             }
         }
     }
+
+    pub fn into_owned(self) -> MenuItem<'static> {
+        MenuItem {
+            text: Cow::Owned(self.text.into_owned()),
+            link: self.link.map(|link| Cow::Owned(link.into_owned())),
+            children: self.children.map(Menu::into_owned),
+        }
+    }
 }
 impl<'a> Render for MenuItem<'a> {
     fn render(&self) -> Markup {
         html! {
             li {
-                (MenuItem::render_possible_link(self.link, self.text))
-                @if let Some(children) = self.children {
+                (MenuItem::render_possible_link(self.link.as_deref(), self.text.as_ref()))
+                @if let Some(children) = self.children.as_ref() {
                     (children)
                 }
             }
@@ -333,7 +854,8 @@ impl<'a> Render for MenuItem<'a> {
 }
 
 /// A newtype for a list of [`MenuItem`](crate::data::MenuItem)s.
-pub struct Menu<'a>(pub &'a [MenuItem<'a>]);
+#[derive(Debug, Clone, Deserialize)]
+pub struct Menu<'a>(pub Cow<'a, [MenuItem<'a>]>);
 impl<'a> Render for Menu<'a> {
     fn render(&self) -> Markup {
         html! {
@@ -351,4 +873,8 @@ impl<'a> Menu<'a> {
     pub fn into_string(self) -> String {
         self.render().into_string()
     }
+
+    pub fn into_owned(self) -> Menu<'static> {
+        Menu(Cow::Owned(self.0.iter().cloned().map(MenuItem::into_owned).collect()))
+    }
 }
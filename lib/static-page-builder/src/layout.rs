@@ -0,0 +1,66 @@
+//! The chrome shared by every page of the site.
+
+use serde::Deserialize;
+use std::borrow::Cow;
+
+use crate::data::{Css, Favicon, Logo, Menu, MenuItem, PageMetaData};
+
+/// Everything that stays the same from page to page: base styles, favicons, the menu and logo.
+///
+/// Pages start from [`SiteLayout::page`] and add only their differences using the setters on
+/// [`PageMetaData`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct SiteLayout<'a> {
+    /// CSS included on every page, ahead of any page specific CSS.
+    pub css: Cow<'a, [Css<'a>]>,
+    /// The favicons.
+    pub favicons: Cow<'a, [Favicon<'a>]>,
+    /// The menu in the header.
+    pub menu: Option<Menu<'a>>,
+    /// The logo in the header.
+    pub logo: Option<Logo<'a>>,
+}
+impl Default for SiteLayout<'static> {
+    fn default() -> Self {
+        Self {
+            css: Cow::Borrowed(&[
+                Css::Critical { src: Cow::Borrowed("public/css/reset.css") },
+                Css::Critical { src: Cow::Borrowed("public/css/typography.css") },
+                Css::Critical { src: Cow::Borrowed("public/css/main.css") },
+            ]),
+            favicons: Cow::Borrowed(&[Favicon {
+                link: Cow::Borrowed("/favicon.svg"),
+                media_type: None,
+                sizes: None,
+            }]),
+            menu: Some(Menu(Cow::Borrowed(&[
+                MenuItem {
+                    text: Cow::Borrowed("Blog"),
+                    link: Some(Cow::Borrowed("/blog")),
+                    children: None,
+                },
+                MenuItem {
+                    text: Cow::Borrowed("Projects"),
+                    link: Some(Cow::Borrowed("/projects")),
+                    children: None,
+                },
+            ]))),
+            logo: Some(Logo {
+                src: Cow::Borrowed("/public/svg/branding.svg"),
+                href: Some(Cow::Borrowed("/")),
+            }),
+        }
+    }
+}
+impl<'a> SiteLayout<'a> {
+    /// Starts the metadata of a page using this layout.
+    pub fn page(&self) -> PageMetaData<'a> {
+        PageMetaData {
+            css: self.css.clone(),
+            favicons: self.favicons.clone(),
+            menu: self.menu.clone(),
+            logo: self.logo.clone(),
+            ..PageMetaData::default()
+        }
+    }
+}
@@ -1,6 +1,10 @@
-#![feature(proc_macro_hygiene, decl_macro)]
+#![feature(proc_macro_hygiene)]
 
 //! Provides a few utility functions to generate parts of static webpages at compile time.
 
 pub mod data;
+pub mod layout;
 pub mod partials;
+pub mod schema;
+#[cfg(test)]
+mod tests;
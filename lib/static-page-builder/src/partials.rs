@@ -1,5 +1,5 @@
 //! A series of components used across the site.
-use crate::data::PageMetaData;
+use crate::{data::PageMetaData, schema::render_json_ld};
 use maud::{html, Markup, DOCTYPE};
 
 /// The `<head>` portion of the webpage.
@@ -7,17 +7,30 @@ pub fn head(meta: &PageMetaData) -> Markup {
     html! {
         head {
             meta charset=(meta.charset);
-            title { (meta.title) }
+            title { (meta.full_title()) }
             meta name="description" content=(meta.description);
+            (meta.robots)
+            @if let Some(canonical) = meta.canonical.as_ref() {
+                link rel="canonical" href=(canonical);
+            }
+            @if let Some(social) = meta.social.as_ref() {
+                (social.render_for(meta))
+            }
+            @for alternate in meta.alternates.iter() {
+                (alternate)
+            }
+            @for json_ld in meta.structured_data.iter() {
+                (render_json_ld(json_ld))
+            }
             meta name="viewport" content="width=device-width, initial-scale=1";
             meta name="theme-color" content=(meta.theme_color);
-            @for f in meta.favicons {
+            @for f in meta.favicons.iter() {
                 (f)
             }
-            @for css in meta.css {
+            @for css in meta.css.iter() {
                 (css)
             }
-            @for js in meta.scripts {
+            @for js in meta.scripts.iter() {
                 (js)
             }
         }
@@ -28,10 +41,10 @@ pub fn head(meta: &PageMetaData) -> Markup {
 pub fn header(meta: &PageMetaData) -> Markup {
     html! {
         header.site-header {
-            @if let Some(logo) = meta.logo {
+            @if let Some(logo) = meta.logo.as_ref() {
                 (logo)
             }
-            @if let Some(menu) = meta.menu {
+            @if let Some(menu) = meta.menu.as_ref() {
                 (menu)
             }
         }
@@ -42,7 +55,7 @@ pub fn header(meta: &PageMetaData) -> Markup {
 pub fn footer(meta: &PageMetaData) -> Markup {
     html! {
         footer.site-footer {
-            @if let Some(contact) = meta.contact {
+            @if let Some(contact) = meta.contact.as_ref() {
                 (contact)
             }
             (meta.copyright)
@@ -70,8 +83,8 @@ pub fn page(m: Markup, meta: &PageMetaData) -> Markup {
     html! {
         (DOCTYPE)
         html lang=(meta.lang) {
-            (head(&meta))
-            (body(m, &meta))
+            (head(meta))
+            (body(m, meta))
         }
     }
 }
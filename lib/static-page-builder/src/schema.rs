@@ -0,0 +1,228 @@
+//! Typed [schema.org](https://schema.org) structured data, rendered as JSON-LD.
+//!
+//! Build one of the types here and hand it to
+//! [`PageMetaData::structured_data`](crate::data::PageMetaData::structured_data), which emits it in
+//! the `<head>`.
+
+use chrono::{DateTime, Utc};
+use maud::{html, Markup, PreEscaped};
+use serde::{Serialize, Serializer};
+use std::borrow::Cow;
+
+use crate::data::{Contact, LogoLink, Name};
+
+/// Any of the supported schema.org types.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "@type")]
+pub enum Schema<'a> {
+    Person(Person<'a>),
+    WebSite(WebSite<'a>),
+    BlogPosting(BlogPosting<'a>),
+    BreadcrumbList(BreadcrumbList<'a>),
+    CreativeWork(CreativeWork<'a>),
+}
+impl<'a> Schema<'a> {
+    /// Serializes to JSON-LD, escaped so that it can sit inside a `<script>` tag.
+    pub fn to_json_ld(&self) -> String {
+        #[derive(Serialize)]
+        struct WithContext<'b, 'a> {
+            #[serde(rename = "@context")]
+            context: &'static str,
+            #[serde(flatten)]
+            schema: &'b Schema<'a>,
+        }
+
+        let json = serde_json::to_string(&WithContext { context: "https://schema.org", schema: self })
+            .expect("schema serializes");
+        // `<` only ever shows up in strings, where the escape means the same thing.
+        json.replace('<', "\\u003c")
+    }
+}
+
+/// Renders a JSON-LD string produced by [`Schema::to_json_ld`].
+pub fn render_json_ld(json_ld: &str) -> Markup {
+    html! {
+        script type="application/ld+json" { (PreEscaped(json_ld)) }
+    }
+}
+
+macro_rules! into_schema {
+    ($($ty:ident),*) => {
+        $(
+            impl<'a> From<$ty<'a>> for Schema<'a> {
+                fn from(value: $ty<'a>) -> Self {
+                    Schema::$ty(value)
+                }
+            }
+        )*
+    };
+}
+into_schema!(Person, WebSite, BlogPosting, BreadcrumbList, CreativeWork);
+
+/// Serializes a [`Person`] nested in another type, which needs its own `@type`.
+fn nested_person<S: Serializer>(person: &Option<Box<Person>>, serializer: S) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Nested<'b, 'a> {
+        #[serde(rename = "@type")]
+        kind: &'static str,
+        #[serde(flatten)]
+        person: &'b Person<'a>,
+    }
+
+    person.as_deref().map(|person| Nested { kind: "Person", person }).serialize(serializer)
+}
+
+/// A `Person`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Person<'a> {
+    pub name: Cow<'a, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub given_name: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_name: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub family_name: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternate_name: Vec<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<Cow<'a, str>>,
+    /// Profiles elsewhere on the web.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub same_as: Vec<Cow<'a, str>>,
+}
+impl<'a> From<&Name<'a>> for Person<'a> {
+    fn from(name: &Name<'a>) -> Self {
+        let full_name = match name.middle.as_deref() {
+            Some(middle) => format!("{} {} {}", name.first, middle, name.last),
+            None => format!("{} {}", name.first, name.last),
+        };
+        Self {
+            name: full_name.into(),
+            given_name: Some(name.first.clone()),
+            additional_name: name.middle.clone(),
+            family_name: Some(name.last.clone()),
+            alternate_name: name.nicknames.to_vec(),
+            ..Self::default()
+        }
+    }
+}
+impl<'a> Person<'a> {
+    pub fn url(mut self, url: impl Into<Cow<'a, str>>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    pub fn image(mut self, image: impl Into<Cow<'a, str>>) -> Self {
+        self.image = Some(image.into());
+        self
+    }
+
+    /// Pulls the email and other profiles out of a set of links.
+    ///
+    /// `mailto:` links become the email, any other absolute links are treated as profiles.
+    /// Relative links are skipped since they're pages on this site.
+    pub fn links<'b>(mut self, links: impl IntoIterator<Item = &'b LogoLink<'a>>) -> Self where 'a: 'b {
+        for link in links {
+            if let Some(email) = link.url.strip_prefix("mailto:") {
+                self.email.get_or_insert_with(|| Cow::Owned(email.to_owned()));
+            } else if link.url.starts_with("http://") || link.url.starts_with("https://") {
+                self.same_as.push(link.url.clone());
+            }
+        }
+        self
+    }
+
+    /// Pulls the email out of a contact card, if it has one.
+    pub fn contact(mut self, contact: &Contact<'a>) -> Self {
+        if let Some(email) = contact.email.first() {
+            self.email = Some(format!("{}@{}", email.user, email.domain).into());
+        }
+        self
+    }
+}
+
+/// A `WebSite`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSite<'a> {
+    pub name: Cow<'a, str>,
+    pub url: Cow<'a, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "nested_person")]
+    pub author: Option<Box<Person<'a>>>,
+}
+
+/// A `BlogPosting`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlogPosting<'a> {
+    pub headline: Cow<'a, str>,
+    pub url: Cow<'a, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_published: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_modified: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "nested_person")]
+    pub author: Option<Box<Person<'a>>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<Cow<'a, str>>,
+}
+
+/// A `BreadcrumbList`. Build it with [`BreadcrumbList::crumb`].
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreadcrumbList<'a> {
+    pub item_list_element: Vec<ListItem<'a>>,
+}
+impl<'a> BreadcrumbList<'a> {
+    /// Adds the next crumb, numbering it automatically.
+    pub fn crumb(mut self, name: impl Into<Cow<'a, str>>, item: impl Into<Cow<'a, str>>) -> Self {
+        let position = self.item_list_element.len() + 1;
+        self.item_list_element.push(ListItem {
+            kind: "ListItem",
+            position,
+            name: name.into(),
+            item: item.into(),
+        });
+        self
+    }
+}
+
+/// A `ListItem` within a [`BreadcrumbList`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ListItem<'a> {
+    #[serde(rename = "@type")]
+    kind: &'static str,
+    /// 1-indexed position within the list.
+    pub position: usize,
+    pub name: Cow<'a, str>,
+    /// The URL of the crumb.
+    pub item: Cow<'a, str>,
+}
+
+/// A `CreativeWork`, used for projects.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreativeWork<'a> {
+    pub name: Cow<'a, str>,
+    pub url: Cow<'a, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "nested_person")]
+    pub author: Option<Box<Person<'a>>>,
+    /// Other places the work lives, like its repository.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub same_as: Vec<Cow<'a, str>>,
+}
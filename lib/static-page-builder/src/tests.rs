@@ -0,0 +1,161 @@
+use std::borrow::Cow;
+
+use maud::html;
+
+use crate::{
+    data::{Alternate, Copyright, Favicon, OgImage, OgKind, PageMetaData, Robots, Script, Social, TwitterCard},
+    layout::SiteLayout,
+    schema::{BlogPosting, BreadcrumbList, Person, Schema},
+};
+
+#[test]
+fn setters_only_touch_their_field() {
+    let meta = PageMetaData::default()
+        .page_title("Post")
+        .description(String::from("Owned."))
+        .canonical("https://example.com/post")
+        .favicon(Favicon::new("/one.svg"))
+        .favicon(Favicon::new("/two.svg"));
+    let default = PageMetaData::default();
+    assert_eq!(meta.title, default.title);
+    assert_eq!(meta.full_title(), "Post | Benjamin Xu");
+    assert_eq!(meta.description, "Owned.");
+    assert_eq!(meta.favicons.iter().map(|f| f.link.as_ref()).collect::<Vec<_>>(), ["/one.svg", "/two.svg"]);
+
+    let meta = meta.title_template("{site}: {page}");
+    assert_eq!(meta.full_title(), "Benjamin Xu: Post");
+    assert_eq!(PageMetaData::default().full_title(), "Benjamin Xu");
+}
+
+#[test]
+fn into_owned_keeps_everything() {
+    let title = String::from("Borrowed");
+    let meta = PageMetaData::default().page_title(title.as_str()).script(Script::External("a.js".into()));
+    let owned: PageMetaData<'static> = meta.clone().into_owned();
+    drop(title);
+    assert_eq!(owned.full_title(), "Borrowed | Benjamin Xu");
+    assert!(matches!(&owned.scripts[..], [Script::External(src)] if src == "a.js"));
+    assert!(matches!(owned.page_title, Some(Cow::Owned(_))));
+}
+
+#[test]
+fn deserializes_with_defaults() {
+    let meta: PageMetaData = serde_json::from_str(r#"{"title": "Elsewhere", "robots": {"index": false}}"#).unwrap();
+    assert_eq!(meta.title, "Elsewhere");
+    assert_eq!(meta.lang, "en-US");
+    assert_eq!(meta.robots, Robots { index: false, follow: true });
+}
+
+#[test]
+fn pages_start_from_the_layout() {
+    let layout = SiteLayout { css: Cow::Borrowed(&[]), ..SiteLayout::default() };
+    let meta = layout.page().page_title("Hi");
+    assert_eq!(meta.favicons.len(), layout.favicons.len());
+    assert!(meta.menu.is_some());
+    assert!(meta.logo.is_some());
+}
+
+#[test]
+fn renders_the_head() {
+    let html = PageMetaData::default()
+        .page_title("Quotes \" & <tags>")
+        .canonical("https://example.com/")
+        .alternate(Alternate { href: "/feed.xml".into(), media_type: "application/atom+xml".into(), title: None })
+        .render(html! { p { "Hello" } })
+        .into_string();
+    assert!(html.starts_with("<!DOCTYPE html><html lang=\"en-US\">"), "{}", html);
+    assert!(html.contains("<title>Quotes &quot; &amp; &lt;tags&gt; | Benjamin Xu</title>"), "{}", html);
+    assert!(html.contains("<link rel=\"canonical\" href=\"https://example.com/\">"), "{}", html);
+    assert!(html.contains("<link rel=\"alternate\" type=\"application/atom+xml\" href=\"/feed.xml\">"), "{}", html);
+    assert!(html.contains("<main class=\"site-body\"><p>Hello</p></main>"), "{}", html);
+    // Robots only show up when they say something.
+    assert!(!html.contains("name=\"robots\""), "{}", html);
+    let hidden = PageMetaData::default().robots(Robots { index: false, follow: false }).render(html! {}).into_string();
+    assert!(hidden.contains("<meta name=\"robots\" content=\"noindex, nofollow\">"), "{}", hidden);
+}
+
+#[test]
+fn renders_social_metadata() {
+    let published = chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z").unwrap().with_timezone(&chrono::Utc);
+    let social = Social::default()
+        .kind(OgKind::Article { published: Some(published), modified: None })
+        .image(OgImage { url: "https://example.com/a.png".into(), width: Some(1200), height: None, alt: Some("A".into()) })
+        .twitter_card(TwitterCard::SummaryLargeImage)
+        .twitter_creator("@someone");
+    let html = PageMetaData::default()
+        .page_title("Post")
+        .canonical("https://example.com/post")
+        .social(social)
+        .render(html! {})
+        .into_string();
+    for tag in [
+        r#"<meta property="og:type" content="article">"#,
+        r#"<meta property="og:url" content="https://example.com/post">"#,
+        r#"<meta property="og:image" content="https://example.com/a.png">"#,
+        r#"<meta property="og:image:width" content="1200">"#,
+        r#"<meta property="article:published_time" content="2026-01-02T03:04:05+00:00">"#,
+        r#"<meta name="twitter:card" content="summary_large_image">"#,
+        r#"<meta name="twitter:creator" content="@someone">"#,
+    ] {
+        assert!(html.contains(tag), "{} in {}", tag, html);
+    }
+    assert!(!html.contains("og:image:height") && !html.contains("article:modified_time"), "{}", html);
+}
+
+#[test]
+fn json_ld_serializes_schema_types() {
+    let published = chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z").unwrap().with_timezone(&chrono::Utc);
+    let posting = BlogPosting {
+        headline: "Closing </script> tags".into(),
+        url: "https://example.com/blog/post/closing".into(),
+        description: None,
+        image: None,
+        date_published: Some(published),
+        date_modified: None,
+        author: Some(Box::new(Person::from(&Copyright::default().name).url("https://example.com/"))),
+        keywords: vec!["rust".into()],
+    };
+    let json = Schema::from(posting).to_json_ld();
+    // Nothing in there can end the `<script>` it sits in.
+    assert!(!json.contains('<'), "{}", json);
+    assert!(json.contains(r"Closing \u003c/script> tags"), "{}", json);
+    let value: serde_json::Value = serde_json::from_str(json.as_str()).unwrap();
+    assert_eq!(value, serde_json::json!({
+        "@context": "https://schema.org",
+        "@type": "BlogPosting",
+        "headline": "Closing </script> tags",
+        "url": "https://example.com/blog/post/closing",
+        "datePublished": "2026-01-02T03:04:05Z",
+        "author": {
+            "@type": "Person",
+            "name": "Benjamin Peiyan Xu",
+            "givenName": "Benjamin",
+            "additionalName": "Peiyan",
+            "familyName": "Xu",
+            "url": "https://example.com/",
+        },
+        "keywords": ["rust"],
+    }));
+
+    let crumbs = BreadcrumbList::default().crumb("Blog", "https://example.com/blog").crumb("Post", "https://example.com/p");
+    let value: serde_json::Value = serde_json::from_str(Schema::from(crumbs).to_json_ld().as_str()).unwrap();
+    assert_eq!(value, serde_json::json!({
+        "@context": "https://schema.org",
+        "@type": "BreadcrumbList",
+        "itemListElement": [
+            {"@type": "ListItem", "position": 1, "name": "Blog", "item": "https://example.com/blog"},
+            {"@type": "ListItem", "position": 2, "name": "Post", "item": "https://example.com/p"},
+        ],
+    }));
+}
+
+#[test]
+fn json_ld_ends_up_in_the_head() {
+    let crumbs = BreadcrumbList::default().crumb("</script><script>alert(1)</script>", "https://example.com/");
+    let html = PageMetaData::default().structured_data(crumbs).render(html! {}).into_string();
+    let start = html.find(r#"<script type="application/ld+json">"#).expect("JSON-LD is rendered");
+    let script = &html[start..];
+    let end = script.find("</script>").unwrap();
+    assert!(script[..end].ends_with(r#""\u003c/script>\u003cscript>alert(1)\u003c/script>","item":"https://example.com/"}]}"#), "{}", html);
+    assert!(html[..start].contains("<head>") && !html[..start].contains("</head>"), "{}", html);
+}
@@ -0,0 +1,10 @@
+#!/usr/bin/env bash
+
+set -euxo pipefail;
+
+
+root=`dirname $0`;
+api_server="bin/api-server";
+
+cd "$root";
+"$api_server" "$1";
@@ -0,0 +1,79 @@
+//! Errors, as the API reports them.
+//!
+//! Every error gets an [`ErrorBody`] as its body.
+
+use axum::{
+    body::Body,
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use blog_api::error::ErrorBody;
+
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    /// Shown to whoever made the request.
+    pub message: Option<String>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode) -> Self {
+        Self { status, message: None }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// Middleware answering panics with a JSON 500.
+pub async fn catch_panic(req: Request<Body>, next: Next<Body>) -> Response {
+    catch_panic::catch(req, next, || async { ApiError::new(StatusCode::INTERNAL_SERVER_ERROR).into_response() }).await
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            status: self.status.as_u16(),
+            error: self.status.canonical_reason().unwrap_or("Error").to_owned(),
+            message: self.message,
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<blog_store::Error> for ApiError {
+    fn from(e: blog_store::Error) -> Self {
+        use blog_store::Error;
+        match e {
+            Error::NotFound => ApiError::new(StatusCode::NOT_FOUND).with_message("There's no post with that slug."),
+            Error::SlugTaken(_) => ApiError::new(StatusCode::CONFLICT).with_message(e.to_string()),
+            Error::InvalidSlug(_) => ApiError::new(StatusCode::UNPROCESSABLE_ENTITY).with_message(e.to_string()),
+            Error::NewerSchema(_) | Error::Database(_) => {
+                trc::error!("{e}");
+                ApiError::new(StatusCode::INTERNAL_SERVER_ERROR)
+            },
+        }
+    }
+}
+
+macro_rules! from_rejection {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for ApiError {
+                fn from(rejection: $ty) -> Self {
+                    ApiError::new(rejection.status()).with_message(rejection.body_text())
+                }
+            }
+        )*
+    };
+}
+from_rejection!(JsonRejection, PathRejection, QueryRejection);
+
+pub async fn not_found() -> ApiError {
+    ApiError::new(StatusCode::NOT_FOUND)
+}
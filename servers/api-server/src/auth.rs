@@ -0,0 +1,229 @@
+//! Logging in to the editor: `/api/login` and `/api/session`. Passkeys are in [`crate::passkeys`].
+//!
+//! Passwords are hashed with argon2. Logging in hands out a random token in an `HttpOnly` cookie,
+//! and only a hash of it gets stored. Requests that change something also have to echo the
+//! session's CSRF token back in the `x-csrf-token` header, which other sites can't read or set.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io::BufRead,
+    sync::Mutex,
+};
+
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use axum::{
+    async_trait,
+    extract::{rejection::JsonRejection, FromRequestParts, State},
+    http::{header, request::Parts, HeaderMap, HeaderValue, Method, StatusCode},
+    Json,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use blog_api::auth::{LoginRequest, Session};
+use blog_store::{self as store, UserFields, UserRepository};
+use chrono::{DateTime, Duration, Utc};
+use cookie::{Cookie, SameSite};
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+use crate::{api_error::ApiError, blocking, Api};
+
+/// Name of the session cookie.
+const COOKIE: &str = "session";
+
+/// Header the CSRF token comes back in.
+const CSRF_HEADER: &str = "x-csrf-token";
+
+/// How long logging in lasts.
+fn session_lifetime() -> Duration {
+    Duration::days(30)
+}
+
+/// Most tries at one username's password per [`attempt_window`], whoever's trying.
+const MAX_ATTEMPTS: usize = 10;
+
+fn attempt_window() -> Duration {
+    Duration::minutes(1)
+}
+
+/// What passwords get checked against when there's no such user, hashed the way
+/// [`hash_password`] does it so that checking takes just as long.
+pub const DUMMY_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$Gelx+yBhZXQMGXB6L7Z8Dw$7HN0FcjGCx6wRXfcEbDAnFN4eDiSw5AW7i4ZMlLFSQY";
+
+/// Hashes a password for storing.
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("default argon2 parameters hash anything")
+        .to_string()
+}
+
+fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash).is_ok_and(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+}
+
+/// 32 random bytes, fit for a URL or a cookie.
+pub fn random_token() -> String {
+    let mut bytes = [0; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// What a session is stored under, given the token in its cookie.
+fn session_id(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+fn session_cookie(token: &str, max_age: Duration) -> HeaderValue {
+    let cookie = Cookie::build((COOKIE, token))
+        .path("/")
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Strict)
+        .max_age(cookie::time::Duration::seconds(max_age.num_seconds()))
+        .build();
+    HeaderValue::from_str(cookie.to_string().as_str()).expect("tokens are url safe")
+}
+
+fn api_session(session: &store::Session, user: &store::User) -> Session {
+    Session {
+        username: user.username.clone(),
+        expires_at: session.expires_at,
+        csrf_token: session.csrf_token.clone(),
+    }
+}
+
+fn unauthorized() -> ApiError {
+    ApiError::new(StatusCode::UNAUTHORIZED).with_message("You need to log in first.")
+}
+
+/// Whoever's logged in. Rejects requests without a session, and requests that change something
+/// without the session's CSRF token.
+///
+/// Take an `Option<Authenticated>` to let readers in too.
+#[derive(Debug)]
+pub struct Authenticated {
+    pub user: store::User,
+    pub session: store::Session,
+}
+
+#[async_trait]
+impl FromRequestParts<&'static Api> for Authenticated {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, api: &&'static Api) -> Result<Self, ApiError> {
+        let token = parts.headers.get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(Cookie::split_parse)
+            .filter_map(Result::ok)
+            .find(|cookie| cookie.name() == COOKIE)
+            .ok_or_else(unauthorized)?;
+        let (id, api) = (session_id(token.value()), *api);
+        let (session, user) = blocking(move || Ok(api.users.session(id.as_str(), Utc::now())?)).await?
+            .ok_or_else(unauthorized)?;
+
+        let safe = [Method::GET, Method::HEAD, Method::OPTIONS].contains(&parts.method);
+        let csrf = parts.headers.get(CSRF_HEADER).and_then(|value| value.to_str().ok());
+        if !safe && csrf != Some(session.csrf_token.as_str()) {
+            return Err(ApiError::new(StatusCode::FORBIDDEN).with_message("The request is missing its CSRF token."));
+        }
+        Ok(Self { user, session })
+    }
+}
+
+/// Recent tries at logging in with a password, by the username they were for.
+///
+/// Counting by username rather than by address means guessing from many addresses at once doesn't
+/// get around it, while someone hammering one name doesn't lock everyone else out. Passkeys still
+/// work for a name that's full.
+#[derive(Debug, Default)]
+pub struct LoginAttempts(Mutex<HashMap<String, VecDeque<DateTime<Utc>>>>);
+impl LoginAttempts {
+    /// Counts a try at `username` made `now`, or hands back how long until the next one's allowed.
+    pub fn attempt(&self, username: &str, now: DateTime<Utc>) -> Result<(), Duration> {
+        let mut attempts = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        // Made-up usernames would pile up otherwise.
+        attempts.retain(|_, tries| {
+            while tries.front().is_some_and(|at| *at <= now - attempt_window()) {
+                tries.pop_front();
+            }
+            !tries.is_empty()
+        });
+        let tries = attempts.entry(username.to_owned()).or_default();
+        if tries.len() >= MAX_ATTEMPTS {
+            return Err(tries[0] + attempt_window() - now);
+        }
+        tries.push_back(now);
+        Ok(())
+    }
+}
+
+/// `POST /api/login`
+pub async fn login(
+    State(api): State<&'static Api>,
+    request: Result<Json<LoginRequest>, JsonRejection>,
+) -> Result<(HeaderMap, Json<Session>), ApiError> {
+    let Json(request) = request?;
+    api.login_attempts.attempt(request.username.as_str(), Utc::now()).map_err(|wait| {
+        ApiError::new(StatusCode::TOO_MANY_REQUESTS)
+            .with_message(format!("Too many tries, wait {} seconds.", wait.num_seconds().max(1)))
+    })?;
+    // Argon2 is slow on purpose, so it can't hold up other requests either.
+    blocking(move || {
+        let user = api.users.user(request.username.as_str())?;
+        // Unknown usernames take as long as wrong passwords, so that timing doesn't tell them apart.
+        let hash = user.as_ref().map_or(DUMMY_HASH, |user| user.password_hash.as_str());
+        let matches = verify_password(request.password.as_str(), hash);
+        let Some(user) = user.filter(|_| matches) else {
+            return Err(ApiError::new(StatusCode::UNAUTHORIZED).with_message("That username and password don't match."));
+        };
+        start_session(api, &user)
+    }).await
+}
+
+/// Logs `user` in, handing back the cookie and the session. Blocks, like the store.
+pub fn start_session(api: &Api, user: &store::User) -> Result<(HeaderMap, Json<Session>), ApiError> {
+    let token = random_token();
+    let now = Utc::now();
+    let session = store::Session {
+        id: session_id(token.as_str()),
+        user_id: user.id,
+        csrf_token: random_token(),
+        created_at: now,
+        expires_at: now + session_lifetime(),
+    };
+    api.users.create_session(&session)?;
+    trc::info!("{} logged in", user.username);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::SET_COOKIE, session_cookie(token.as_str(), session_lifetime()));
+    Ok((headers, Json(api_session(&session, user))))
+}
+
+/// `GET /api/session`
+pub async fn session(auth: Authenticated) -> Json<Session> {
+    Json(api_session(&auth.session, &auth.user))
+}
+
+/// `DELETE /api/session`
+pub async fn logout(State(api): State<&'static Api>, auth: Authenticated) -> Result<(HeaderMap, StatusCode), ApiError> {
+    blocking(move || Ok(api.users.delete_session(auth.session.id.as_str())?)).await?;
+    let mut headers = HeaderMap::new();
+    headers.insert(header::SET_COOKIE, session_cookie("", Duration::zero()));
+    Ok((headers, StatusCode::NO_CONTENT))
+}
+
+/// Adds a user, or changes their password, reading the password from stdin.
+pub fn add_user(users: &dyn UserRepository, username: String, name: String) {
+    println!("Password for {username}:");
+    let mut password = String::new();
+    std::io::stdin().lock().read_line(&mut password).expect("password to be readable from stdin");
+    let password = password.trim_end_matches(['\r', '\n']);
+    assert!(!password.is_empty(), "password can't be empty");
+    users.save_user(UserFields { username, name, password_hash: hash_password(password) }).expect("user saves");
+}
@@ -0,0 +1,122 @@
+use std::{env::args, net::SocketAddr};
+
+use axum::{Router, http::StatusCode, middleware, routing::{get, post}};
+use blog_api::paths;
+use blog_store::{PostRepository, SqlitePostStore, UserRepository};
+use shared_config::Cfg;
+
+use crate::api_error::ApiError;
+
+mod api_error;
+mod auth;
+mod passkeys;
+mod posts;
+mod revisions;
+mod search;
+mod series;
+mod tags;
+#[cfg(test)]
+mod tests;
+
+/// Everything handlers need, leaked for the lifetime of the server.
+pub struct Api {
+    pub cfg: &'static Cfg,
+    pub posts: &'static dyn PostRepository,
+    pub users: &'static dyn UserRepository,
+    pub relying_party: blog_passkeys::RelyingParty,
+    pub ceremonies: passkeys::Ceremonies,
+    pub login_attempts: auth::LoginAttempts,
+}
+
+/// Runs `f` on a thread that's allowed to block, since the stores do. Panics carry on from here,
+/// for the panic middleware to catch.
+pub async fn blocking<T, F>(f: F) -> Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, ApiError> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(_) => Err(ApiError::new(StatusCode::INTERNAL_SERVER_ERROR)),
+    }
+}
+
+/// Every route, bar the layers only the real server wants.
+fn app(api: &'static Api) -> Router {
+    Router::new()
+        .route(paths::POSTS, get(posts::list).post(posts::create))
+        .route("/api/posts/:slug", get(posts::get).put(posts::update).delete(posts::delete))
+        .route("/api/posts/:slug/edit", get(posts::edit))
+        .route("/api/posts/:slug/revisions", get(revisions::list))
+        .route("/api/posts/:slug/revisions/:number", get(revisions::get))
+        .route("/api/posts/:slug/diff", get(revisions::diff))
+        .route("/api/series/:slug", get(series::get))
+        .route(paths::TAGS, get(tags::list))
+        .route(paths::SEARCH, get(search::search))
+        .route(paths::LOGIN, post(auth::login))
+        .route(paths::SESSION, get(auth::session).delete(auth::logout))
+        .route(paths::PASSKEYS, get(passkeys::list))
+        .route(paths::PASSKEY_REGISTER, post(passkeys::register))
+        .route(paths::PASSKEY_REGISTER_FINISH, post(passkeys::finish_registration))
+        .route(paths::PASSKEY_LOGIN, post(passkeys::login))
+        .route(paths::PASSKEY_LOGIN_FINISH, post(passkeys::finish_login))
+        // Error paths
+        .fallback(api_error::not_found)
+        .with_state(api)
+}
+
+#[tokio::main]
+async fn main() {
+    let (root_config, command) = {
+        let mut args = args();
+        let _ = args.next().expect("first value to be present");
+        let root_config = args.next().expect("config path to be present as first argument");
+        (root_config, args.collect::<Vec<_>>())
+    };
+    let cfg: &'static Cfg = {
+        let temp: Cfg = config::Config::builder()
+            .add_source(config::File::with_name(root_config.as_str()))
+            .add_source(config::Environment::with_prefix("BENXU_DEV"))
+            .build().expect("Configuration parses correctly")
+            .try_deserialize().expect("Configuration parses correctly");
+        Box::leak(Box::new(temp))
+    };
+
+    // Set up logging.
+    tracing_subscriber::fmt().init();
+    catch_panic::install_hook();
+    trc::info!("Spinning up! (database: {:?})", cfg.database.path);
+
+    let store: &'static SqlitePostStore =
+        Box::leak(Box::new(SqlitePostStore::open(cfg.database.path.as_str()).expect("database opens")));
+    let api: &'static Api = Box::leak(Box::new(Api {
+        cfg,
+        posts: store,
+        users: store,
+        relying_party: passkeys::relying_party(cfg),
+        ceremonies: passkeys::Ceremonies::default(),
+        login_attempts: auth::LoginAttempts::default(),
+    }));
+
+    // `api-server CONFIG add-user USERNAME NAME` sets someone up to log in, instead of serving.
+    match command.as_slice() {
+        [] => {},
+        [command, username, name] if command == "add-user" => {
+            auth::add_user(api.users, username.clone(), name.clone());
+            trc::info!("Saved {username}!");
+            return;
+        },
+        _ => panic!("usage: api-server CONFIG [add-user USERNAME NAME]"),
+    }
+
+    let app = app(api).layer(middleware::from_fn(api_error::catch_panic));
+
+    let address = &cfg.servers.api.address;
+    let server = axum::Server::bind(&SocketAddr::new(address.ip, address.port))
+        .serve(app.into_make_service());
+
+    let handle = tokio::spawn(server);
+    trc::info!("Spun up!");
+    handle.await.expect("no issues joining").expect("no issues from server");
+}
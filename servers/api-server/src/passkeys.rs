@@ -0,0 +1,189 @@
+//! `/api/passkeys`: logging in with a passkey instead of a password.
+//!
+//! Starting a ceremony hands out a challenge under a random ceremony id, which finishing it has to
+//! bring back. Challenges only live in memory, so restarting the server cancels ceremonies midway.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use blog_api::{
+    auth::Session,
+    passkeys::{LoginFinish, LoginStart, Passkey, RegistrationFinish, RegistrationStart},
+};
+use blog_passkeys::{Credential, RelyingParty, UserEntity};
+use blog_store as store;
+use chrono::{DateTime, Duration, Utc};
+use shared_config::Cfg;
+
+use crate::{
+    api_error::ApiError,
+    auth::{self, Authenticated},
+    blocking, Api,
+};
+
+/// Longest a passkey's name can be.
+const MAX_NAME_LENGTH: usize = 100;
+
+/// Most passkey logins that can be going on at once. Anyone can start one, so past this new ones
+/// get turned away rather than letting memory fill up, or cancelling someone else's.
+pub const MAX_CEREMONIES: usize = 1000;
+
+/// How long a ceremony can take, with some leeway over what the browser is told.
+fn ceremony_lifetime() -> Duration {
+    Duration::milliseconds(blog_passkeys::TIMEOUT_MS.into()) + Duration::minutes(1)
+}
+
+/// The site passkeys are made for, as configured.
+pub fn relying_party(cfg: &Cfg) -> RelyingParty {
+    let domain = cfg.servers.primary.address.domain.as_str();
+    let origin = cfg.servers.api.passkey_origin.clone().unwrap_or_else(|| format!("https://{domain}"));
+    RelyingParty::new(origin, domain)
+}
+
+/// What the authenticator knows a user by. Just their id, which says nothing about them.
+fn user_handle(user: &store::User) -> Vec<u8> {
+    user.id.to_be_bytes().to_vec()
+}
+
+#[derive(Debug)]
+struct Ceremony {
+    challenge: Vec<u8>,
+    /// Who's registering a passkey, or `None` when logging in.
+    registering: Option<i64>,
+    expires_at: DateTime<Utc>,
+}
+
+/// Ceremonies that have started and not finished yet, by id.
+#[derive(Debug, Default)]
+pub struct Ceremonies(Mutex<HashMap<String, Ceremony>>);
+impl Ceremonies {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Ceremony>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Starts a ceremony, clearing out expired ones on the way. Logins are refused while there are
+    /// too many of them; registering needs a session, so it isn't.
+    pub fn start(&self, registering: Option<i64>) -> Result<(String, Vec<u8>), ApiError> {
+        let id = auth::random_token();
+        let challenge = blog_passkeys::challenge();
+        let now = Utc::now();
+        let mut ceremonies = self.lock();
+        ceremonies.retain(|_, ceremony| ceremony.expires_at > now);
+        let logins = ceremonies.values().filter(|ceremony| ceremony.registering.is_none()).count();
+        if registering.is_none() && logins >= MAX_CEREMONIES {
+            return Err(ApiError::new(StatusCode::SERVICE_UNAVAILABLE)
+                .with_message("Too many passkey logins going on, try again in a minute."));
+        }
+        ceremonies.insert(id.clone(), Ceremony {
+            challenge: challenge.clone(),
+            registering,
+            expires_at: now + ceremony_lifetime(),
+        });
+        Ok((id, challenge))
+    }
+
+    /// Ends a ceremony, handing back its challenge. Every challenge only gets one try.
+    pub fn finish(&self, id: &str, registering: Option<i64>) -> Result<Vec<u8>, ApiError> {
+        let ceremony = self.lock().remove(id)
+            .filter(|ceremony| ceremony.expires_at > Utc::now() && ceremony.registering == registering)
+            .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST).with_message("That passkey request expired, try again."))?;
+        Ok(ceremony.challenge)
+    }
+}
+
+fn rejected(e: blog_passkeys::Error) -> ApiError {
+    trc::info!("Passkey rejected: {e}");
+    ApiError::new(StatusCode::UNAUTHORIZED).with_message("That passkey didn't check out.")
+}
+
+/// `GET /api/passkeys`
+pub async fn list(State(api): State<&'static Api>, auth: Authenticated) -> Result<Json<Vec<Passkey>>, ApiError> {
+    let passkeys = blocking(move || Ok(api.users.passkeys(auth.user.id)?)).await?;
+    Ok(Json(passkeys.into_iter().map(store::Passkey::into_api).collect()))
+}
+
+/// `POST /api/passkeys/register`
+pub async fn register(State(api): State<&'static Api>, auth: Authenticated) -> Result<Json<RegistrationStart>, ApiError> {
+    let user_id = auth.user.id;
+    let existing: Vec<_> = blocking(move || Ok(api.users.passkeys(user_id)?)).await?
+        .into_iter()
+        .map(|passkey| passkey.id)
+        .collect();
+    let user = UserEntity {
+        id: user_handle(&auth.user),
+        name: auth.user.username.clone(),
+        display_name: auth.user.name.clone(),
+    };
+    let (ceremony, challenge) = api.ceremonies.start(Some(auth.user.id))?;
+    let options = api.relying_party.creation_options(challenge.as_slice(), &user, existing.as_slice());
+    Ok(Json(RegistrationStart { ceremony, options }))
+}
+
+/// `POST /api/passkeys/register/finish`
+pub async fn finish_registration(
+    State(api): State<&'static Api>,
+    auth: Authenticated,
+    request: Result<Json<RegistrationFinish>, JsonRejection>,
+) -> Result<(StatusCode, Json<Passkey>), ApiError> {
+    let Json(request) = request?;
+    let challenge = api.ceremonies.finish(request.ceremony.as_str(), Some(auth.user.id))?;
+    let credential = api.relying_party.finish_registration(challenge.as_slice(), &request.response).map_err(rejected)?;
+
+    let name = request.name.trim();
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(ApiError::new(StatusCode::UNPROCESSABLE_ENTITY)
+            .with_message(format!("Passkey names can be at most {MAX_NAME_LENGTH} characters long.")));
+    }
+    let passkey = store::Passkey {
+        id: credential.id,
+        user_id: auth.user.id,
+        name: if name.is_empty() { "Passkey" } else { name }.to_owned(),
+        public_key: credential.public_key,
+        sign_count: credential.sign_count,
+        created_at: Utc::now(),
+        last_used_at: None,
+    };
+    let passkey = blocking(move || {
+        api.users.add_passkey(&passkey)?;
+        Ok(passkey)
+    }).await?;
+    trc::info!("{} added the passkey {:?}", auth.user.username, passkey.name);
+    Ok((StatusCode::CREATED, Json(passkey.into_api())))
+}
+
+/// `POST /api/passkeys/login`
+pub async fn login(State(api): State<&'static Api>) -> Result<Json<LoginStart>, ApiError> {
+    let (ceremony, challenge) = api.ceremonies.start(None)?;
+    Ok(Json(LoginStart { ceremony, options: api.relying_party.request_options(challenge.as_slice()) }))
+}
+
+/// `POST /api/passkeys/login/finish`
+pub async fn finish_login(
+    State(api): State<&'static Api>,
+    request: Result<Json<LoginFinish>, JsonRejection>,
+) -> Result<(HeaderMap, Json<Session>), ApiError> {
+    let Json(request) = request?;
+    let challenge = api.ceremonies.finish(request.ceremony.as_str(), None)?;
+    blocking(move || {
+        let Some((passkey, user)) = api.users.passkey(request.response.id.0.as_slice())? else {
+            return Err(ApiError::new(StatusCode::UNAUTHORIZED).with_message("That passkey isn't registered here."));
+        };
+        let credential = Credential {
+            id: passkey.id,
+            public_key: passkey.public_key,
+            sign_count: passkey.sign_count,
+        };
+        let sign_count = api.relying_party
+            .finish_login(challenge.as_slice(), &credential, user_handle(&user).as_slice(), &request.response)
+            .map_err(rejected)?;
+        api.users.record_passkey_use(credential.id.as_slice(), sign_count, Utc::now())?;
+        auth::start_session(api, &user)
+    }).await
+}
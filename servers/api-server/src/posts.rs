@@ -0,0 +1,159 @@
+//! `/api/posts`: listing, reading and authoring posts.
+//!
+//! Readers only ever see published posts, drafts and posts scheduled for later are for whoever's
+//! logged in. Only they get to write, too.
+
+use axum::{
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        Path, Query, State,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    Json,
+};
+use blog_api::{paths, posts::{ListParams, Post, PostInput, PostPage}};
+use blog_store::{self as store, Cursor, ListQuery, PostFields, PostSeries};
+use chrono::{DateTime, Utc};
+
+use crate::{api_error::ApiError, auth::Authenticated, blocking, Api};
+
+/// Posts per page when the request doesn't say.
+const DEFAULT_PAGE_SIZE: usize = 10;
+
+/// What gets saved, attributed to whoever saved it.
+fn fields(input: PostInput, author: &Authenticated) -> PostFields {
+    PostFields {
+        slug: input.slug,
+        title: input.title,
+        body: input.body,
+        summary: input.summary.filter(|s| !s.trim().is_empty()),
+        tags: input.tags,
+        series: input.series.map(PostSeries::from_api),
+        author: author.user.name.clone(),
+    }
+}
+
+/// Publishes, schedules or unpublishes a freshly saved post to match what the author asked for.
+fn apply_draft(
+    api: &Api,
+    post: store::Post,
+    draft: bool,
+    publish_at: Option<DateTime<Utc>>,
+) -> Result<store::Post, ApiError> {
+    Ok(match (post.draft, draft) {
+        (false, true) => api.posts.unpublish(post.slug.as_str())?,
+        (true, false) => api.posts.publish(post.slug.as_str(), publish_at.unwrap_or_else(Utc::now))?,
+        // Rescheduling, or bringing a scheduled post forward. Without a date a scheduled post goes
+        // live now, by this server's clock rather than the author's.
+        (false, false) => match publish_at {
+            Some(at) => api.posts.publish(post.slug.as_str(), at)?,
+            None if post.is_scheduled() => api.posts.publish(post.slug.as_str(), Utc::now())?,
+            None => post,
+        },
+        (true, true) => post,
+    })
+}
+
+/// `GET /api/posts`
+pub async fn list(
+    State(api): State<&'static Api>,
+    params: Result<Query<ListParams>, QueryRejection>,
+) -> Result<Json<PostPage>, ApiError> {
+    let Query(params) = params?;
+    let after = match params.after.as_ref() {
+        Some(after) => Some(Cursor::decode(after).ok_or_else(|| {
+            ApiError::new(StatusCode::BAD_REQUEST).with_message("That isn't a cursor this server handed out.")
+        })?),
+        None => None,
+    };
+    let query = ListQuery {
+        tag: params.tag,
+        include_drafts: false,
+        after,
+        limit: params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, api.cfg.servers.api.max_page_size),
+    };
+    let page = blocking(move || Ok(api.posts.list(&query)?)).await?;
+    Ok(Json(page.into_api()))
+}
+
+/// `GET /api/posts/:slug`
+pub async fn get(
+    State(api): State<&'static Api>,
+    auth: Option<Authenticated>,
+    Path(slug): Path<String>,
+) -> Result<Json<Post>, ApiError> {
+    let logged_in = auth.is_some();
+    blocking(move || match api.posts.get(slug.as_str())? {
+        Some(post) if post.is_published() => {
+            let neighbors = api.posts.neighbors(&post)?.into_api();
+            let series_parts = match post.series.as_ref() {
+                Some(series) => api.posts.series(series.slug.as_str())?.map(|series| series.parts()).unwrap_or_default(),
+                None => vec![],
+            };
+            Ok(Json(Post { neighbors, series_parts, ..post.into_api() }))
+        },
+        Some(post) if logged_in => Ok(Json(post.into_api())),
+        _ => Err(store::Error::NotFound.into()),
+    }).await
+}
+
+/// `GET /api/posts/:slug/edit`
+///
+/// Just the post, drafts and scheduled posts included, for the editor to start from. Unlike
+/// `GET /api/posts/:slug`, a lapsed login gets a 401 instead of passing for a missing draft.
+pub async fn edit(
+    State(api): State<&'static Api>,
+    _: Authenticated,
+    Path(slug): Path<String>,
+) -> Result<Json<Post>, ApiError> {
+    let post = blocking(move || api.posts.get(slug.as_str())?.ok_or_else(|| store::Error::NotFound.into())).await?;
+    Ok(Json(post.into_api()))
+}
+
+/// `POST /api/posts`
+pub async fn create(
+    State(api): State<&'static Api>,
+    auth: Authenticated,
+    input: Result<Json<PostInput>, JsonRejection>,
+) -> Result<(StatusCode, HeaderMap, Json<Post>), ApiError> {
+    let Json(input) = input?;
+    let (draft, publish_at) = (input.draft, input.publish_at);
+    let post = blocking(move || {
+        let post = api.posts.create(fields(input, &auth))?;
+        apply_draft(api, post, draft, publish_at)
+    }).await?;
+
+    let mut headers = HeaderMap::new();
+    if let Ok(location) = HeaderValue::from_str(paths::post(post.slug.as_str()).as_str()) {
+        headers.insert(header::LOCATION, location);
+    }
+    Ok((StatusCode::CREATED, headers, Json(post.into_api())))
+}
+
+/// `PUT /api/posts/:slug`
+///
+/// The slug in the body wins over the one in the path, which is how posts get renamed.
+pub async fn update(
+    State(api): State<&'static Api>,
+    auth: Authenticated,
+    Path(slug): Path<String>,
+    input: Result<Json<PostInput>, JsonRejection>,
+) -> Result<Json<Post>, ApiError> {
+    let Json(input) = input?;
+    let (draft, publish_at) = (input.draft, input.publish_at);
+    let post = blocking(move || {
+        let post = api.posts.update(slug.as_str(), fields(input, &auth))?;
+        apply_draft(api, post, draft, publish_at)
+    }).await?;
+    Ok(Json(post.into_api()))
+}
+
+/// `DELETE /api/posts/:slug`
+pub async fn delete(
+    State(api): State<&'static Api>,
+    _: Authenticated,
+    Path(slug): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    blocking(move || Ok(api.posts.delete(slug.as_str())?)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,68 @@
+//! `/api/posts/:slug/revisions` and `/api/posts/:slug/diff`: what a post looked like before.
+//!
+//! Histories can hold whatever a post went through before being published, so they're only there
+//! for whoever's logged in.
+
+use axum::{
+    extract::{
+        rejection::{PathRejection, QueryRejection},
+        Path, Query, State,
+    },
+    http::StatusCode,
+    Json,
+};
+use blog_api::revisions::{DiffParams, Revision, RevisionDiff, RevisionSummary};
+use blog_store as store;
+
+use crate::{api_error::ApiError, auth::Authenticated, blocking, Api};
+
+fn no_revision() -> ApiError {
+    ApiError::new(StatusCode::NOT_FOUND).with_message("The post doesn't have that revision.")
+}
+
+/// `GET /api/posts/:slug/revisions`
+pub async fn list(
+    State(api): State<&'static Api>,
+    _: Authenticated,
+    Path(slug): Path<String>,
+) -> Result<Json<Vec<RevisionSummary>>, ApiError> {
+    let revisions = blocking(move || Ok(api.posts.revisions(slug.as_str())?)).await?;
+    Ok(Json(revisions.iter().map(store::Revision::summary).collect()))
+}
+
+/// `GET /api/posts/:slug/revisions/:number`
+pub async fn get(
+    State(api): State<&'static Api>,
+    _: Authenticated,
+    path: Result<Path<(String, u32)>, PathRejection>,
+) -> Result<Json<Revision>, ApiError> {
+    let Path((slug, number)) = path?;
+    let revision = blocking(move || Ok(api.posts.revision(slug.as_str(), number)?)).await?.ok_or_else(no_revision)?;
+    Ok(Json(revision.into_api()))
+}
+
+/// `GET /api/posts/:slug/diff`
+pub async fn diff(
+    State(api): State<&'static Api>,
+    _: Authenticated,
+    Path(slug): Path<String>,
+    params: Result<Query<DiffParams>, QueryRejection>,
+) -> Result<Json<RevisionDiff>, ApiError> {
+    let Query(params) = params?;
+    blocking(move || {
+        let slug = slug.as_str();
+        let to = match params.to {
+            Some(to) => api.posts.revision(slug, to)?,
+            None => api.posts.latest_revision(slug)?,
+        }.ok_or_else(no_revision)?;
+        let from = match params.from {
+            Some(from) => Some(api.posts.revision(slug, from)?.ok_or_else(no_revision)?),
+            // Numbers don't skip, so the one before is one less.
+            None => match to.number.checked_sub(1) {
+                Some(before) => api.posts.revision(slug, before)?,
+                None => None,
+            },
+        };
+        Ok(Json(to.diff(from.as_ref())))
+    }).await
+}
@@ -0,0 +1,26 @@
+//! `/api/search`: full-text search over published posts.
+
+use axum::{
+    extract::{rejection::QueryRejection, Query, State},
+    Json,
+};
+use blog_api::search::{SearchHit, SearchParams};
+use blog_store as store;
+
+use crate::{api_error::ApiError, blocking, Api};
+
+/// How many results to return when the client doesn't say.
+const DEFAULT_LIMIT: usize = 10;
+
+/// `GET /api/search`
+///
+/// An empty search finds nothing rather than failing, since that's what a search box starts out as.
+pub async fn search(
+    State(api): State<&'static Api>,
+    params: Result<Query<SearchParams>, QueryRejection>,
+) -> Result<Json<Vec<SearchHit>>, ApiError> {
+    let Query(params) = params?;
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, api.cfg.servers.api.max_page_size);
+    let hits = blocking(move || Ok(api.posts.search(params.q.as_str(), limit)?)).await?;
+    Ok(Json(hits.into_iter().map(store::SearchHit::into_api).collect()))
+}
@@ -0,0 +1,20 @@
+//! `/api/series/:slug`: the posts of a series, in reading order.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use blog_api::posts::Series;
+
+use crate::{api_error::ApiError, blocking, Api};
+
+/// `GET /api/series/:slug`
+///
+/// Series with nothing published yet don't exist as far as readers are concerned.
+pub async fn get(State(api): State<&'static Api>, Path(slug): Path<String>) -> Result<Json<Series>, ApiError> {
+    match blocking(move || Ok(api.posts.series(slug.as_str())?)).await? {
+        Some(series) if !series.posts.is_empty() => Ok(Json(series.into_api())),
+        _ => Err(ApiError::new(StatusCode::NOT_FOUND).with_message("There's no series with that slug.")),
+    }
+}
@@ -0,0 +1,13 @@
+//! `/api/tags`: every tag in use, for a tag cloud.
+
+use axum::{extract::State, Json};
+use blog_api::posts::TagCount;
+use blog_store as store;
+
+use crate::{api_error::ApiError, blocking, Api};
+
+/// `GET /api/tags`
+pub async fn list(State(api): State<&'static Api>) -> Result<Json<Vec<TagCount>>, ApiError> {
+    let tags = blocking(move || Ok(api.posts.tags()?)).await?;
+    Ok(Json(tags.into_iter().map(store::TagCount::into_api).collect()))
+}
@@ -0,0 +1,409 @@
+//! Handler tests, sending requests through the real routes to a memory store.
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use blog_api::{
+    auth::{LoginRequest, Session},
+    error::ErrorBody,
+    paths,
+    posts::{Post, PostInput, PostPage, PostSeries, Series, TagCount},
+    revisions::{Revision, RevisionDiff, RevisionSummary},
+};
+use blog_store::{MemoryPostStore, UserFields, UserRepository};
+use chrono::{Duration, DurationRound, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use shared_config::Cfg;
+use tower::ServiceExt;
+
+use crate::{app, auth, passkeys, Api};
+
+/// Leaves out `[servers.api]`, which has defaults.
+const CFG: &str = r#"
+[servers.primary]
+resource_root = "."
+[servers.primary.address]
+domain = "example.com"
+ip = "127.0.0.1"
+port = 8080
+"#;
+
+const USERNAME: &str = "ben";
+const PASSWORD: &str = "hunter2";
+
+fn api() -> &'static Api {
+    let cfg: Cfg = config::Config::builder()
+        .add_source(config::File::from_str(CFG, config::FileFormat::Toml))
+        .build().unwrap()
+        .try_deserialize().unwrap();
+    let cfg: &'static Cfg = Box::leak(Box::new(cfg));
+    let store: &'static MemoryPostStore = Box::leak(Box::new(MemoryPostStore::new()));
+    store.save_user(UserFields {
+        username: USERNAME.to_owned(),
+        name: "Ben".to_owned(),
+        password_hash: auth::hash_password(PASSWORD),
+    }).unwrap();
+    Box::leak(Box::new(Api {
+        cfg,
+        posts: store,
+        users: store,
+        relying_party: passkeys::relying_party(cfg),
+        ceremonies: passkeys::Ceremonies::default(),
+        login_attempts: auth::LoginAttempts::default(),
+    }))
+}
+
+/// Whoever's logged in, as far as requests go.
+struct Login {
+    cookie: String,
+    csrf_token: Option<String>,
+}
+
+struct Response {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+impl Response {
+    fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body).unwrap_or_else(|e| panic!("{e}: {:?}", self.body))
+    }
+
+    /// The message of an error, checking the body is an [`ErrorBody`] that agrees on the status.
+    fn message(&self) -> String {
+        let body: ErrorBody = self.json();
+        assert_eq!(body.status, self.status.as_u16());
+        body.message.unwrap_or_default()
+    }
+}
+
+async fn send(app: &Router, method: Method, uri: &str, login: Option<&Login>, body: Option<&impl Serialize>) -> Response {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(login) = login {
+        request = request.header(header::COOKIE, login.cookie.as_str());
+        if let Some(csrf_token) = login.csrf_token.as_deref() {
+            request = request.header("x-csrf-token", csrf_token);
+        }
+    }
+    let body = match body {
+        Some(body) => {
+            request = request.header(header::CONTENT_TYPE, "application/json");
+            Body::from(serde_json::to_vec(body).unwrap())
+        },
+        None => Body::empty(),
+    };
+    let response = app.clone().oneshot(request.body(body).unwrap()).await.unwrap();
+    let (parts, body) = response.into_parts();
+    Response { status: parts.status, headers: parts.headers, body: hyper::body::to_bytes(body).await.unwrap() }
+}
+
+async fn get(app: &Router, uri: &str, login: Option<&Login>) -> Response {
+    send(app, Method::GET, uri, login, None::<&()>).await
+}
+
+async fn log_in(app: &Router) -> Login {
+    let request = LoginRequest { username: USERNAME.to_owned(), password: PASSWORD.to_owned() };
+    let response = send(app, Method::POST, paths::LOGIN, None, Some(&request)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let cookie = response.headers[header::SET_COOKIE].to_str().unwrap();
+    let cookie = cookie.split(';').next().unwrap().to_owned();
+    Login { cookie, csrf_token: Some(response.json::<Session>().csrf_token) }
+}
+
+fn input(slug: &str, draft: bool) -> PostInput {
+    PostInput {
+        slug: slug.to_owned(),
+        title: format!("About {slug}"),
+        body: "Some *words*.".to_owned(),
+        summary: None,
+        tags: vec!["testing".to_owned()],
+        series: None,
+        draft,
+        publish_at: None,
+    }
+}
+
+#[test]
+fn the_api_server_has_defaults() {
+    let api = api();
+    assert_eq!(api.cfg.servers.api.address.port, 8081);
+    assert_eq!(api.cfg.servers.api.max_page_size, 50);
+}
+
+#[tokio::test]
+async fn creating_posts() {
+    let app = app(api());
+    let login = log_in(&app).await;
+
+    let created = send(&app, Method::POST, paths::POSTS, Some(&login), Some(&input("first", false))).await;
+    assert_eq!(created.status, StatusCode::CREATED);
+    assert_eq!(created.headers[header::LOCATION], paths::post("first"));
+    let post: Post = created.json();
+    assert_eq!((post.slug.as_str(), post.draft), ("first", false));
+    assert!(post.html.contains("<em>words</em>"), "{}", post.html);
+
+    let again = send(&app, Method::POST, paths::POSTS, Some(&login), Some(&input("first", false))).await;
+    assert_eq!(again.status, StatusCode::CONFLICT);
+    assert!(again.message().contains("first"));
+
+    let invalid = send(&app, Method::POST, paths::POSTS, Some(&login), Some(&input("Not A Slug", false))).await;
+    assert_eq!(invalid.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(invalid.message().contains("Not A Slug"));
+
+    let missing_title = serde_json::json!({ "slug": "untitled", "body": "" });
+    let missing = send(&app, Method::POST, paths::POSTS, Some(&login), Some(&missing_title)).await;
+    assert_eq!(missing.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(!missing.message().is_empty());
+
+    let deleted = send(&app, Method::DELETE, paths::post("first").as_str(), Some(&login), None::<&()>).await;
+    assert_eq!(deleted.status, StatusCode::NO_CONTENT);
+    assert_eq!(get(&app, paths::post("first").as_str(), None).await.status, StatusCode::NOT_FOUND);
+    let gone = send(&app, Method::DELETE, paths::post("first").as_str(), Some(&login), None::<&()>).await;
+    assert_eq!(gone.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn drafts_are_only_for_whoever_is_logged_in() {
+    let app = app(api());
+    let login = log_in(&app).await;
+    let uri = paths::post("draft");
+    send(&app, Method::POST, paths::POSTS, Some(&login), Some(&input("draft", true))).await;
+
+    let hidden = get(&app, uri.as_str(), None).await;
+    assert_eq!(hidden.status, StatusCode::NOT_FOUND);
+    assert!(!hidden.message().is_empty());
+    assert!(get(&app, paths::POSTS, None).await.json::<PostPage>().posts.is_empty());
+    let shown = get(&app, uri.as_str(), Some(&login)).await;
+    assert_eq!(shown.status, StatusCode::OK);
+    assert!(shown.json::<Post>().draft);
+
+    assert_eq!(get(&app, paths::edit("draft").as_str(), None).await.status, StatusCode::UNAUTHORIZED);
+    let editing = get(&app, paths::edit("draft").as_str(), Some(&login)).await;
+    assert_eq!(editing.status, StatusCode::OK);
+    assert_eq!(editing.json::<Post>().body, "Some *words*.");
+    assert_eq!(get(&app, paths::edit("nothing").as_str(), Some(&login)).await.status, StatusCode::NOT_FOUND);
+
+    let published = send(&app, Method::PUT, uri.as_str(), Some(&login), Some(&input("draft", false))).await;
+    assert_eq!(published.status, StatusCode::OK);
+    assert_eq!(get(&app, uri.as_str(), None).await.json::<Post>().slug, "draft");
+    let listed = get(&app, paths::POSTS, None).await.json::<PostPage>();
+    assert_eq!(listed.posts.iter().map(|post| post.slug.as_str()).collect::<Vec<_>>(), ["draft"]);
+}
+
+#[tokio::test]
+async fn scheduled_posts_go_live_on_the_servers_clock() {
+    let app = app(api());
+    let login = log_in(&app).await;
+    let uri = paths::post("later");
+    let at = Utc::now().duration_trunc(Duration::seconds(1)).unwrap() + Duration::days(1);
+    let scheduled = PostInput { publish_at: Some(at), ..input("later", false) };
+    send(&app, Method::POST, paths::POSTS, Some(&login), Some(&scheduled)).await;
+    assert_eq!(get(&app, uri.as_str(), None).await.status, StatusCode::NOT_FOUND);
+
+    let saved = send(&app, Method::PUT, uri.as_str(), Some(&login), Some(&scheduled)).await;
+    assert_eq!(saved.json::<Post>().published_at, Some(at));
+    assert_eq!(get(&app, uri.as_str(), None).await.status, StatusCode::NOT_FOUND);
+
+    let before = Utc::now().duration_trunc(Duration::seconds(1)).unwrap();
+    let published = send(&app, Method::PUT, uri.as_str(), Some(&login), Some(&input("later", false))).await;
+    let published_at = published.json::<Post>().published_at.unwrap();
+    assert!(before <= published_at && published_at <= Utc::now(), "{published_at}");
+    assert_eq!(get(&app, uri.as_str(), None).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn series_and_tags_are_for_published_posts() {
+    let app = app(api());
+    let login = log_in(&app).await;
+    let part = |slug: &str, part, title: &str, draft| PostInput {
+        series: Some(PostSeries { slug: "parts".to_owned(), title: title.to_owned(), part }),
+        ..input(slug, draft)
+    };
+    send(&app, Method::POST, paths::POSTS, Some(&login), Some(&part("one", 1, "Parts", false))).await;
+    send(&app, Method::POST, paths::POSTS, Some(&login), Some(&part("two", 2, "Renamed", true))).await;
+    send(&app, Method::POST, paths::POSTS, Some(&login), Some(&PostInput {
+        series: Some(PostSeries { slug: "unstarted".to_owned(), title: "Unstarted".to_owned(), part: 1 }),
+        ..input("draft", true)
+    })).await;
+
+    let series = get(&app, paths::series("parts").as_str(), None).await.json::<Series>();
+    assert_eq!(series.title, "Parts");
+    assert_eq!(series.posts.iter().map(|post| post.slug.as_str()).collect::<Vec<_>>(), ["one"]);
+    for slug in ["unstarted", "nothing"] {
+        let missing = get(&app, paths::series(slug).as_str(), None).await;
+        assert_eq!(missing.status, StatusCode::NOT_FOUND, "{slug}");
+        assert!(!missing.message().is_empty(), "{slug}");
+    }
+    let count = |tag: &str, posts| TagCount { tag: tag.to_owned(), posts };
+    assert_eq!(get(&app, paths::TAGS, None).await.json::<Vec<TagCount>>(), [count("testing", 1)]);
+
+    // The draft's title for the series goes out with it.
+    let uri = paths::post("two");
+    send(&app, Method::PUT, uri.as_str(), Some(&login), Some(&part("two", 2, "Renamed", false))).await;
+    let series = get(&app, paths::series("parts").as_str(), None).await.json::<Series>();
+    assert_eq!(series.title, "Renamed");
+    assert_eq!(series.posts.iter().map(|post| post.slug.as_str()).collect::<Vec<_>>(), ["one", "two"]);
+    assert_eq!(get(&app, paths::TAGS, None).await.json::<Vec<TagCount>>(), [count("testing", 2)]);
+}
+
+#[tokio::test]
+async fn bad_requests_say_what_is_wrong() {
+    let app = app(api());
+    for uri in ["/api/posts?after=nonsense", "/api/posts?limit=many", "/api/search?limit=-1"] {
+        let response = get(&app, uri, None).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{uri}");
+        assert!(!response.message().is_empty(), "{uri}");
+    }
+
+    let nowhere = get(&app, "/api/nowhere", None).await;
+    assert_eq!(nowhere.status, StatusCode::NOT_FOUND);
+    nowhere.message();
+    let malformed = send(&app, Method::POST, paths::LOGIN, None, Some(&"not a login")).await;
+    assert_eq!(malformed.status, StatusCode::UNPROCESSABLE_ENTITY);
+    malformed.message();
+}
+
+#[tokio::test]
+async fn revisions_are_only_for_whoever_is_logged_in() {
+    let app = app(api());
+    let login = log_in(&app).await;
+    send(&app, Method::POST, paths::POSTS, Some(&login), Some(&input("history", false))).await;
+    let edited = PostInput { body: "Other words.".to_owned(), ..input("history", false) };
+    send(&app, Method::PUT, paths::post("history").as_str(), Some(&login), Some(&edited)).await;
+
+    for uri in [paths::revisions("history"), paths::revision("history", 1), paths::diff("history")] {
+        assert_eq!(get(&app, uri.as_str(), None).await.status, StatusCode::UNAUTHORIZED, "{uri}");
+    }
+
+    let listed: Vec<RevisionSummary> = get(&app, paths::revisions("history").as_str(), Some(&login)).await.json();
+    assert_eq!(listed.iter().map(|revision| revision.number).collect::<Vec<_>>(), [2, 1]);
+    let first: Revision = get(&app, paths::revision("history", 1).as_str(), Some(&login)).await.json();
+    assert_eq!(first.body, "Some *words*.");
+    let missing = get(&app, paths::revision("history", 3).as_str(), Some(&login)).await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+    missing.message();
+    let malformed = get(&app, "/api/posts/history/revisions/first", Some(&login)).await;
+    assert_eq!(malformed.status, StatusCode::BAD_REQUEST);
+    malformed.message();
+
+    // The latest revision against the one before it, unless told otherwise.
+    let diff: RevisionDiff = get(&app, paths::diff("history").as_str(), Some(&login)).await.json();
+    assert_eq!((diff.from.map(|from| from.number), diff.to.number), (Some(1), 2));
+    let diff: RevisionDiff = get(&app, "/api/posts/history/diff?to=1", Some(&login)).await.json();
+    assert_eq!((diff.from.map(|from| from.number), diff.to.number), (None, 1));
+    let diff: RevisionDiff = get(&app, "/api/posts/history/diff?from=2&to=1", Some(&login)).await.json();
+    assert_eq!((diff.from.map(|from| from.number), diff.to.number), (Some(2), 1));
+    assert_eq!(get(&app, "/api/posts/history/diff?from=5", Some(&login)).await.status, StatusCode::NOT_FOUND);
+    assert_eq!(get(&app, paths::diff("nothing").as_str(), Some(&login)).await.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn logging_in_and_out() {
+    let app = app(api());
+    let wrong = |username: &str, password: &str| LoginRequest { username: username.to_owned(), password: password.to_owned() };
+    let wrong_password = send(&app, Method::POST, paths::LOGIN, None, Some(&wrong(USERNAME, "hunter3"))).await;
+    assert_eq!(wrong_password.status, StatusCode::UNAUTHORIZED);
+    let nobody = send(&app, Method::POST, paths::LOGIN, None, Some(&wrong("nobody", PASSWORD))).await;
+    assert_eq!(nobody.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(wrong_password.message(), nobody.message());
+
+    let login = log_in(&app).await;
+    let session: Session = get(&app, paths::SESSION, Some(&login)).await.json();
+    assert_eq!(session.username, USERNAME);
+    assert_eq!(Some(session.csrf_token), login.csrf_token);
+
+    let logged_out = send(&app, Method::DELETE, paths::SESSION, Some(&login), None::<&()>).await;
+    assert_eq!(logged_out.status, StatusCode::NO_CONTENT);
+    let cleared = logged_out.headers[header::SET_COOKIE].to_str().unwrap();
+    assert!(cleared.starts_with("session=;") && cleared.contains("Max-Age=0"), "{cleared}");
+    assert_eq!(get(&app, paths::SESSION, Some(&login)).await.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn sessions_and_csrf_tokens_are_checked() {
+    let app = app(api());
+    let login = log_in(&app).await;
+    let create = |login| {
+        let app = app.clone();
+        async move { send(&app, Method::POST, paths::POSTS, Some(&login), Some(&input("checked", true))).await }
+    };
+
+    let no_cookie = get(&app, paths::SESSION, None).await;
+    assert_eq!(no_cookie.status, StatusCode::UNAUTHORIZED);
+    no_cookie.message();
+    let unknown = Login { cookie: "session=made-up".to_owned(), csrf_token: login.csrf_token.clone() };
+    assert_eq!(get(&app, paths::SESSION, Some(&unknown)).await.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(create(unknown).await.status, StatusCode::UNAUTHORIZED);
+
+    // Reading doesn't need the token, changing something does.
+    let no_token = Login { cookie: login.cookie.clone(), csrf_token: None };
+    assert_eq!(get(&app, paths::SESSION, Some(&no_token)).await.status, StatusCode::OK);
+    let forbidden = create(no_token).await;
+    assert_eq!(forbidden.status, StatusCode::FORBIDDEN);
+    forbidden.message();
+    let wrong_token = Login { cookie: login.cookie.clone(), csrf_token: Some("guess".to_owned()) };
+    assert_eq!(create(wrong_token).await.status, StatusCode::FORBIDDEN);
+    assert_eq!(create(login).await.status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn logging_in_is_rate_limited() {
+    let api = api();
+    let app = app(api);
+    let now = chrono::Utc::now();
+    for _ in 0..10 {
+        api.login_attempts.attempt(USERNAME, now).unwrap();
+    }
+    let request = LoginRequest { username: USERNAME.to_owned(), password: PASSWORD.to_owned() };
+    let limited = send(&app, Method::POST, paths::LOGIN, None, Some(&request)).await;
+    assert_eq!(limited.status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(limited.message().contains("seconds"));
+
+    let wait = api.login_attempts.attempt(USERNAME, now + chrono::Duration::seconds(20)).unwrap_err();
+    assert_eq!(wait.num_seconds(), 40);
+    api.login_attempts.attempt(USERNAME, now + chrono::Duration::minutes(1)).unwrap();
+}
+
+#[tokio::test]
+async fn one_flooded_username_doesnt_block_another() {
+    let api = api();
+    let app = app(api);
+    let now = chrono::Utc::now();
+    for _ in 0..10 {
+        api.login_attempts.attempt("admin", now).unwrap();
+    }
+    assert!(api.login_attempts.attempt("admin", now).is_err());
+
+    let request = LoginRequest { username: USERNAME.to_owned(), password: PASSWORD.to_owned() };
+    let response = send(&app, Method::POST, paths::LOGIN, None, Some(&request)).await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[test]
+fn the_dummy_hash_costs_as_much_as_a_real_one() {
+    use argon2::password_hash::PasswordHash;
+
+    let real = auth::hash_password(PASSWORD);
+    let (real, dummy) = (PasswordHash::new(real.as_str()).unwrap(), PasswordHash::new(auth::DUMMY_HASH).unwrap());
+    assert_eq!((real.algorithm, real.version, real.params), (dummy.algorithm, dummy.version, dummy.params));
+}
+
+#[test]
+fn passkey_ceremonies_are_capped() {
+    let ceremonies = passkeys::Ceremonies::default();
+    let (first, challenge) = ceremonies.start(None).unwrap();
+    for _ in 1..passkeys::MAX_CEREMONIES {
+        ceremonies.start(None).unwrap();
+    }
+    // More logins are turned away instead of cancelling the ones going on.
+    assert_eq!(ceremonies.start(None).unwrap_err().status, StatusCode::SERVICE_UNAVAILABLE);
+    let (registration, _) = ceremonies.start(Some(1)).unwrap();
+    assert_eq!(ceremonies.finish(first.as_str(), None).unwrap(), challenge);
+    assert!(ceremonies.finish(registration.as_str(), Some(1)).is_ok());
+    // Every challenge only gets one try.
+    assert!(ceremonies.finish(first.as_str(), None).is_err());
+    // Finishing one makes room for another.
+    ceremonies.start(None).unwrap();
+}
@@ -1,43 +1,214 @@
-use axum::response::Html;
-use maud::html;
-use spb::{data::{Css, Favicon, Logo, Menu, MenuItem, PageMetaData, Script}, partials::basic_page};
+//! The blog, rendered here with the `blog` client's own components and then hydrated by it.
+//!
+//! Whatever the page needed from the store goes along with it as [`Prefetched`], so that hydration
+//! renders exactly what the server did. Rendered pages are kept in the [render cache](crate::cache).
 
-pub async fn page() -> Html<String> {
+use axum::{
+    extract::{Query, State},
+    http::{StatusCode, Uri},
+    response::Html,
+};
+use blog::{prefetched::{self, Prefetched}, Route};
+use blog_api::{posts::{ListParams, Post}, search::SearchParams};
+use blog_store::{Cursor, ListQuery, PostRepository};
+use maud::{html, PreEscaped};
+use spb::{
+    data::{Css, OgKind, PageMetaData, Robots, Script},
+    schema::{BlogPosting, BreadcrumbList, Person},
+};
+use yew_router::Routable;
+
+use crate::{blocking, cache::Rendered, error_page::ErrorPage, feed, page_context::PageContext, Site};
+
+/// Posts per page of the blog, same as the api server's default page.
+const PAGE_SIZE: usize = 10;
+
+/// What every page of the blog starts with.
+fn meta(ctx: &PageContext) -> PageMetaData<'static> {
     let (glue, load) = Script::wasm_bindgen_loader("public/js", "public/wasm", "blog");
-    let meta = PageMetaData {
-        scripts: &[
-            Script::External(glue.as_str()),
-            Script::Embedded(load.as_str()),
-        ],
-        css: &[
-            Css::Critical { src: "public/css/reset.css" },
-            Css::Critical { src: "public/css/typography.css" },
-            Css::Critical { src: "public/css/main.css" },
-            Css::Critical { src: "public/css/index.css" },
-        ],
-        menu: Some(&Menu(&[MenuItem {
-            text: "Blog",
-            link: Some("/blog"),
-            children: None,
-        }])),
-        logo: Some(&Logo {
-            src: "public/svg/branding.svg",
-            href: Some("/"),
-        }),
-        favicons: &[Favicon {
-            link: "favicon.svg",
-            media_type: None,
-            sizes: None,
-        }],
-        ..PageMetaData::default()
-    };
-
-    basic_page(
-        html! {
-            .blog {
-                "Under construction, please be patient!"
+    ctx.meta()
+        .page_title("Blog")
+        .description("Ben Xu's blog, mostly about Rust, graphics and other side projects.")
+        .alternates(feed::alternates())
+        .css(Css::critical("public/css/index.css"))
+        .css(Css::critical("public/css/blog.css"))
+        .css(Css::non_critical("/public/css/highlight.css"))
+        .script(glue)
+        .script(load)
+}
+
+fn post_meta(ctx: &PageContext, post: &Post) -> PageMetaData<'static> {
+    let meta = meta(ctx)
+        .page_title(post.title.clone())
+        .social(ctx.social().kind(OgKind::Article {
+            published: post.published_at,
+            modified: Some(post.updated_at),
+        }));
+    let meta = match post.summary.as_ref() {
+        Some(summary) => meta.description(summary.clone()),
+        None => meta,
+    };
+    let posting = BlogPosting {
+        headline: post.title.clone().into(),
+        url: ctx.canonical.clone().into(),
+        description: post.summary.clone().map(Into::into),
+        image: None,
+        date_published: post.published_at,
+        date_modified: Some(post.updated_at),
+        author: Some(Box::new(Person::from(&meta.copyright.name).url(ctx.absolute("/")))),
+        keywords: post.tags.iter().cloned().map(Into::into).collect(),
+    };
+    let crumbs = BreadcrumbList::default()
+        .crumb("Blog", ctx.absolute("/blog"))
+        .crumb(post.title.clone(), ctx.canonical.clone());
+    meta.structured_data(posting).structured_data(crumbs)
+}
+
+/// The listing the query string asks for, the same way the api server reads it.
+fn list_query(uri: &Uri) -> Result<ListQuery, ErrorPage> {
+    let Ok(Query(params)) = Query::<ListParams>::try_from_uri(uri) else {
+        return Err(ErrorPage::new(StatusCode::BAD_REQUEST));
+    };
+    let after = match params.after.as_ref() {
+        Some(after) => Some(Cursor::decode(after).ok_or_else(|| {
+            ErrorPage::new(StatusCode::BAD_REQUEST).with_message("That link to an older page doesn't work anymore.")
+        })?),
+        None => None,
+    };
+    Ok(ListQuery { tag: params.tag, after, limit: PAGE_SIZE, ..ListQuery::default() })
+}
+
+fn published_post(posts: &dyn PostRepository, slug: &str) -> Result<Post, ErrorPage> {
+    match posts.get(slug)? {
+        Some(post) if post.is_published() => {
+            let neighbors = posts.neighbors(&post)?.into_api();
+            let series_parts = match post.series.as_ref() {
+                Some(series) => posts.series(series.slug.as_str())?.map(|series| series.parts()).unwrap_or_default(),
+                None => vec![],
+            };
+            Ok(Post { neighbors, series_parts, ..post.into_api() })
+        },
+        _ => Err(blog_store::Error::NotFound.into()),
+    }
+}
+
+/// The query parameters a page depends on, `None` for pages not worth keeping. Anything else in
+/// the query string is left out of both the cache key and the render, so that it can't fill the
+/// cache with copies of the same page.
+fn kept_params(route: &Route) -> Option<&'static [&'static str]> {
+    match route {
+        Route::PostList => Some(&["after", "tag"]),
+        // The tag comes from the path.
+        Route::Tag { .. } => Some(&["after"]),
+        Route::Post { .. } | Route::Series { .. } | Route::EditNew | Route::EditExisting { .. } => Some(&[]),
+        // Every search is different, and the login page's `next` can be anything.
+        Route::Search | Route::Login => None,
+    }
+}
+
+/// `uri` with only the `kept` query parameters.
+fn kept_uri(uri: &Uri, kept: &[(String, String)]) -> Result<Uri, ErrorPage> {
+    let query = serde_urlencoded::to_string(kept).map_err(|_| ErrorPage::new(StatusCode::BAD_REQUEST))?;
+    let uri = match query.as_str() {
+        "" => uri.path().parse(),
+        query => format!("{}?{query}", uri.path()).parse(),
+    };
+    uri.map_err(|_| ErrorPage::new(StatusCode::BAD_REQUEST))
+}
+
+pub async fn page(
+    State(site): State<&'static Site>,
+    ctx: PageContext,
+    Query(mut query): Query<Vec<(String, String)>>,
+    uri: Uri,
+) -> Result<Html<String>, ErrorPage> {
+    let Some(route) = Route::recognize(uri.path()) else {
+        return Err(ErrorPage::new(StatusCode::NOT_FOUND));
+    };
+    let Some(kept) = kept_params(&route) else {
+        return Ok(render(site, ctx, route, query, uri).await?.body.into());
+    };
+    query.retain(|(name, _)| kept.contains(&name.as_str()));
+    let uri = kept_uri(&uri, &query)?;
+    // Pages on alternate domains differ in their metadata.
+    let key = format!("{}{uri}", if ctx.alternate { "alternate:" } else { "" });
+    let rendered = site.cache.get_or_render(site.posts.as_ref(), key, || render(site, ctx, route, query, uri)).await?;
+    Ok(rendered.body.into())
+}
+
+async fn render(
+    site: &'static Site,
+    ctx: PageContext,
+    route: Route,
+    query: Vec<(String, String)>,
+    uri: Uri,
+) -> Result<Rendered, ErrorPage> {
+    let posts: &'static dyn PostRepository = site.posts.as_ref();
+    let mut prefetched = Prefetched::default();
+    let meta = match route {
+        Route::PostList | Route::Tag { .. } => {
+            let mut list = list_query(&uri)?;
+            if let Route::Tag { tag } = route {
+                list.tag = Some(tag);
             }
+            let meta = match list.tag.as_ref() {
+                Some(tag) => meta(&ctx).page_title(format!("Posts tagged {tag}")),
+                None => meta(&ctx),
+            };
+            let (page, tags) = blocking(move || Ok((posts.list(&list)?, posts.tags()?))).await?;
+            prefetched.posts = Some(page.into_api());
+            prefetched.tags = Some(tags.into_iter().map(blog_store::TagCount::into_api).collect());
+            meta
+        },
+        Route::Series { slug } => {
+            let series = match blocking(move || Ok(posts.series(slug.as_str())?)).await? {
+                Some(series) if !series.posts.is_empty() => series,
+                _ => return Err(blog_store::Error::NotFound.into()),
+            };
+            let crumbs = BreadcrumbList::default()
+                .crumb("Blog", ctx.absolute("/blog"))
+                .crumb(series.title.clone(), ctx.canonical.clone());
+            let meta = meta(&ctx).page_title(series.title.clone()).structured_data(crumbs);
+            prefetched.series = Some(series.into_api());
+            meta
+        },
+        Route::Search => {
+            let Ok(Query(params)) = Query::<SearchParams>::try_from_uri(&uri) else {
+                return Err(ErrorPage::new(StatusCode::BAD_REQUEST));
+            };
+            // Same as the client, which doesn't ask the api server about empty searches.
+            let (hits, title) = match params.q.trim() {
+                "" => (vec![], "Search".to_owned()),
+                q => {
+                    let title = format!("Search for {q}");
+                    (blocking(move || Ok(posts.search(params.q.as_str(), PAGE_SIZE)?)).await?, title)
+                },
+            };
+            prefetched.search = Some(hits.into_iter().map(blog_store::SearchHit::into_api).collect());
+            // Results are better found through the posts themselves.
+            meta(&ctx).page_title(title).robots(Robots { index: false, follow: true })
+        },
+        Route::Post { slug } => {
+            let post = blocking(move || published_post(posts, slug.as_str())).await?;
+            let meta = post_meta(&ctx, &post);
+            prefetched.post = Some(post);
+            meta
+        },
+        Route::EditNew | Route::EditExisting { .. } => meta(&ctx)
+            .page_title("Editor")
+            .robots(Robots { index: false, follow: false }),
+        Route::Login => meta(&ctx)
+            .page_title("Log in")
+            .robots(Robots { index: false, follow: false }),
+    };
+
+    let json = prefetched.to_script_json();
+    let content = blog::render(uri.path().to_owned(), query, prefetched).await;
+    let body = meta.render(
+        html! {
+            .blog { (PreEscaped(content)) }
+            script #(prefetched::ELEMENT_ID) type="application/json" { (PreEscaped(json)) }
         },
-        Some(&meta),
-    ).into_string().into()
+    ).into_string();
+    Ok(Rendered { body, updated: None })
 }
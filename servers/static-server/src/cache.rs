@@ -0,0 +1,126 @@
+//! Rendered blog pages, feeds and sitemaps, kept until they could have changed.
+//!
+//! Posts only change through the api server, so every lookup first checks the store's
+//! [`version`](PostRepository::version) and starts over if it moved. Scheduled posts go live
+//! without anything being written though, which is what [`expire_scheduled`] is for.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Mutex, MutexGuard},
+};
+
+use blog_store::PostRepository;
+use chrono::{DateTime, Utc};
+use tokio::sync::Notify;
+
+use crate::{blocking, error_page::ErrorPage, Site};
+
+/// Most responses kept at once. Query strings make for endless keys, so past this new renders
+/// just aren't kept until the next clear.
+const MAX_ENTRIES: usize = 1000;
+
+/// A rendered response body.
+#[derive(Debug, Clone)]
+pub struct Rendered {
+    pub body: String,
+    /// When what went into it last changed, for responses that say.
+    pub updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// The store's version the entries were rendered at.
+    version: Option<u64>,
+    /// Bumped on every clear, so that renders that started before one don't get kept.
+    generation: u64,
+    rendered: HashMap<String, Rendered>,
+}
+impl Entries {
+    fn clear(&mut self) {
+        self.generation += 1;
+        self.rendered.clear();
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RenderCache {
+    entries: Mutex<Entries>,
+    /// Wakes [`expire_scheduled`] when posts changed, in case one got scheduled.
+    changed: Notify,
+}
+impl RenderCache {
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        // Entries are only ever swapped in whole, so a poisoned lock is still usable.
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whatever's kept under `key`, rendering it with `render` if nothing is.
+    ///
+    /// Keys should hold whatever the rendering depends on, like the path and query.
+    pub async fn get_or_render<F>(
+        &self,
+        posts: &'static dyn PostRepository,
+        key: String,
+        render: impl FnOnce() -> F,
+    ) -> Result<Rendered, ErrorPage>
+    where
+        F: Future<Output = Result<Rendered, ErrorPage>>,
+    {
+        let version = blocking(move || Ok(posts.version()?)).await?;
+        let generation = {
+            let mut entries = self.lock();
+            if entries.version != Some(version) {
+                entries.clear();
+                entries.version = Some(version);
+                self.changed.notify_one();
+            }
+            if let Some(rendered) = entries.rendered.get(key.as_str()) {
+                return Ok(rendered.clone());
+            }
+            entries.generation
+        };
+
+        let rendered = render().await?;
+        let mut entries = self.lock();
+        if entries.generation == generation && entries.rendered.len() < MAX_ENTRIES {
+            entries.rendered.insert(key, rendered.clone());
+        }
+        Ok(rendered)
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// How many responses are kept right now.
+    #[cfg(test)]
+    pub fn kept(&self) -> usize {
+        self.lock().rendered.len()
+    }
+}
+
+/// Clears the cache the moment a scheduled post goes live, so that it shows up on every page,
+/// feed and sitemap at once. Runs for as long as the server does.
+pub async fn expire_scheduled(site: &'static Site) {
+    loop {
+        let posts: &'static dyn PostRepository = site.posts.as_ref();
+        let next = match blocking(move || Ok(posts.next_publication()?)).await {
+            Ok(next) => next,
+            // What went wrong was logged on the way into the error page.
+            Err(_) => {
+                trc::error!("Couldn't look up the next scheduled post");
+                // Try again later rather than never.
+                Some(Utc::now() + chrono::Duration::minutes(1))
+            },
+        };
+        let wait = next.map(|at| (at - Utc::now()).to_std().unwrap_or_default());
+        tokio::select! {
+            _ = tokio::time::sleep(wait.unwrap_or_default()), if wait.is_some() => {
+                trc::info!("A scheduled post is going live");
+                site.cache.clear();
+            },
+            _ = site.cache.changed.notified() => {},
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Renders error responses for every failing route.
+//!
+//! Handlers return an [`ErrorPage`] (or just a bare error status) and [`layer`] takes care of the
+//! rest: site chrome for browsers, a small JSON envelope for anything that asks for JSON.
+
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{Html, IntoResponse, Response},
+};
+use maud::{html, Markup};
+use serde::Serialize;
+use spb::{data::Robots, layout::SiteLayout};
+
+/// An error response, rendered as a full page unless the client prefers JSON.
+#[derive(Debug, Clone)]
+pub struct ErrorPage {
+    pub status: StatusCode,
+    /// Extra detail shown under the status-specific text.
+    pub message: Option<String>,
+}
+
+impl ErrorPage {
+    pub fn new(status: StatusCode) -> Self {
+        Self { status, message: None }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// The canonical reason phrase, falling back to a generic one for unregistered codes.
+    fn reason(&self) -> &'static str {
+        self.status.canonical_reason().unwrap_or("Error")
+    }
+
+    /// A human-friendly explanation for the status.
+    fn friendly_body(&self) -> Markup {
+        match self.status {
+            StatusCode::BAD_REQUEST => html! {
+                p { "That request didn't quite make sense to me." }
+            },
+            StatusCode::FORBIDDEN => html! {
+                p { "You're not allowed in here, sorry." }
+            },
+            StatusCode::NOT_FOUND => html! {
+                p { "You're probably looking to head back to " a href="/" { "the home page" } "..." }
+            },
+            StatusCode::METHOD_NOT_ALLOWED => html! {
+                p { "This page exists, but it can't do that." }
+            },
+            StatusCode::TOO_MANY_REQUESTS => html! {
+                p { "Slow down a little! Try again in a bit." }
+            },
+            StatusCode::SERVICE_UNAVAILABLE => html! {
+                p { "The site is taking a short break. Please check back soon." }
+            },
+            s if s.is_client_error() => html! {
+                p { "Something about that request wasn't right." }
+            },
+            _ => html! {
+                p { "Something went wrong..." }
+            },
+        }
+    }
+
+    pub fn render_html(&self) -> Html<String> {
+        let meta = SiteLayout::default().page()
+            .page_title(format!("{} {}", self.status.as_u16(), self.reason()))
+            .robots(Robots { index: false, follow: false });
+
+        let text = html! {
+            .error-page {
+                h1 { (self.status.as_u16()) " " (self.reason()) }
+                (self.friendly_body())
+                @if let Some(message) = self.message.as_ref() {
+                    p.error-message { (message) }
+                }
+            }
+        };
+
+        Html(meta.render(text).into_string())
+    }
+
+    pub fn render_json(&self) -> Response {
+        let body = ErrorBody {
+            status: self.status.as_u16(),
+            error: self.reason(),
+            message: self.message.as_deref(),
+        };
+        let json = serde_json::to_string(&body).expect("error body serializes");
+        let mut response = (self.status, json).into_response();
+        response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        response.extensions_mut().insert(self.clone());
+        response
+    }
+}
+
+impl From<blog_store::Error> for ErrorPage {
+    /// This server only ever reads posts, so anything but a missing post is on us.
+    fn from(e: blog_store::Error) -> Self {
+        match e {
+            blog_store::Error::NotFound => ErrorPage::new(StatusCode::NOT_FOUND).with_message("There's no post by that name."),
+            e => {
+                trc::error!("Post store error: {e}");
+                ErrorPage::new(StatusCode::INTERNAL_SERVER_ERROR)
+            },
+        }
+    }
+}
+
+impl IntoResponse for ErrorPage {
+    /// Renders HTML. [`layer`] swaps this out for JSON if the client asked for it.
+    fn into_response(self) -> Response {
+        let mut response = (self.status, self.render_html()).into_response();
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    status: u16,
+    error: &'a str,
+    message: Option<&'a str>,
+}
+
+/// Checks if the `Accept` header ranks JSON above HTML.
+pub fn wants_json(headers: &HeaderMap) -> bool {
+    let Some(accept) = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+
+    let mut json_q = 0f32;
+    let mut html_q = 0f32;
+    for entry in accept.split(',') {
+        let mut parts = entry.split(';').map(str::trim);
+        let media_type = parts.next().unwrap_or_default();
+        let q = parts
+            .find_map(|p| p.strip_prefix("q="))
+            .and_then(|q| q.parse().ok())
+            .unwrap_or(1f32);
+        match media_type {
+            "application/json" => json_q = json_q.max(q),
+            "text/html" => html_q = html_q.max(q),
+            _ => {},
+        }
+    }
+    json_q > html_q
+}
+
+/// Middleware rendering every error response through [`ErrorPage`].
+///
+/// Responses that already carry an [`ErrorPage`] only get converted to JSON if needed. Anything
+/// else with an error status (axum's own 405s, extractor rejections, bare status codes) gets
+/// replaced wholesale.
+pub async fn layer(req: Request<Body>, next: Next<Body>) -> Response {
+    let json = wants_json(req.headers());
+    let response = next.run(req).await;
+
+    let status = response.status();
+    if !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+
+    match response.extensions().get::<ErrorPage>().cloned() {
+        Some(page) if json => page.render_json(),
+        Some(_) => response,
+        None => {
+            trc::debug!("Rendering error page for bare {status} response.");
+            let page = ErrorPage::new(status);
+            let mut rendered = if json { page.render_json() } else { page.into_response() };
+            // Keep headers like `Allow` and `Retry-After` that give the status its meaning.
+            for (name, value) in response.headers() {
+                if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+                    rendered.headers_mut().insert(name.clone(), value.clone());
+                }
+            }
+            rendered
+        },
+    }
+}
@@ -1,8 +1,8 @@
-use axum::{http::{StatusCode, HeaderMap, HeaderValue}, response::Html};
+use axum::http::{StatusCode, HeaderMap, HeaderValue};
 
-use crate::internal_error;
+use crate::{error_page::ErrorPage, internal_error};
 
-pub async fn ico() -> Result<(StatusCode, HeaderMap, Vec<u8>), (StatusCode, Html<String>)> {
+pub async fn ico() -> Result<(StatusCode, HeaderMap, Vec<u8>), ErrorPage> {
     let path = "public/ico/favicon.ico";
     let Ok(data) = std::fs::read(path) else {
         return Err(internal_error::page().await);
@@ -13,7 +13,7 @@ pub async fn ico() -> Result<(StatusCode, HeaderMap, Vec<u8>), (StatusCode, Html
     Ok((StatusCode::OK, headers, data))
 }
 
-pub async fn svg() -> Result<(StatusCode, HeaderMap, Vec<u8>), (StatusCode, Html<String>)> {
+pub async fn svg() -> Result<(StatusCode, HeaderMap, Vec<u8>), ErrorPage> {
     let path = "public/svg/branding.svg";
     let Ok(data) = std::fs::read(path) else {
         return Err(internal_error::page().await);
@@ -0,0 +1,323 @@
+//! Atom and RSS 2.0 feeds for the blog.
+//!
+//! Both come in a site-wide flavor (`/blog/feed.xml`, `/blog/rss.xml`) and a per-tag flavor
+//! (`/blog/tag/<tag>/feed.xml`, `/blog/tag/<tag>/rss.xml`). Entries carry the full post unless
+//! `?mode=summary` is passed.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::Write,
+    hash::{Hash, Hasher},
+};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use blog::Route;
+use chrono::{DateTime, SecondsFormat, Utc};
+use blog_store::{ListQuery, PostRepository};
+use serde::Deserialize;
+use spb::data::Alternate;
+use yew_router::Routable;
+
+use crate::{blocking, cache::Rendered, error_page::ErrorPage, sitemap, xml::{self, escape}, Site};
+
+const ATOM_TYPE: &str = "application/atom+xml";
+const RSS_TYPE: &str = "application/rss+xml";
+
+const FEED_TITLE: &str = "Benjamin Xu's Blog";
+const FEED_DESCRIPTION: &str = "Posts about Rust, graphics and other side projects.";
+
+/// How many of the latest posts make it into a feed.
+const FEED_SIZE: usize = 20;
+
+/// A post, as far as the feeds are concerned.
+pub struct FeedPost {
+    pub slug: String,
+    pub title: String,
+    pub summary: Option<String>,
+    /// The rendered body of the post.
+    pub html: String,
+    pub tags: Vec<String>,
+    pub author: String,
+    pub published: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+}
+
+/// The latest published posts, newest first, optionally limited to a tag.
+fn published_posts(posts: &dyn PostRepository, tag: Option<&str>) -> blog_store::Result<Vec<FeedPost>> {
+    let query = ListQuery {
+        tag: tag.map(str::to_owned),
+        limit: FEED_SIZE,
+        ..ListQuery::default()
+    };
+    Ok(posts.list(&query)?.posts.into_iter()
+        .filter_map(|post| {
+            let published = post.published_at?;
+            let post = post.into_api();
+            Some(FeedPost {
+                slug: post.slug,
+                title: post.title,
+                summary: post.summary,
+                html: post.html,
+                tags: post.tags,
+                author: post.author,
+                published,
+                // Scheduled posts go live after their last save.
+                updated: post.updated_at.max(published),
+            })
+        })
+        .collect())
+}
+
+/// The `<link rel="alternate">`s advertising the feeds.
+pub fn alternates() -> [Alternate<'static>; 2] {
+    [
+        Alternate {
+            href: "/blog/feed.xml".into(),
+            media_type: ATOM_TYPE.into(),
+            title: Some(format!("{FEED_TITLE} (Atom)").into()),
+        },
+        Alternate {
+            href: "/blog/rss.xml".into(),
+            media_type: RSS_TYPE.into(),
+            title: Some(format!("{FEED_TITLE} (RSS)").into()),
+        },
+    ]
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    /// Entries carry the whole post.
+    #[default]
+    Full,
+    /// Entries only carry the summary, falling back to the full post when there isn't one.
+    Summary,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedQuery {
+    #[serde(default)]
+    mode: Mode,
+}
+
+/// Everything needed to render a feed.
+struct Feed<'a> {
+    origin: String,
+    /// Path of the feed itself.
+    path: String,
+    tag: Option<&'a str>,
+    mode: Mode,
+    posts: Vec<FeedPost>,
+}
+
+impl<'a> Feed<'a> {
+    fn new(site: &Site, path: String, tag: Option<&'a str>, mode: Mode) -> Result<Self, ErrorPage> {
+        Ok(Self {
+            origin: format!("https://{}", site.cfg.servers.primary.address.domain),
+            path,
+            tag,
+            mode,
+            posts: published_posts(site.posts.as_ref(), tag)?,
+        })
+    }
+
+    fn title(&self) -> String {
+        match self.tag {
+            Some(tag) => format!("{FEED_TITLE}: {tag}"),
+            None => FEED_TITLE.to_owned(),
+        }
+    }
+
+    fn post_url(&self, post: &FeedPost) -> String {
+        format!("{}/blog/post/{}", self.origin, post.slug)
+    }
+
+    /// The most recent update to the feed, used for `Last-Modified` and the feed's own timestamps.
+    fn updated(&self) -> DateTime<Utc> {
+        self.posts.iter()
+            .map(|p| p.updated)
+            .max()
+            .or_else(sitemap::deployed_at)
+            .unwrap_or_else(Utc::now)
+    }
+
+    fn body<'p>(&self, post: &'p FeedPost) -> &'p str {
+        match self.mode {
+            Mode::Full => post.html.as_str(),
+            Mode::Summary => post.summary.as_deref().unwrap_or(post.html.as_str()),
+        }
+    }
+
+    fn atom(&self) -> String {
+        let mut out = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        out.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+        let _ = write!(out, "<title>{}</title>", escape(self.title().as_str()));
+        let _ = write!(out, "<subtitle>{}</subtitle>", escape(FEED_DESCRIPTION));
+        let _ = write!(out, "<id>{}{}</id>", escape(self.origin.as_str()), escape(self.path.as_str()));
+        let _ = write!(out, r#"<link rel="self" type="{ATOM_TYPE}" href="{}{}"/>"#, escape(self.origin.as_str()), escape(self.path.as_str()));
+        let _ = write!(out, r#"<link rel="alternate" type="text/html" href="{}/blog"/>"#, escape(self.origin.as_str()));
+        let _ = write!(out, "<updated>{}</updated>", self.updated().to_rfc3339_opts(SecondsFormat::Secs, true));
+        for post in self.posts.iter() {
+            let url = escape(self.post_url(post).as_str());
+            out.push_str("<entry>");
+            let _ = write!(out, "<title>{}</title>", escape(post.title.as_str()));
+            let _ = write!(out, "<id>{url}</id>");
+            let _ = write!(out, r#"<link rel="alternate" type="text/html" href="{url}"/>"#);
+            let _ = write!(out, "<published>{}</published>", post.published.to_rfc3339_opts(SecondsFormat::Secs, true));
+            let _ = write!(out, "<updated>{}</updated>", post.updated.to_rfc3339_opts(SecondsFormat::Secs, true));
+            let _ = write!(out, "<author><name>{}</name></author>", escape(post.author.as_str()));
+            for tag in post.tags.iter() {
+                let _ = write!(out, r#"<category term="{}"/>"#, escape(tag.as_str()));
+            }
+            if let Some(summary) = post.summary.as_ref() {
+                let _ = write!(out, r#"<summary type="html">{}</summary>"#, escape(summary.as_str()));
+            }
+            let _ = write!(out, r#"<content type="html">{}</content>"#, escape(self.body(post)));
+            out.push_str("</entry>");
+        }
+        out.push_str("</feed>");
+        out
+    }
+
+    fn rss(&self) -> String {
+        let mut out = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        out.push_str(r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:dc="http://purl.org/dc/elements/1.1/">"#);
+        out.push_str("<channel>");
+        let _ = write!(out, "<title>{}</title>", escape(self.title().as_str()));
+        let _ = write!(out, "<link>{}/blog</link>", escape(self.origin.as_str()));
+        let _ = write!(out, "<description>{}</description>", escape(FEED_DESCRIPTION));
+        let _ = write!(out, r#"<atom:link rel="self" type="{RSS_TYPE}" href="{}{}"/>"#, escape(self.origin.as_str()), escape(self.path.as_str()));
+        let _ = write!(out, "<lastBuildDate>{}</lastBuildDate>", self.updated().to_rfc2822());
+        for post in self.posts.iter() {
+            let url = escape(self.post_url(post).as_str());
+            out.push_str("<item>");
+            let _ = write!(out, "<title>{}</title>", escape(post.title.as_str()));
+            let _ = write!(out, "<link>{url}</link>");
+            let _ = write!(out, r#"<guid isPermaLink="true">{url}</guid>"#);
+            let _ = write!(out, "<pubDate>{}</pubDate>", post.published.to_rfc2822());
+            let _ = write!(out, "<dc:creator>{}</dc:creator>", escape(post.author.as_str()));
+            for tag in post.tags.iter() {
+                let _ = write!(out, "<category>{}</category>", escape(tag.as_str()));
+            }
+            let _ = write!(out, "<description>{}</description>", escape(self.body(post)));
+            out.push_str("</item>");
+        }
+        out.push_str("</channel></rss>");
+        out
+    }
+}
+
+/// Formats a timestamp the way HTTP headers expect.
+fn http_date(date: DateTime<Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Responds with the feed, or a 304 if the client's copy is still current.
+///
+/// `If-None-Match` wins over `If-Modified-Since` when both are present, as the spec asks.
+fn conditional(headers: &HeaderMap, body: String, last_modified: DateTime<Utc>, media_type: &'static str) -> Response {
+    let etag = {
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        format!("\"{:016x}\"", hasher.finish())
+    };
+
+    let not_modified = if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) {
+        if_none_match.split(',').map(str::trim).any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+    } else if let Some(if_modified_since) = headers.get(header::IF_MODIFIED_SINCE).and_then(|v| v.to_str().ok()) {
+        DateTime::parse_from_rfc2822(if_modified_since)
+            // Headers only have second precision.
+            .map(|since| last_modified.timestamp() <= since.timestamp())
+            .unwrap_or(false)
+    } else {
+        false
+    };
+
+    let mut response = if not_modified {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        xml::response(body, media_type)
+    };
+    let headers = response.headers_mut();
+    headers.insert(header::ETAG, HeaderValue::from_str(etag.as_str()).expect("etag is a valid header"));
+    headers.insert(header::LAST_MODIFIED, HeaderValue::from_str(http_date(last_modified).as_str()).expect("date is a valid header"));
+    response
+}
+
+/// Renders a feed, or reuses the last rendering of it while the posts stay the same.
+///
+/// Kept by path and mode only, the rest of the query string doesn't change the feed.
+async fn cached(
+    site: &'static Site,
+    uri: &Uri,
+    mode: Mode,
+    headers: &HeaderMap,
+    media_type: &'static str,
+    render: impl FnOnce() -> Result<(String, DateTime<Utc>), ErrorPage> + Send + 'static,
+) -> Result<Response, ErrorPage> {
+    let rendered = site.cache.get_or_render(site.posts.as_ref(), format!("{} {mode:?}", uri.path()), move || async move {
+        let (body, updated) = blocking(render).await?;
+        Ok::<_, ErrorPage>(Rendered { body, updated: Some(updated) })
+    }).await?;
+    Ok(conditional(headers, rendered.body, rendered.updated.unwrap_or_default(), media_type))
+}
+
+pub async fn atom(
+    State(site): State<&'static Site>,
+    Query(query): Query<FeedQuery>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Result<Response, ErrorPage> {
+    cached(site, &uri, query.mode, &headers, ATOM_TYPE, move || {
+        let feed = Feed::new(site, "/blog/feed.xml".to_owned(), None, query.mode)?;
+        Ok((feed.atom(), feed.updated()))
+    }).await
+}
+
+pub async fn rss(
+    State(site): State<&'static Site>,
+    Query(query): Query<FeedQuery>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Result<Response, ErrorPage> {
+    cached(site, &uri, query.mode, &headers, RSS_TYPE, move || {
+        let feed = Feed::new(site, "/blog/rss.xml".to_owned(), None, query.mode)?;
+        Ok((feed.rss(), feed.updated()))
+    }).await
+}
+
+/// Where the feed `file` for `tag` lives, with the tag percent-encoded the same way links to its
+/// page are.
+fn tag_feed_path(tag: &str, file: &str) -> String {
+    format!("{}/{file}", Route::Tag { tag: tag.to_owned() }.to_path())
+}
+
+pub async fn tag_atom(
+    State(site): State<&'static Site>,
+    Path(tag): Path<String>,
+    Query(query): Query<FeedQuery>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Result<Response, ErrorPage> {
+    cached(site, &uri, query.mode, &headers, ATOM_TYPE, move || {
+        let feed = Feed::new(site, tag_feed_path(tag.as_str(), "feed.xml"), Some(tag.as_str()), query.mode)?;
+        Ok((feed.atom(), feed.updated()))
+    }).await
+}
+
+pub async fn tag_rss(
+    State(site): State<&'static Site>,
+    Path(tag): Path<String>,
+    Query(query): Query<FeedQuery>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Result<Response, ErrorPage> {
+    cached(site, &uri, query.mode, &headers, RSS_TYPE, move || {
+        let feed = Feed::new(site, tag_feed_path(tag.as_str(), "rss.xml"), Some(tag.as_str()), query.mode)?;
+        Ok((feed.rss(), feed.updated()))
+    }).await
+}
@@ -1,33 +1,25 @@
-use axum::{http::StatusCode, response::Html};
-use maud::html;
-use spb::{data::{PageMetaData, Css, Menu, MenuItem, Logo, Favicon}, partials::basic_page};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
 
-pub async fn page() -> (StatusCode, Html<String>) {
-    let meta = PageMetaData {
-        scripts: &[],
-        css: &[
-            Css::Critical { src: "public/css/reset.css" },
-            Css::Critical { src: "public/css/typography.css" },
-            Css::Critical { src: "public/css/main.css" },
-        ],
-        menu: Some(&Menu(&[MenuItem {
-            text: "Blog",
-            link: Some("/blog"),
-            children: None,
-        }])),
-        logo: Some(&Logo {
-            src: "public/svg/branding.svg",
-            href: Some("/"),
-        }),
-        favicons: &[Favicon {
-            link: "public/svg/favicon.svg",
-            media_type: None,
-            sizes: None,
-        }],
-        ..PageMetaData::default()
-    };
+use crate::error_page::{self, ErrorPage};
 
-    let text = html! { p { "Something went wrong..." } };
+pub async fn page() -> ErrorPage {
+    ErrorPage::new(StatusCode::INTERNAL_SERVER_ERROR)
+}
 
-    (StatusCode::INTERNAL_SERVER_ERROR, Html(basic_page(text, Some(&meta)).into_string()))
+/// Middleware answering panics with the 500 page.
+///
+/// This sits outside [`error_page::layer`] so that a panic while rendering the error page itself
+/// still gets an (admittedly bare) 500.
+pub async fn catch_panic(req: Request<Body>, next: Next<Body>) -> Response {
+    let json = error_page::wants_json(req.headers());
+    catch_panic::catch(req, next, move || async move {
+        let page = page().await;
+        std::panic::catch_unwind(|| if json { page.render_json() } else { page.into_response() })
+            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+    }).await
 }
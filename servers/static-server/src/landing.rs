@@ -3,61 +3,51 @@
 use axum::response::Html;
 use maud::{html, Markup, Render};
 
-use spb::{data::{Script, Css, MenuItem, Menu, Logo, LogoLink, PageMetaData, Favicon}, partials::basic_page};
+use spb::{data::{Script, Css, LogoLink, OgKind}, schema::{Person, WebSite}};
 
-pub async fn page() -> Html<String> {
+use crate::page_context::PageContext;
+
+pub async fn page(ctx: PageContext) -> Html<String> {
     trc::info!("build-start page=landing");
     let (glue, load) = Script::wasm_bindgen_loader("public/js", "public/wasm", "slideshow");
-    let meta = PageMetaData {
-        scripts: &[
-            Script::External(glue.as_str()),
-            Script::Embedded(load.as_str()),
-        ],
-        css: &[
-            Css::Critical { src: "public/css/reset.css" },
-            Css::Critical { src: "public/css/typography.css" },
-            Css::Critical { src: "public/css/main.css" },
-            Css::Critical { src: "public/css/index.css" },
-        ],
-        menu: Some(&Menu(&[MenuItem {
-            text: "Blog",
-            link: Some("/blog"),
-            children: None,
-        }])),
-        logo: Some(&Logo {
-            src: "public/svg/branding.svg",
-            href: Some("/"),
-        }),
-        favicons: &[Favicon {
-            link: "favicon.svg",
-            media_type: None,
-            sizes: None,
-        }],
-        ..PageMetaData::default()
-    };
-
-
     let links = Links([
         LogoLink {
-            url: "https://github.com/AlterionX/",
-            logo: "public/png/github.png",
-            alt_text: "Github",
-            text: "AlterionX",
+            url: "https://github.com/AlterionX/".into(),
+            logo: "public/png/github.png".into(),
+            alt_text: "Github".into(),
+            text: "AlterionX".into(),
         },
         LogoLink {
-            url: "mailto:ben.xu.cs@gmail.com",
-            logo: "public/svg/email.svg",
-            alt_text: "Email",
-            text: "ben.xu.cs@gmail.com",
+            url: "mailto:ben.xu.cs@gmail.com".into(),
+            logo: "public/svg/email.svg".into(),
+            alt_text: "Email".into(),
+            text: "ben.xu.cs@gmail.com".into(),
         },
         LogoLink {
-            url: "/resume",
-            logo: "public/svg/resume.svg",
-            alt_text: "Resume",
-            text: "Resume",
+            url: "/resume".into(),
+            logo: "public/svg/resume.svg".into(),
+            alt_text: "Resume".into(),
+            text: "Resume".into(),
         },
     ]);
 
+    let meta = ctx.meta()
+        .social(ctx.social().kind(OgKind::Profile))
+        .css(Css::critical("public/css/index.css"))
+        .script(glue)
+        .script(load);
+    let me = Person::from(&meta.copyright.name)
+        .url(ctx.absolute("/"))
+        .image(ctx.absolute("/public/jpg/propic.jpg"))
+        .links(links.0.iter());
+    let site = WebSite {
+        name: meta.title.clone(),
+        url: ctx.absolute("/").into(),
+        description: Some(meta.description.clone()),
+        author: Some(Box::new(me.clone())),
+    };
+    let meta = meta.structured_data(site).structured_data(me);
+
     let slides = Slides([
         my_intro(),
         my_story(),
@@ -68,7 +58,7 @@ pub async fn page() -> Html<String> {
         my_gaming_time(),
     ]);
 
-    let built_page = basic_page(
+    let built_page = meta.render(
         html! {
             div.profile {
                 h1.tagline { "Ben Xu | Developer" }
@@ -77,7 +67,6 @@ pub async fn page() -> Html<String> {
                 (slides)
             }
         },
-        Some(&meta),
     ).into_string().into();
 
     trc::info!("build-end route=landing");
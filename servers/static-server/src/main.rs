@@ -1,16 +1,88 @@
 use std::{env::args, net::SocketAddr};
 
-use axum::{Router, routing::get};
+use axum::{Router, extract::FromRef, http::StatusCode, middleware, routing::get};
+use blog_store::{PostRepository, SqlitePostStore};
 use shared_config::Cfg;
 
 mod landing;
 mod resume;
 mod blog;
+mod feed;
+mod projects;
+mod sitemap;
+mod xml;
 mod favicon;
 mod static_file;
 
+mod error_page;
 mod not_found;
+mod page_context;
+mod cache;
 mod internal_error;
+#[cfg(test)]
+mod tests;
+
+/// Everything handlers need, leaked for the lifetime of the server.
+pub struct Site {
+    pub cfg: &'static Cfg,
+    pub posts: Box<dyn PostRepository>,
+    pub cache: cache::RenderCache,
+}
+
+/// Runs `f` on a thread that's allowed to block, since the store does. Panics carry on from here,
+/// for the panic middleware to catch.
+pub async fn blocking<T, F>(f: F) -> Result<T, error_page::ErrorPage>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, error_page::ErrorPage> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(_) => Err(error_page::ErrorPage::new(StatusCode::INTERNAL_SERVER_ERROR)),
+    }
+}
+
+impl FromRef<&'static Site> for &'static Cfg {
+    fn from_ref(site: &&'static Site) -> Self {
+        site.cfg
+    }
+}
+
+/// Every page, feed and file the server has.
+fn app(site: &'static Site) -> Router {
+    Router::new()
+        // Starting points
+        .route("/", get(landing::page))
+        .route("/blog", get(blog::page))
+        .route("/blog/*path", get(blog::page))
+        .route("/blog/feed.xml", get(feed::atom))
+        .route("/blog/rss.xml", get(feed::rss))
+        .route("/blog/tag/:tag/feed.xml", get(feed::tag_atom))
+        .route("/blog/tag/:tag/rss.xml", get(feed::tag_rss))
+        .route("/projects", get(projects::page))
+        .route("/projects/:project", get(projects::project::page))
+        // Crawlers
+        .route("/robots.txt", get(sitemap::robots))
+        .route("/sitemap.xml", get(sitemap::index))
+        .route("/sitemaps/:file", get(sitemap::part))
+        // Static files
+        .route("/favicon.svg", get(favicon::svg))
+        .route("/favicon.ico", get(favicon::ico))
+        .route("/resume", get(resume::file))
+        .route("/public/css/highlight.css", get(static_file::highlight_css))
+        .route("/public/css/:file", get(static_file::css))
+        .route("/public/js/:file", get(static_file::js))
+        .route("/public/wasm/:file", get(static_file::wasm))
+        .route("/public/png/:file", get(static_file::png))
+        .route("/public/jpg/:file", get(static_file::jpg))
+        .route("/public/svg/:file", get(static_file::svg))
+        // Error paths
+        .fallback(not_found::page)
+        .layer(middleware::from_fn(error_page::layer))
+        .layer(middleware::from_fn(internal_error::catch_panic))
+        .with_state(site)
+}
 
 #[tokio::main]
 async fn main() {
@@ -19,7 +91,7 @@ async fn main() {
         let _ = args.next().expect("first value to be present");
         args.next().expect("config path to be present as first argument")
     };
-    let cfg = {
+    let cfg: &'static Cfg = {
         let temp: Cfg = config::Config::builder()
             .add_source(config::File::with_name(root_config.as_str()))
             .add_source(config::Environment::with_prefix("BENXU_DEV"))
@@ -30,26 +102,18 @@ async fn main() {
 
     // Set up logging.
     tracing_subscriber::fmt().init();
+    catch_panic::install_hook();
     trc::info!("Spinning up! (pwd: {:?})", std::env::current_dir().expect("existing directory").as_os_str());
 
-    let app = Router::new()
-        // Starting points
-        .route("/", get(landing::page))
-        .route("/blog", get(blog::page))
-        .route("/blog/*path", get(blog::page))
-        // Static files
-        .route("/favicon.svg", get(favicon::svg))
-        .route("/favicon.ico", get(favicon::ico))
-        .route("/resume", get(resume::file))
-        .route("/public/css/:file", get(static_file::css))
-        .route("/public/js/:file", get(static_file::js))
-        .route("/public/wasm/:file", get(static_file::wasm))
-        .route("/public/png/:file", get(static_file::png))
-        .route("/public/jpg/:file", get(static_file::jpg))
-        .route("/public/svg/:file", get(static_file::svg))
-        // Error paths
-        .fallback(not_found::page);
+    // Shared with the api server, which does all the writing.
+    let site: &'static Site = Box::leak(Box::new(Site {
+        cfg,
+        posts: Box::new(SqlitePostStore::open(cfg.database.path.as_str()).expect("database opens")),
+        cache: cache::RenderCache::default(),
+    }));
+    tokio::spawn(cache::expire_scheduled(site));
 
+    let app = app(site);
 
     let server = axum::Server::bind(&SocketAddr::new(cfg.servers.primary.address.ip, cfg.servers.primary.address.port))
         .serve(app.into_make_service());
@@ -0,0 +1,78 @@
+//! Per-request information that pages need to fill in their metadata.
+
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts, Host},
+    http::request::Parts,
+};
+use shared_config::Cfg;
+use spb::{data::{OgImage, PageMetaData, Robots, Social}, layout::SiteLayout};
+
+/// Where the page lives, both canonically and as requested.
+pub struct PageContext {
+    /// The scheme and primary domain, without a trailing slash.
+    pub origin: String,
+    /// The URL of the page on the primary domain.
+    pub canonical: String,
+    /// Whether the request came in through one of the alternate domains.
+    pub alternate: bool,
+}
+
+impl PageContext {
+    /// Metadata for the page, starting from the site's layout.
+    pub fn meta(&self) -> PageMetaData<'static> {
+        let meta = SiteLayout::default().page()
+            .canonical(self.canonical.clone())
+            .social(self.social());
+        if self.alternate {
+            meta.robots(Robots { index: false, follow: true })
+        } else {
+            meta
+        }
+    }
+
+    /// The social metadata shared by most pages. Pages with their own image replace it.
+    pub fn social(&self) -> Social<'static> {
+        Social::default()
+            .site_name("Benjamin Xu")
+            .image(OgImage {
+                url: self.absolute("/public/jpg/propic.jpg").into(),
+                width: Some(2500),
+                height: Some(1669),
+                alt: Some("Ben Xu".into()),
+            })
+    }
+
+    /// Turns a path into an absolute URL on the primary domain.
+    pub fn absolute(&self, path: &str) -> String {
+        format!("{}{path}", self.origin)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for PageContext
+where
+    S: Send + Sync,
+    &'static Cfg: FromRef<S>,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let cfg = <&'static Cfg>::from_ref(state);
+        let address = &cfg.servers.primary.address;
+        let host = Host::from_request_parts(parts, state).await.ok().map(|Host(host)| host);
+        // Drop the port and any `www.` so that they compare cleanly against the config.
+        let alternate = host.as_deref()
+            .map(|host| host.split(':').next().unwrap_or(host))
+            .map(|host| host.strip_prefix("www.").unwrap_or(host))
+            .map(|host| address.alternate_domains.iter().any(|d| d == host))
+            .unwrap_or(false);
+
+        let origin = format!("https://{}", address.domain);
+        Ok(Self {
+            canonical: format!("{origin}{}", parts.uri.path()),
+            origin,
+            alternate,
+        })
+    }
+}
@@ -2,17 +2,40 @@
 //!
 //!
 
-use rocket::{
-    http::{RawStr, Status},
-    request::FromParam,
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
 };
-use std::fmt::{self, Display, Formatter};
 
-/// Returns the "projects" page, which will have brief demos or videos (eventually). Not yet
-/// implemented.
-#[get("/projects")]
-pub fn get() -> Status {
-    Status::NotImplemented
+use axum::response::Html;
+use maud::html;
+use spb::data::{Css, OgImage};
+
+use crate::page_context::PageContext;
+
+/// Returns the "projects" page, which lists my projects. Brief demos or videos will (eventually)
+/// live on the pages of each project.
+pub async fn page(ctx: PageContext) -> Html<String> {
+    let meta = ctx.meta()
+        .page_title("Projects")
+        .description("Things Ben Xu has built: ray tracers, simulations, engines and compilers.")
+        .css(Css::critical("public/css/index.css"));
+
+    meta.render(
+        html! {
+            .projects {
+                h1 { "Projects" }
+                ul.project-list {
+                    @for project in Project::ALL {
+                        li.project-item {
+                            h2 { a href={ "/projects/" (project) } { (project.name()) } }
+                            p { (project.summary()) }
+                        }
+                    }
+                }
+            }
+        },
+    ).into_string().into()
 }
 
 /// Enum representing all possible projects to query for.
@@ -40,10 +63,70 @@ pub enum Project {
     /// Can be found on [github](https://github.com/AlterionX/Bifrost).
     Bifrost,
 }
-impl<'a> FromParam<'a> for Project {
-    type Error = ();
-    fn from_param(param: &'a RawStr) -> Result<Self, Self::Error> {
-        match &*(param.percent_decode().map_err(|_| ())?) {
+impl Project {
+    pub const ALL: [Project; 5] = [
+        Project::RayTracer,
+        Project::ThermalLilette,
+        Project::Totality,
+        Project::Shatter,
+        Project::Bifrost,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Project::RayTracer => "Ray Tracer",
+            Project::ThermalLilette => "Thermal Lilette",
+            Project::Totality => "Totality",
+            Project::Shatter => "Shatter",
+            Project::Bifrost => "Bifrost",
+        }
+    }
+
+    pub fn summary(&self) -> &'static str {
+        match self {
+            Project::RayTracer => "A simple ray tracer, written with C++ and FLTK.",
+            Project::ThermalLilette => "A gas simulation model, written with C++, GLFW, GLUT, and OpenGL.",
+            Project::Totality => "\
+                A graphics/physics engine aiming to model the shattering of trimeshes and tearing of \
+                soft bodies, written with Rust, and gfx-hal.\
+            ",
+            Project::Shatter => "A physics engine modeling the shattering of trimeshes.",
+            Project::Bifrost => "A modular compiler.",
+        }
+    }
+
+    /// The owner and name of the repository on github.
+    fn repository_name(&self) -> &'static str {
+        match self {
+            Project::RayTracer => "AlterionX/cs378hgraphics-raytracer",
+            Project::ThermalLilette => "AlterionX/thermal-lilette",
+            Project::Totality => "AlterionX/totality-rs",
+            Project::Shatter => "AlterionX/physical-sim",
+            Project::Bifrost => "AlterionX/Bifrost",
+        }
+    }
+
+    pub fn repository(&self) -> String {
+        match self {
+            Project::Shatter => format!("https://github.com/{}/tree/master/final-project", self.repository_name()),
+            _ => format!("https://github.com/{}", self.repository_name()),
+        }
+    }
+
+    /// The preview image github generates for the repository.
+    pub fn image(&self) -> OgImage<'static> {
+        OgImage {
+            url: format!("https://opengraph.githubassets.com/1/{}", self.repository_name()).into(),
+            width: Some(1200),
+            height: Some(600),
+            alt: Some(format!("The {} repository on github", self.name()).into()),
+        }
+    }
+}
+impl FromStr for Project {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
             "ray-tracer" => Ok(Project::RayTracer),
             "totality" => Ok(Project::Totality),
             "shatter" => Ok(Project::Shatter),
@@ -60,22 +143,52 @@ impl Display for Project {
             Project::ThermalLilette => write!(f, "thermal-lilette"),
             Project::Totality => write!(f, "totality"),
             Project::Shatter => write!(f, "shatter"),
-            Project::Bifrost => write!(f, "Bifrost"),
+            Project::Bifrost => write!(f, "bifrost"),
         }
     }
 }
 
 /// All handlers for handling per-project information.
 pub mod project {
+    use axum::{extract::Path, http::StatusCode, response::Html};
+    use maud::html;
+    use spb::{data::{Css, TwitterCard}, schema::{BreadcrumbList, CreativeWork, Person}};
+
     use super::Project;
-    use rocket::http::Status;
+    use crate::{error_page::ErrorPage, page_context::PageContext};
 
     /// Retrieve information for an individual project.
-    #[get("/projects/<_project>")]
-    pub fn get(_project: Project) -> Status {
-        Status::NotImplemented
+    pub async fn page(ctx: PageContext, Path(project): Path<String>) -> Result<Html<String>, ErrorPage> {
+        let Ok(project) = project.parse::<Project>() else {
+            return Err(ErrorPage::new(StatusCode::NOT_FOUND).with_message("I don't have a project by that name."));
+        };
+
+        let meta = ctx.meta()
+            .page_title(project.name())
+            .description(project.summary())
+            .css(Css::critical("public/css/index.css"))
+            .social(ctx.social().image(project.image()).twitter_card(TwitterCard::SummaryLargeImage));
+        let work = CreativeWork {
+            name: project.name().into(),
+            url: ctx.canonical.clone().into(),
+            description: Some(project.summary().into()),
+            image: Some(project.image().url),
+            author: Some(Box::new(Person::from(&meta.copyright.name).url(ctx.absolute("/")))),
+            same_as: vec![project.repository().into()],
+        };
+        let crumbs = BreadcrumbList::default()
+            .crumb("Projects", ctx.absolute("/projects"))
+            .crumb(project.name(), ctx.canonical.clone());
+        let meta = meta.structured_data(work).structured_data(crumbs);
+
+        Ok(meta.render(
+            html! {
+                .project {
+                    h1 { (project.name()) }
+                    p { (project.summary()) }
+                    p { "Can be found on " a href=(project.repository()) { "github" } "." }
+                }
+            },
+        ).into_string().into())
     }
 }
-
-/// Functions generating a complete list of my projects. Not yet implemented.
-pub mod htmlgen {}
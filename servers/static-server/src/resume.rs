@@ -1,8 +1,8 @@
-use axum::{http::{StatusCode, HeaderMap, HeaderValue}, response::Html};
+use axum::http::{StatusCode, HeaderMap, HeaderValue};
 
-use crate::internal_error;
+use crate::{error_page::ErrorPage, internal_error};
 
-pub async fn file() -> Result<(StatusCode, HeaderMap, Vec<u8>), (StatusCode, Html<String>)> {
+pub async fn file() -> Result<(StatusCode, HeaderMap, Vec<u8>), ErrorPage> {
     let path = "public/resume/resume.pdf";
     let Ok(data) = std::fs::read(path) else {
         return Err(internal_error::page().await);
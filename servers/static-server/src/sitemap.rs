@@ -0,0 +1,166 @@
+//! Generates `/sitemap.xml` and `/robots.txt`.
+//!
+//! Sitemaps are capped at [`URLS_PER_SITEMAP`] entries. Past that `/sitemap.xml` turns into a
+//! sitemap index pointing at `/sitemaps/<n>.xml`. Both are kept in the [render cache](crate::cache).
+
+use std::{collections::BTreeSet, fmt::Write, sync::OnceLock};
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use blog::Route;
+use blog_store::{ListQuery, PostRepository};
+use chrono::{DateTime, SecondsFormat, Utc};
+use shared_config::Cfg;
+use yew_router::Routable;
+
+use crate::{blocking, cache::Rendered, error_page::ErrorPage, projects::Project, xml::{self, escape}, Site};
+
+/// The most URLs the sitemap protocol allows in one file.
+const URLS_PER_SITEMAP: usize = 50_000;
+
+const MEDIA_TYPE: &str = "application/xml; charset=utf-8";
+
+/// A `<url>` in a sitemap.
+pub struct Entry {
+    /// The path of the page, starting with a `/`.
+    pub path: String,
+    pub lastmod: Option<DateTime<Utc>>,
+}
+
+/// When this build of the server was deployed. Static pages only change on deployment, so this
+/// doubles as their last modification time.
+pub fn deployed_at() -> Option<DateTime<Utc>> {
+    static DEPLOYED_AT: OnceLock<Option<DateTime<Utc>>> = OnceLock::new();
+    *DEPLOYED_AT.get_or_init(|| {
+        let modified = std::env::current_exe().ok()?.metadata().ok()?.modified().ok()?;
+        Some(modified.into())
+    })
+}
+
+/// Every published post, paging through the store.
+fn posts(posts: &dyn PostRepository) -> blog_store::Result<Vec<Entry>> {
+    let mut entries = vec![];
+    let mut series = BTreeSet::new();
+    let mut query = ListQuery { limit: 500, ..ListQuery::default() };
+    loop {
+        let page = posts.list(&query)?;
+        entries.extend(page.posts.iter().map(|post| Entry {
+            path: format!("/blog/post/{}", post.slug),
+            // Scheduled posts go live after their last save.
+            lastmod: Some(post.published_at.map_or(post.updated_at, |at| at.max(post.updated_at))),
+        }));
+        series.extend(page.posts.into_iter().filter_map(|post| post.series).map(|series| series.slug));
+        match page.next {
+            Some(next) => query.after = Some(next),
+            None => break,
+        }
+    }
+    // Listings change with every post, so they go without a date.
+    let series = series.into_iter().map(|slug| Entry { path: Route::Series { slug }.to_path(), lastmod: None });
+    let tags = posts.tags()?.into_iter()
+        .map(|tag| Entry { path: Route::Tag { tag: tag.tag }.to_path(), lastmod: None });
+    Ok(entries.into_iter().chain(series).chain(tags).collect())
+}
+
+/// Every page that should be in the sitemap.
+fn entries(site: &Site) -> blog_store::Result<Vec<Entry>> {
+    let lastmod = deployed_at();
+    let static_pages = ["/", "/blog", "/projects", "/resume"]
+        .into_iter()
+        .map(|path| Entry { path: path.to_owned(), lastmod });
+    let projects = Project::ALL
+        .into_iter()
+        .map(|project| Entry { path: format!("/projects/{project}"), lastmod });
+    Ok(static_pages.chain(projects).chain(posts(site.posts.as_ref())?).collect())
+}
+
+fn origin(cfg: &Cfg) -> String {
+    format!("https://{}", cfg.servers.primary.address.domain)
+}
+
+fn urlset(origin: &str, entries: &[Entry]) -> String {
+    let mut out = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    out.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+    for entry in entries {
+        let _ = write!(out, "<url><loc>{}</loc>", escape(format!("{origin}{}", entry.path).as_str()));
+        if let Some(lastmod) = entry.lastmod {
+            let _ = write!(out, "<lastmod>{}</lastmod>", lastmod.to_rfc3339_opts(SecondsFormat::Secs, true));
+        }
+        out.push_str("</url>");
+    }
+    out.push_str("</urlset>");
+    out
+}
+
+fn sitemap_index(origin: &str, chunks: std::slice::Chunks<'_, Entry>) -> String {
+    let mut out = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    out.push_str(r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+    for (idx, chunk) in chunks.enumerate() {
+        let _ = write!(out, "<sitemap><loc>{}</loc>", escape(format!("{origin}/sitemaps/{}.xml", idx + 1).as_str()));
+        if let Some(lastmod) = chunk.iter().filter_map(|e| e.lastmod).max() {
+            let _ = write!(out, "<lastmod>{}</lastmod>", lastmod.to_rfc3339_opts(SecondsFormat::Secs, true));
+        }
+        out.push_str("</sitemap>");
+    }
+    out.push_str("</sitemapindex>");
+    out
+}
+
+/// Renders a sitemap, or reuses the last rendering of it while the posts stay the same.
+async fn cached(
+    site: &'static Site,
+    key: String,
+    render: impl FnOnce() -> Result<String, ErrorPage> + Send + 'static,
+) -> Result<Response, ErrorPage> {
+    let rendered = site.cache.get_or_render(site.posts.as_ref(), key, move || async move {
+        Ok(Rendered { body: blocking(render).await?, updated: None })
+    }).await?;
+    Ok(xml::response(rendered.body, MEDIA_TYPE))
+}
+
+/// The sitemap, or a sitemap index if there are too many pages for one file.
+pub async fn index(State(site): State<&'static Site>) -> Result<Response, ErrorPage> {
+    cached(site, "/sitemap.xml".to_owned(), move || {
+        let entries = entries(site)?;
+        let origin = origin(site.cfg);
+        Ok(if entries.len() <= URLS_PER_SITEMAP {
+            urlset(origin.as_str(), entries.as_slice())
+        } else {
+            sitemap_index(origin.as_str(), entries.chunks(URLS_PER_SITEMAP))
+        })
+    }).await
+}
+
+/// One part of a split sitemap, numbered from 1.
+pub async fn part(State(site): State<&'static Site>, Path(file): Path<String>) -> Result<Response, ErrorPage> {
+    let Some(page) = file.strip_suffix(".xml").and_then(|n| n.parse::<usize>().ok()) else {
+        return Err(ErrorPage::new(StatusCode::NOT_FOUND));
+    };
+    cached(site, format!("/sitemaps/{page}.xml"), move || {
+        let entries = entries(site)?;
+        let Some(chunk) = page.checked_sub(1).and_then(|idx| entries.chunks(URLS_PER_SITEMAP).nth(idx)) else {
+            return Err(ErrorPage::new(StatusCode::NOT_FOUND));
+        };
+        Ok(urlset(origin(site.cfg).as_str(), chunk))
+    }).await
+}
+
+/// `robots.txt`, built from the config and pointing at the sitemap.
+pub async fn robots(State(cfg): State<&'static Cfg>) -> Response {
+    let robots = &cfg.servers.primary.robots;
+    let mut out = String::from("User-agent: *\n");
+    for path in robots.disallow.iter() {
+        let _ = writeln!(out, "Disallow: {path}");
+    }
+    if let Some(delay) = robots.crawl_delay {
+        let _ = writeln!(out, "Crawl-delay: {delay}");
+    }
+    let _ = writeln!(out, "\nSitemap: {}/sitemap.xml", origin(cfg));
+
+    let mut response = (StatusCode::OK, out).into_response();
+    response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain; charset=utf-8"));
+    response
+}
@@ -2,11 +2,11 @@ use std::path::PathBuf;
 
 use sanitise_file_name::sanitize;
 
-use axum::{http::{HeaderMap, StatusCode, HeaderValue}, response::Html};
+use axum::http::{HeaderMap, StatusCode, HeaderValue};
 
-use crate::not_found;
+use crate::{error_page::ErrorPage, not_found};
 
-type StaticFile = Result<(StatusCode, HeaderMap, Vec<u8>), (StatusCode, Html<String>)>;
+type StaticFile = Result<(StatusCode, HeaderMap, Vec<u8>), ErrorPage>;
 
 macro_rules! static_file_accessor {
     ($n:ident, $mty:literal, $use_utf8:expr) => {
@@ -33,6 +33,23 @@ static_file_accessor!(png, "image/png", false);
 static_file_accessor!(jpg, "image/jpeg", false);
 static_file_accessor!(svg, "image/svg+xml", true);
 
+/// The stylesheet for highlighted code in posts. It's generated from the highlighting themes
+/// rather than living in `public/css`, so it can't fall out of sync with the highlighter.
+pub async fn highlight_css() -> StaticFile {
+    Ok((StatusCode::OK, content_type("text/css", true), blog_markdown::highlight::stylesheet().as_bytes().to_vec()))
+}
+
+fn content_type(media_type: &'static str, use_utf8: bool) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let content_type = if use_utf8 {
+        HeaderValue::from_str(format!("{media_type}; charset=utf-8").as_str()).expect("media type is valid header")
+    } else {
+        HeaderValue::from_str(media_type).expect("media type is valid header")
+    };
+    headers.insert("Content-Type", content_type);
+    headers
+}
+
 async fn accessor(
     root: PathBuf,
     required_ending: &'static str,
@@ -52,15 +69,8 @@ async fn accessor(
 
     let Ok(data) = std::fs::read(path.as_path()) else {
         trc::warn!("Failed to access file {:?}.", path.as_os_str());
-        return Err(not_found::page().await);
+        return Err(ErrorPage::new(StatusCode::NOT_FOUND).with_message("That file doesn't exist."));
     };
 
-    let mut headers = HeaderMap::new();
-    let content_type = if use_utf8 {
-        HeaderValue::from_str(format!("{media_type}; charset=utf-8").as_str()).expect("media type is valid header")
-    } else {
-        HeaderValue::from_str(media_type).expect("media type is valid header")
-    };
-    headers.insert("Content-Type", content_type);
-    Ok((StatusCode::OK, headers, data))
+    Ok((StatusCode::OK, content_type(media_type, use_utf8), data))
 }
@@ -0,0 +1,210 @@
+//! Tests of the render cache, on its own and behind the real routes, and of the pages it keeps.
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use blog_store::{MemoryPostStore, PostFields, PostSeries};
+use chrono::Utc;
+use shared_config::Cfg;
+use tower::ServiceExt;
+
+use crate::{app, cache::{self, Rendered, RenderCache}, error_page::ErrorPage, Site};
+
+const CFG: &str = r#"
+[servers.primary]
+resource_root = "."
+[servers.primary.address]
+domain = "example.com"
+ip = "127.0.0.1"
+port = 8080
+"#;
+
+fn site() -> &'static Site {
+    // Pages inline their critical CSS from here, same as `run.sh` does.
+    std::env::set_current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/../../resources")).unwrap();
+    let cfg: Cfg = config::Config::builder()
+        .add_source(config::File::from_str(CFG, config::FileFormat::Toml))
+        .build().unwrap()
+        .try_deserialize().unwrap();
+    Box::leak(Box::new(Site {
+        cfg: Box::leak(Box::new(cfg)),
+        posts: Box::new(MemoryPostStore::new()),
+        cache: RenderCache::default(),
+    }))
+}
+
+fn fields(slug: &str) -> PostFields {
+    PostFields {
+        slug: slug.to_owned(),
+        title: format!("About {slug}"),
+        body: "Some *words*.".to_owned(),
+        summary: None,
+        tags: vec!["testing".to_owned()],
+        series: None,
+        author: "Ben".to_owned(),
+    }
+}
+
+/// Renders through the cache, counting how many renders actually happened.
+async fn render(site: &'static Site, renders: &AtomicUsize) -> usize {
+    let rendered = site.cache.get_or_render(site.posts.as_ref(), "/page".to_owned(), || async {
+        let count = renders.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok::<_, ErrorPage>(Rendered { body: count.to_string(), updated: None })
+    }).await.unwrap();
+    rendered.body.parse().unwrap()
+}
+
+async fn get(site: &'static Site, uri: &str) -> (StatusCode, String) {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    let response = app(site).oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn the_cache_starts_over_when_posts_change() {
+    let site = site();
+    let renders = AtomicUsize::new(0);
+    assert_eq!(render(site, &renders).await, 1);
+    assert_eq!(render(site, &renders).await, 1);
+
+    site.posts.create(fields("first")).unwrap();
+    assert_eq!(render(site, &renders).await, 2);
+    assert_eq!(render(site, &renders).await, 2);
+    site.posts.publish("first", Utc::now()).unwrap();
+    assert_eq!(render(site, &renders).await, 3);
+}
+
+#[tokio::test]
+async fn scheduled_posts_clear_the_cache_when_they_go_live() {
+    let site = site();
+    tokio::spawn(cache::expire_scheduled(site));
+    let renders = AtomicUsize::new(0);
+    site.posts.create(fields("soon")).unwrap();
+    site.posts.publish("soon", Utc::now() + chrono::Duration::milliseconds(300)).unwrap();
+    // Noticing the new version is what wakes the expirer up to the schedule.
+    assert_eq!(render(site, &renders).await, 1);
+    assert_eq!(render(site, &renders).await, 1);
+
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    assert_eq!(render(site, &renders).await, 2);
+    assert_eq!(render(site, &renders).await, 2);
+}
+
+#[tokio::test]
+async fn pages_are_kept_by_the_query_parameters_they_use() {
+    let site = site();
+    site.posts.create(fields("first")).unwrap();
+    site.posts.publish("first", Utc::now()).unwrap();
+
+    for uri in ["/blog", "/blog?utm_source=a", "/blog?utm_source=b&limit=3", "/blog/post/first?ref=x"] {
+        assert_eq!(get(site, uri).await.0, StatusCode::OK, "{uri}");
+    }
+    assert_eq!(site.cache.kept(), 2);
+    let (status, tagged) = get(site, "/blog?tag=testing").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(tagged.contains("About first"));
+    assert_eq!(site.cache.kept(), 3);
+
+    for uri in ["/blog/feed.xml?a", "/blog/feed.xml?b", "/blog/feed.xml?mode=summary"] {
+        assert_eq!(get(site, uri).await.0, StatusCode::OK, "{uri}");
+    }
+    assert_eq!(site.cache.kept(), 5);
+
+    for uri in ["/blog/search?q=words", "/blog/search?q=other", "/blog/login?next=/blog/editor"] {
+        assert_eq!(get(site, uri).await.0, StatusCode::OK, "{uri}");
+    }
+    assert_eq!(site.cache.kept(), 5);
+}
+
+#[tokio::test]
+async fn tag_and_series_pages_only_show_published_posts() {
+    let site = site();
+    let in_series = |slug: &str, title: &str, tag: &str| PostFields {
+        series: Some(PostSeries { slug: "parts".to_owned(), title: title.to_owned(), part: 1 }),
+        tags: vec![tag.to_owned()],
+        ..fields(slug)
+    };
+    site.posts.create(in_series("first", "Series of parts", "testing")).unwrap();
+    site.posts.publish("first", Utc::now()).unwrap();
+    site.posts.create(in_series("unfinished", "Draft title", "secret")).unwrap();
+
+    let (status, tagged) = get(site, "/blog/tag/testing").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(tagged.contains("Posts tagged testing"), "{tagged}");
+    assert!(tagged.contains("About first"), "{tagged}");
+    let (status, secret) = get(site, "/blog/tag/secret").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!secret.contains("About unfinished"), "{secret}");
+
+    let (status, series) = get(site, "/blog/series/parts").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(series.contains("<title>Series of parts"), "{series}");
+    assert!(series.contains("About first"), "{series}");
+    assert!(!series.contains("About unfinished") && !series.contains("Draft title"), "{series}");
+    assert_eq!(get(site, "/blog/series/nothing").await.0, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn project_pages_fill_in_their_previews() {
+    let site = site();
+    let (status, listing) = get(site, "/projects").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(listing.contains(r#"href="/projects/ray-tracer""#), "{listing}");
+
+    let (status, project) = get(site, "/projects/ray-tracer").await;
+    assert_eq!(status, StatusCode::OK);
+    for tag in [
+        r#"<meta property="og:image" content="https://opengraph.githubassets.com/1/AlterionX/cs378hgraphics-raytracer">"#,
+        r#"<meta property="og:url" content="https://example.com/projects/ray-tracer">"#,
+        r#"<meta name="twitter:card" content="summary_large_image">"#,
+    ] {
+        assert!(project.contains(tag), "{tag} in {project}");
+    }
+    assert_eq!(get(site, "/projects/nothing").await.0, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn project_pages_describe_themselves_as_creative_works() {
+    let (_, project) = get(site(), "/projects/bifrost").await;
+    let start = project.find(r#""@type":"CreativeWork""#).expect("a CreativeWork is emitted");
+    let script_start = project[..start].rfind(r#"<script type="application/ld+json">"#).unwrap() + r#"<script type="application/ld+json">"#.len();
+    let script_end = script_start + project[script_start..].find("</script>").unwrap();
+    let work: serde_json::Value = serde_json::from_str(&project[script_start..script_end]).unwrap();
+    assert_eq!(work["name"], "Bifrost");
+    assert_eq!(work["url"], "https://example.com/projects/bifrost");
+    assert_eq!(work["sameAs"], serde_json::json!(["https://github.com/AlterionX/Bifrost"]));
+    assert_eq!(work["author"]["@type"], "Person");
+}
+
+#[tokio::test]
+async fn the_sitemap_lists_every_project() {
+    let (status, sitemap) = get(site(), "/sitemap.xml").await;
+    assert_eq!(status, StatusCode::OK);
+    for path in ["/projects", "/projects/ray-tracer", "/projects/thermal-lilette", "/projects/totality", "/projects/shatter", "/projects/bifrost"] {
+        assert!(sitemap.contains(format!("<loc>https://example.com{path}</loc>").as_str()), "{path} in {sitemap}");
+    }
+}
+
+#[tokio::test]
+async fn tag_feeds_link_to_themselves_with_the_tag_encoded() {
+    let site = site();
+    site.posts.create(PostFields { tags: vec!["rust & wasm".to_owned()], ..fields("tagged") }).unwrap();
+    site.posts.publish("tagged", Utc::now()).unwrap();
+
+    let (status, atom) = get(site, "/blog/tag/rust%20%26%20wasm/feed.xml").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(atom.contains("<id>https://example.com/blog/tag/rust%20%26%20wasm/feed.xml</id>"), "{atom}");
+    assert!(atom.contains(r#"href="https://example.com/blog/tag/rust%20%26%20wasm/feed.xml""#), "{atom}");
+    assert!(atom.contains("About tagged"), "{atom}");
+    let (status, rss) = get(site, "/blog/tag/rust%20%26%20wasm/rss.xml").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(rss.contains("https://example.com/blog/tag/rust%20%26%20wasm/rss.xml"), "{rss}");
+}